@@ -0,0 +1,127 @@
+use std::collections::HashMap;
+
+use crate::parser::GpuSample;
+
+/// SM utilization at or above this marks a GPU as busy
+const BUSY_SM_PCT: u32 = 10;
+/// SM utilization below this counts toward going idle
+const IDLE_SM_PCT: u32 = 5;
+/// Consecutive idle samples before a busy GPU is considered finished
+const IDLE_HOLD_SAMPLES: u32 = 5;
+/// GPU temperature considered critical
+pub const CRIT_TEMP_C: u32 = 85;
+/// Temperature must drop this far below critical before it can alert again
+const CRIT_HYSTERESIS_C: u32 = 5;
+
+/// Per-GPU alert state, so each transition rings once
+#[derive(Debug, Default)]
+struct GpuAlertState {
+    busy: bool,
+    idle_streak: u32,
+    critical: bool,
+}
+
+/// Detects idle/critical transitions and decides when to ring the terminal bell
+#[derive(Debug, Default)]
+pub struct BellAlerts {
+    on_idle: bool,
+    on_crit: bool,
+    gpus: HashMap<u32, GpuAlertState>,
+}
+
+impl BellAlerts {
+    pub fn new(on_idle: bool, on_crit: bool) -> Self {
+        Self {
+            on_idle,
+            on_crit,
+            gpus: HashMap::new(),
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.on_idle || self.on_crit
+    }
+
+    /// Feed the newest sample for a GPU; returns true when the bell should ring
+    pub fn check(&mut self, prev: Option<&GpuSample>, cur: &GpuSample) -> bool {
+        let state = self.gpus.entry(cur.gpu_idx).or_default();
+        let mut ring = false;
+
+        // Busy -> idle, once utilization has stayed low for a few samples
+        let sm = cur.sm_util.unwrap_or(0);
+        if sm >= BUSY_SM_PCT {
+            state.busy = true;
+            state.idle_streak = 0;
+        } else if sm < IDLE_SM_PCT {
+            state.idle_streak += 1;
+            if state.busy && state.idle_streak >= IDLE_HOLD_SAMPLES {
+                state.busy = false;
+                ring |= self.on_idle;
+            }
+        }
+
+        // Crossing into critical temperature, re-armed after cooling down
+        if let Some(temp) = cur.gpu_temp_c {
+            let prev_temp = prev.and_then(|p| p.gpu_temp_c).unwrap_or(0);
+            if !state.critical && temp >= CRIT_TEMP_C && prev_temp < CRIT_TEMP_C {
+                state.critical = true;
+                ring |= self.on_crit;
+            } else if state.critical && temp + CRIT_HYSTERESIS_C < CRIT_TEMP_C {
+                state.critical = false;
+            }
+        }
+
+        ring
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(sm: u32, temp: u32) -> GpuSample {
+        GpuSample {
+            sm_util: Some(sm),
+            gpu_temp_c: Some(temp),
+            ..Default::default()
+        }
+    }
+
+    fn feed(alerts: &mut BellAlerts, samples: &[GpuSample]) -> usize {
+        let mut rings = 0;
+        let mut prev: Option<&GpuSample> = None;
+        for s in samples {
+            if alerts.check(prev, s) {
+                rings += 1;
+            }
+            prev = Some(s);
+        }
+        rings
+    }
+
+    #[test]
+    fn test_idle_rings_once_after_hold() {
+        let mut alerts = BellAlerts::new(true, false);
+        let mut samples = vec![sample(100, 60); 3];
+        samples.extend(vec![sample(0, 50); 4]);
+        assert_eq!(feed(&mut alerts, &samples), 0);
+        // A brief dip back up resets the streak
+        samples.push(sample(90, 60));
+        samples.extend(vec![sample(0, 50); 10]);
+        assert_eq!(feed(&mut BellAlerts::new(true, false), &samples), 1);
+    }
+
+    #[test]
+    fn test_crit_debounced_by_hysteresis() {
+        let mut alerts = BellAlerts::new(false, true);
+        let samples = [
+            sample(100, 84),
+            sample(100, 86),
+            sample(100, 84),
+            sample(100, 86),
+            sample(100, 70),
+            sample(100, 90),
+        ];
+        assert_eq!(feed(&mut alerts, &samples), 2);
+    }
+}
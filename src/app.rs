@@ -1,26 +1,84 @@
 use anyhow::Result;
 use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use crossterm::terminal::SetTitle;
 use ratatui::{
+    buffer::Buffer,
     layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, Clear, Paragraph},
+    widgets::{Clear, Paragraph},
     DefaultTerminal, Frame,
 };
-use std::time::Duration;
-
-use crate::data::DataStore;
-use crate::process::{NvidiaMonitor, NvidiaMessage};
-use crate::ui::dashboard::render_dashboard;
-use crate::ui::charts::render_chart_view;
-use crate::ui::status::{render_status_bar, render_help_bar};
-use crate::ui::topology::render_topology_view;
+use std::collections::{HashSet, VecDeque};
+use std::io::Write;
+use std::time::{Duration, Instant, SystemTime};
+use tokio::sync::{mpsc, oneshot};
+
+use crate::alert::BellAlerts;
+use crate::config::{CommandDisplay, Config, GpuSort, Settings, TimeFormat};
+use crate::data::{reselect_gpu, DataStore, EnrichedProcess, ProcessEvent};
+use crate::events::{EventLog, LogLevel};
+use crate::output::{buffer_text, sample_json_line, summary_report, Snapshot};
+use crate::parser::{GpuInfo, GpuSample};
+use crate::process::{MonitorOptions, NvidiaMonitor, NvidiaMessage, Source, Target};
+use crate::ui::dashboard::{format_vram, gpu_table_width, render_dashboard, render_gpu_table};
+use crate::ui::diff::DiffHighlight;
+use crate::ui::charts::{render_chart_view, ChartMetric, CHART_SLOTS};
+use crate::ui::cluster::render_cluster_view;
+use crate::ui::status::{format_duration, render_status_bar, render_help_bar, StatusInfo};
+use crate::ui::rack::render_rack_view;
+use crate::ui::topology::{render_topology_view, topology_text};
+use crate::ui::users::render_users_view;
 use crate::ui::info::render_info_view;
+use crate::ui::log::render_log_view;
+use crate::ui::process_detail::render_process_detail;
+use crate::ui::recap::{render_pause_recap, PauseRecap};
+use crate::ui::{format_power, is_monochrome, panel, strip_colors};
+
+/// How long a status bar notice stays visible
+const NOTICE_DURATION: Duration = Duration::from_secs(5);
+
+/// How long an error stays in the status bar. The newest one stays past
+/// that while no sample has arrived since, so a dead feed keeps its reason
+const ERROR_DURATION: Duration = Duration::from_secs(15);
+/// Errors kept, and how many of the newest the status bar shows
+const MAX_ERRORS: usize = 8;
+const ERRORS_SHOWN: usize = 3;
+
+/// Most messages handled per monitor between two frames, so a burst of
+/// buffered dmon output is spread over a few frames instead of holding up
+/// drawing and key handling; the rest waits in the channel
+const MAX_MESSAGES_PER_FRAME: usize = 1000;
+
+/// Longest `--max-fps` goes without drawing when nothing changed
+const IDLE_FRAME_INTERVAL: Duration = Duration::from_secs(1);
+
+/// History windows `[` and `]` step through, in seconds
+const HISTORY_STEPS: [u64; 8] = [60, 120, 300, 600, 900, 1800, 3600, 7200];
+
+/// How long a `#` jump waits for a further digit before keys act as usual again
+const GPU_JUMP_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// How often `--set-title` refreshes the terminal title
+const TITLE_INTERVAL: Duration = Duration::from_secs(2);
+
+/// xterm title stack: save the title before `--set-title` changes it, and
+/// put it back on exit. Terminals without a stack ignore these
+pub const PUSH_TITLE: &str = "\x1b[22;0t";
+pub const POP_TITLE: &str = "\x1b[23;0t";
+
+/// Narrowest chart pane the Split view will draw beside the GPU table
+const SPLIT_MIN_CHART_WIDTH: u16 = 60;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ViewMode {
     Dashboard,
     Charts,
+    Cluster,
+    /// GPU table and the selected GPU's charts side by side
+    Split,
+    /// Every GPU as one dense line, for nodes with many GPUs
+    Rack,
 }
 
 impl ViewMode {
@@ -28,19 +86,30 @@ impl ViewMode {
         match self {
             Self::Dashboard => "Dashboard",
             Self::Charts => "Charts",
+            Self::Cluster => "Cluster",
+            Self::Split => "Split",
+            Self::Rack => "Rack",
         }
     }
 
     pub fn all() -> &'static [ViewMode] {
-        &[ViewMode::Dashboard, ViewMode::Charts]
+        &[ViewMode::Dashboard, ViewMode::Charts, ViewMode::Cluster, ViewMode::Split, ViewMode::Rack]
     }
 
     pub fn next(&self) -> Self {
         match self {
             Self::Dashboard => Self::Charts,
-            Self::Charts => Self::Dashboard,
+            Self::Charts => Self::Cluster,
+            Self::Cluster => Self::Split,
+            Self::Split => Self::Rack,
+            Self::Rack => Self::Dashboard,
         }
     }
+
+    /// Whether the chart keys (`c`, `m`, `M`) apply
+    pub fn has_charts(&self) -> bool {
+        matches!(self, Self::Charts | Self::Split)
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -48,41 +117,218 @@ pub enum Overlay {
     None,
     Info,
     Topology,
+    Process,
+    Recap,
+    Log,
+    Users,
+}
+
+/// Which table j/k navigates on the dashboard
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Focus {
+    Gpus,
+    Processes,
+}
+
+/// Stores frozen when the display was paused; monitoring carries on underneath
+struct Paused {
+    since: Instant,
+    data: DataStore,
+    remotes: Vec<DataStore>,
+}
+
+/// Outcome of a background `nvidia-smi -pl`: GPU, watts, and the refreshed
+/// GPU details on success
+type PowerLimitResult = (u32, f32, Result<Vec<GpuInfo>>);
+
+/// An additional `--host` beyond the primary one
+struct RemoteHost {
+    target: Target,
+    data: DataStore,
 }
 
 pub struct App {
+    config: Config,
+    target: Target,
     data: DataStore,
+    remotes: Vec<RemoteHost>,
+    alerts: BellAlerts,
     view_mode: ViewMode,
     overlay: Overlay,
     selected_gpu: usize,
-    error: Option<String>,
+    // GPU indices `selected_gpu` was last a position in
+    gpu_set: Vec<u32>,
+    focus: Focus,
+    selected_process: usize,
+    chart_metrics: [ChartMetric; CHART_SLOTS],
+    chart_slot: usize,
+    // Seconds ago the chart cursor reads values at; None while it's hidden
+    chart_cursor: Option<f64>,
+    // GPU (by index) whose charts are tiled beside the selected GPU's
+    compare_gpu: Option<u32>,
+    // Right edge of the charts' time axis; held between samples with --sync-charts
+    chart_now: Instant,
+    diff: Option<DiffHighlight>,
+    paused: Option<Paused>,
+    // Digits typed after `#` and when the last one came, while a jump to a
+    // GPU position is pending
+    gpu_jump: Option<(String, Instant)>,
+    // Watts typed after `L`, while a power limit prompt is open
+    power_prompt: Option<String>,
+    power_limit_result: Option<oneshot::Receiver<PowerLimitResult>>,
+    // Advances with every dmon sample to drive the status bar spinner
+    activity: usize,
+    recap: Option<PauseRecap>,
+    // Last carousel advance; None when not cycling (or paused by a keypress)
+    carousel_since: Option<Instant>,
+    // Shown with the wall clock in the status bar when --clock is set
+    hostname: Option<String>,
+    // CUDA_VISIBLE_DEVICES of our own environment, for the banner; unset
+    // for remote hosts, whose jobs don't share it
+    cuda_visible: Option<String>,
+    // Last `--set-title` update, to refresh it every TITLE_INTERVAL
+    title_at: Option<Instant>,
+    // Recent errors, oldest first, so intermittent ones don't flash by for a frame
+    errors: VecDeque<(Instant, String)>,
+    // Last GPU sample, to tell a recovered feed from a dead one
+    sample_at: Option<Instant>,
+    log: EventLog,
+    // Command lines run on the targets, with --verbose
+    commands: Option<mpsc::UnboundedReceiver<String>>,
+    // Command lines already logged, so the periodic queries show up once
+    commands_seen: HashSet<String>,
+    // GPUs whose fan stall has been logged, so it's reported once per episode
+    fan_stalled: HashSet<u32>,
+    /// GPUs whose retired VRAM pages were already warned about
+    memory_failing: HashSet<u32>,
+    /// `[Insufficient Permissions]` readings were already pointed out
+    permissions_noted: bool,
+    notice: Option<(String, Instant)>,
+    // Kept alive because X11 clipboards serve content from the owning client
+    clipboard: Option<arboard::Clipboard>,
+    /// `S` was pressed; the next frame drawn is saved to a file
+    screenshot_requested: bool,
     should_quit: bool,
 }
 
 impl App {
-    pub fn new(history_seconds: u64) -> Self {
+    pub fn new(config: Config) -> Self {
+        // With --verbose, every target reports what it runs to one channel
+        let (trace, commands) = match config.verbose {
+            true => {
+                let (tx, rx) = mpsc::unbounded_channel();
+                (Some(tx), Some(rx))
+            }
+            false => (None, None),
+        };
+        let traced = |target: Target| match &trace {
+            Some(tx) => target.traced(tx.clone()),
+            None => target,
+        };
+
+        // The first --host (or the local machine) drives every view; any
+        // further hosts only feed the cluster view
+        let target = traced(config.hosts.first().map(|h| Target::remote(h)).unwrap_or_else(Target::local));
+        let remotes = config
+            .hosts
+            .iter()
+            .skip(1)
+            .map(|h| RemoteHost {
+                target: traced(Target::remote(h)),
+                data: new_store(&config),
+            })
+            .collect();
+
+        // Say which GPUs the environment scoped the display to, or why it didn't
+        let mut log = EventLog::default();
+        if let Some(warning) = &config.gpu_filter_warning {
+            if config.headless {
+                eprintln!("nvidiagpu_top: {}", warning);
+            }
+            log.push(LogLevel::Warn, warning.clone());
+        }
+        if let Some(filter) = config.gpu_filter.as_ref().filter(|f| f.source != "--gpus") {
+            let indices: Vec<String> = filter.indices.iter().map(|i| i.to_string()).collect();
+            log.info(format!("Showing GPUs {} from {}", indices.join(","), filter.source));
+        }
+        let notice = config.gpu_filter_warning.clone().map(|w| (w, Instant::now()));
+
         Self {
-            data: DataStore::new(history_seconds),
+            target,
+            data: new_store(&config),
+            remotes,
+            alerts: BellAlerts::new(config.bell_on_idle, config.bell_on_crit),
             view_mode: ViewMode::Dashboard,
             overlay: Overlay::None,
             selected_gpu: 0,
-            error: None,
+            gpu_set: Vec::new(),
+            focus: Focus::Gpus,
+            selected_process: 0,
+            chart_metrics: ChartMetric::defaults(),
+            chart_slot: 0,
+            chart_cursor: None,
+            compare_gpu: None,
+            chart_now: Instant::now(),
+            diff: config.highlight_changes.then(DiffHighlight::default),
+            paused: None,
+            gpu_jump: None,
+            power_prompt: None,
+            power_limit_result: None,
+            activity: 0,
+            recap: None,
+            carousel_since: config.carousel.map(|_| Instant::now()),
+            hostname: config.clock.then(|| match config.hosts.first() {
+                Some(host) => host.clone(),
+                None => local_hostname(),
+            }),
+            title_at: None,
+            cuda_visible: if config.hosts.is_empty() { std::env::var("CUDA_VISIBLE_DEVICES").ok() } else { None },
+            errors: VecDeque::new(),
+            sample_at: None,
+            log,
+            commands,
+            commands_seen: HashSet::new(),
+            fan_stalled: HashSet::new(),
+            memory_failing: HashSet::new(),
+            permissions_noted: false,
+            notice,
+            clipboard: None,
+            screenshot_requested: false,
             should_quit: false,
+            config,
         }
     }
 
-    pub async fn run(mut self, mut terminal: DefaultTerminal) -> Result<()> {
-        // Query topology once at startup
-        match NvidiaMonitor::query_topology().await {
-            Ok(topo) => self.data.set_topology(topo),
-            Err(e) => self.error = Some(format!("Topology: {}", e)),
+    pub async fn run(&mut self, mut terminal: DefaultTerminal) -> Result<()> {
+        // Query topology once at startup; a replay has no nvidia-smi to ask
+        if self.config.replay.is_none() {
+            match NvidiaMonitor::query_topology(&self.target).await {
+                Ok(topo) => self.data.set_topology(topo),
+                Err(e) => self.push_error(format!("Topology: {}", e)),
+            }
+            if let Ok(version) = NvidiaMonitor::query_cuda_version(&self.target).await {
+                self.data.set_cuda_version(version);
+            }
+        }
+
+        // Monitors for additional hosts; a failing host only reports an error
+        let mut remote_monitors = Vec::new();
+        let targets: Vec<Target> = self.remotes.iter().map(|r| r.target.clone()).collect();
+        for target in targets {
+            match NvidiaMonitor::spawn(Source::Live(target), MonitorOptions::from_config(&self.config)).await {
+                Ok((monitor, rx)) => remote_monitors.push(Some((monitor, rx))),
+                Err(e) => {
+                    self.push_error(e.to_string());
+                    remote_monitors.push(None);
+                }
+            }
         }
 
         // Spawn all monitoring processes
-        let (_monitor, mut rx) = match NvidiaMonitor::spawn().await {
+        let (_monitor, mut rx) = match NvidiaMonitor::spawn(self.source(), MonitorOptions::from_config(&self.config)).await {
             Ok((m, r)) => (m, r),
             Err(e) => {
-                self.error = Some(e.to_string());
+                self.push_error(e.to_string());
                 while !self.should_quit {
                     terminal.draw(|frame| self.render(frame))?;
                     if self.handle_events()? {
@@ -93,41 +339,58 @@ impl App {
             }
         };
 
+        // Whether anything (a key, a resize, monitor output) may have
+        // changed the screen since the last frame
+        let mut dirty = true;
+        let mut last_frame: Option<Instant> = None;
         loop {
-            terminal.draw(|frame| self.render(frame))?;
+            if self.frame_due(last_frame, dirty) {
+                let frame = terminal.draw(|frame| self.render(frame))?;
+                if std::mem::take(&mut self.screenshot_requested) {
+                    self.save_screenshot(frame.buffer);
+                }
+                last_frame = Some(Instant::now());
+                dirty = false;
+            }
+            if self.config.set_title {
+                self.update_title(&mut terminal)?;
+            }
 
             if event::poll(Duration::from_millis(100))? {
+                dirty = true;
                 if self.handle_events()? {
                     break;
                 }
             }
+            self.advance_carousel();
+            self.check_power_limit_result();
 
-            while let Ok(msg) = rx.try_recv() {
-                match msg {
-                    NvidiaMessage::GpuSample(sample) => {
-                        self.data.add_sample(sample);
-                        self.error = None;
-                    }
-                    NvidiaMessage::ProcessSample(sample) => {
-                        self.data.add_process_sample(sample);
-                    }
-                    NvidiaMessage::GpuInfo(info) => {
-                        self.data.update_gpu_info(info);
-                    }
-                    NvidiaMessage::ComputeApps(apps) => {
-                        self.data.update_compute_apps(apps);
-                    }
-                    NvidiaMessage::ProcessSystemInfo(infos) => {
-                        self.data.update_process_sys_info(infos);
-                    }
-                    NvidiaMessage::Error(e) => {
-                        self.error = Some(e);
-                    }
-                    NvidiaMessage::Exited(which) => {
-                        self.error = Some(format!("{} exited", which));
+            let mut has_new_data = false;
+            for _ in 0..MAX_MESSAGES_PER_FRAME {
+                let Ok(msg) = rx.try_recv() else { break };
+                has_new_data |= matches!(msg, NvidiaMessage::GpuSample(_));
+                dirty = true;
+                self.handle_message(msg);
+            }
+            self.follow_gpu_set();
+            self.advance_chart_time(has_new_data);
+
+            let mut remote_errors = Vec::new();
+            for (remote, monitor) in self.remotes.iter_mut().zip(&mut remote_monitors) {
+                if let Some((_, remote_rx)) = monitor {
+                    for _ in 0..MAX_MESSAGES_PER_FRAME {
+                        let Ok(msg) = remote_rx.try_recv() else { break };
+                        dirty = true;
+                        if let Some(e) = update_store(&mut remote.data, msg) {
+                            remote_errors.push(format!("{}: {}", remote.target.name(), e));
+                        }
                     }
                 }
             }
+            for e in remote_errors {
+                self.push_error(e);
+            }
+            dirty |= self.log_commands();
 
             if self.should_quit {
                 break;
@@ -137,9 +400,641 @@ impl App {
         Ok(())
     }
 
+    /// Run without a terminal UI, streaming samples to stdout until the
+    /// monitors exit or the process is interrupted
+    pub async fn run_headless(&mut self) -> Result<()> {
+        let spawned = NvidiaMonitor::spawn(self.source(), MonitorOptions::from_config(&self.config)).await;
+        // Before bailing out, so a failed start shows what was tried
+        self.log_commands();
+        let (_monitor, mut rx) = spawned?;
+        let shutdown = shutdown_signal();
+        tokio::pin!(shutdown);
+
+        loop {
+            tokio::select! {
+                msg = rx.recv() => match msg {
+                    Some(msg) => self.handle_message(msg),
+                    None => break,
+                },
+                Some(line) = recv_command(&mut self.commands) => {
+                    self.log_command(line);
+                }
+                // Returning drops the monitor, which kills the nvidia-smi children
+                _ = &mut shutdown => break,
+            }
+
+            if self.should_quit {
+                break;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Log command lines the targets ran since the last call (`--verbose`);
+    /// true if any were new
+    fn log_commands(&mut self) -> bool {
+        let mut lines = Vec::new();
+        if let Some(rx) = &mut self.commands {
+            while let Ok(line) = rx.try_recv() {
+                lines.push(line);
+            }
+        }
+        let mut logged = false;
+        for line in lines {
+            logged |= self.log_command(line);
+        }
+        logged
+    }
+
+    /// Log a command line the first time it's run; true if it was new
+    fn log_command(&mut self, line: String) -> bool {
+        if !self.commands_seen.insert(line.clone()) {
+            return false;
+        }
+        if self.config.headless {
+            eprintln!("nvidiagpu_top: $ {}", line);
+        }
+        self.log.info(format!("$ {}", line));
+        true
+    }
+
+    fn handle_message(&mut self, msg: NvidiaMessage) {
+        match msg {
+            NvidiaMessage::GpuSample(sample) => {
+                self.activity = self.activity.wrapping_add(1);
+                if self.config.stream_json {
+                    self.stream_sample(&sample);
+                }
+                if self.alerts.enabled() {
+                    let prev = self.data.get_gpu(sample.gpu_idx).and_then(|h| h.latest());
+                    if self.alerts.check(prev, &sample) {
+                        self.ring_bell();
+                    }
+                }
+                self.data.add_sample(sample);
+                self.sample_at = Some(Instant::now());
+            }
+            NvidiaMessage::ProcessSample(sample) => {
+                self.data.add_process_sample(sample);
+            }
+            NvidiaMessage::GpuInfo(info) => {
+                self.log_fan_stalls(&info);
+                self.log_retired_pages(&info);
+                if !self.permissions_noted && info.iter().any(|g| g.insufficient_permissions) {
+                    self.permissions_noted = true;
+                    let note = "Some metrics need elevated permissions";
+                    self.log.info(format!("{} (nvidia-smi reported [Insufficient Permissions])", note));
+                    self.notice = Some((note.into(), Instant::now()));
+                }
+                self.data.update_gpu_info(info);
+            }
+            NvidiaMessage::ComputeApps(apps) => {
+                for event in self.data.update_compute_apps(apps) {
+                    self.log_process_event(event);
+                }
+                let max_process = self.process_rows().len().saturating_sub(1);
+                self.selected_process = self.selected_process.min(max_process);
+            }
+            NvidiaMessage::ProcessSystemInfo(infos) => {
+                self.data.update_process_sys_info(infos);
+            }
+            NvidiaMessage::EncoderSessions(sessions) => {
+                self.data.update_encoder_sessions(sessions);
+            }
+            NvidiaMessage::XidEvents(events) => {
+                for event in self.data.update_xid_events(events) {
+                    let idx = self.data.gpu_index_for_pci(&event.pci);
+                    if idx.is_some_and(|idx| !self.data.shows_gpu(idx)) {
+                        continue;
+                    }
+                    let gpu = idx.map(|idx| format!("GPU {}", idx)).unwrap_or_else(|| format!("PCI {}", event.pci));
+                    self.log.push(LogLevel::Warn, format!("{} Xid {}: {}", gpu, event.code, event.detail));
+                }
+            }
+            NvidiaMessage::Error(e) => {
+                if self.config.headless {
+                    eprintln!("nvidiagpu_top: {}", e);
+                }
+                self.log.push(LogLevel::Warn, e.clone());
+                self.push_error(e);
+            }
+            NvidiaMessage::Exited(which) => {
+                if self.config.headless {
+                    eprintln!("nvidiagpu_top: {} exited", which);
+                }
+                self.log.push(LogLevel::Warn, format!("{} exited", which));
+                self.push_error(format!("{} exited", which));
+            }
+        }
+    }
+
+    /// Warn once when a GPU's fan stalls while hot, and note when it recovers
+    fn log_fan_stalls(&mut self, infos: &[GpuInfo]) {
+        for info in infos.iter().filter(|g| self.data.shows_gpu(g.index)) {
+            let stalled = info.fan_stalled();
+            if stalled == self.fan_stalled.contains(&info.index) {
+                continue;
+            }
+            if stalled {
+                self.fan_stalled.insert(info.index);
+                self.log.push(
+                    LogLevel::Warn,
+                    format!(
+                        "GPU {} fan at {}% while at {}°C - possible fan failure",
+                        info.index,
+                        info.fan_speed_pct.unwrap_or(0),
+                        info.temperature_c.unwrap_or(0)
+                    ),
+                );
+            } else {
+                self.fan_stalled.remove(&info.index);
+                self.log.info(format!("GPU {} fan recovered", info.index));
+            }
+        }
+    }
+
+    /// Warn once when a GPU starts retiring VRAM pages for double-bit ECC
+    /// errors (or has a retirement pending), a sign of failing HBM
+    fn log_retired_pages(&mut self, infos: &[GpuInfo]) {
+        for info in infos.iter().filter(|g| self.data.shows_gpu(g.index)) {
+            if !info.memory_failing() || !self.memory_failing.insert(info.index) {
+                continue;
+            }
+            let pending = if info.retired_pages_pending { ", retirement pending" } else { "" };
+            self.log.push(
+                LogLevel::Warn,
+                format!(
+                    "GPU {} has {} VRAM pages retired for double-bit ECC errors{} - memory may be failing",
+                    info.index,
+                    info.retired_pages_dbe.unwrap_or(0),
+                    pending
+                ),
+            );
+        }
+    }
+
+    /// Record a process appearing or going away, e.g.
+    /// "PID 12345 (python) started on GPU 0 using 2.1 GiB"
+    fn log_process_event(&mut self, event: ProcessEvent) {
+        let (app, verb) = match event {
+            ProcessEvent::Started(app) => (app, "started"),
+            ProcessEvent::Ended(app) => (app, "ended"),
+        };
+        let command = app.name.split('/').next_back().unwrap_or(&app.name);
+        let gpu = self
+            .data
+            .gpu_index_for_uuid(&app.gpu_uuid)
+            .map(|idx| idx.to_string())
+            .unwrap_or_else(|| app.gpu_uuid.clone());
+        self.log.info(format!(
+            "PID {} ({}) {} on GPU {} using {}",
+            app.pid,
+            command,
+            verb,
+            gpu,
+            format_vram(app.vram_used_mib)
+        ));
+    }
+
+    /// Write one JSON line for a sample; a closed stdout (e.g. `| head`) ends the run
+    fn stream_sample(&mut self, sample: &GpuSample) {
+        let line = sample_json_line(sample, SystemTime::now());
+        let mut stdout = std::io::stdout().lock();
+        if writeln!(stdout, "{}", line).and_then(|_| stdout.flush()).is_err() {
+            self.should_quit = true;
+        }
+    }
+
+    /// Emit a terminal bell; stderr in headless mode so stdout stays clean
+    fn ring_bell(&self) {
+        if self.config.headless {
+            let mut stderr = std::io::stderr();
+            let _ = stderr.write_all(b"\x07").and_then(|_| stderr.flush());
+        } else {
+            let mut stdout = std::io::stdout();
+            let _ = stdout.write_all(b"\x07").and_then(|_| stdout.flush());
+        }
+    }
+
+    /// Process table rows with the current merge and idle settings
+    fn process_rows(&self) -> Vec<EnrichedProcess> {
+        self.data.get_process_rows(self.config.merge_pids, self.config.hide_idle)
+    }
+
+    /// Keep the selection on the same GPU when GPUs appear or disappear,
+    /// clamped to the GPUs that remain
+    fn follow_gpu_set(&mut self) {
+        let indices = self.data.gpu_indices();
+        if indices != self.gpu_set {
+            self.selected_gpu = reselect_gpu(&self.gpu_set, &indices, self.selected_gpu);
+            self.gpu_set = indices;
+        }
+    }
+
+    /// Move the GPU selection one row in the GPU table's (possibly sorted)
+    /// order; `selected_gpu` itself stays a position in index order
+    fn step_gpu(&mut self, step: fn(usize, usize, bool) -> usize) {
+        let all = self.data.gpu_indices();
+        let order = self.data.sorted_gpu_indices(self.config.gpu_sort);
+        let row = all
+            .get(self.selected_gpu)
+            .and_then(|idx| order.iter().position(|o| o == idx))
+            .unwrap_or(0);
+        let next = step(row, order.len(), self.config.wrap_nav);
+        if let Some(pos) = order.get(next).and_then(|idx| all.iter().position(|a| a == idx)) {
+            self.selected_gpu = pos;
+        }
+    }
+
+    /// Step the GPU compared beside the selected one in the charts (J/K),
+    /// in index order. It starts next to the selected GPU and turns off
+    /// when it comes back around to it
+    fn step_compare_gpu(&mut self, forward: bool) {
+        let all = self.data.gpu_indices();
+        let count = all.len();
+        if count < 2 {
+            return;
+        }
+        let from = self
+            .compare_gpu
+            .and_then(|idx| all.iter().position(|&i| i == idx))
+            .unwrap_or(self.selected_gpu.min(count - 1));
+        let next = if forward { (from + 1) % count } else { (from + count - 1) % count };
+        self.compare_gpu = (next != self.selected_gpu).then_some(all[next]);
+        let notice = match self.compare_gpu {
+            Some(idx) => format!("Comparing with GPU {} (J/K to change)", idx),
+            None => "Comparison off".to_string(),
+        };
+        self.notice = Some((notice, Instant::now()));
+    }
+
+    /// Whether to draw on this pass of the run loop: always, unless
+    /// `--max-fps` is set. Then only once something changed and the last
+    /// frame is a frame interval old, plus every IDLE_FRAME_INTERVAL so
+    /// clocks, spinners and expiring notices still move
+    fn frame_due(&self, last_frame: Option<Instant>, dirty: bool) -> bool {
+        let (Some(min_interval), Some(last)) = (self.config.min_frame_interval, last_frame) else {
+            return true;
+        };
+        let since = last.elapsed();
+        since >= IDLE_FRAME_INTERVAL || (dirty && since >= min_interval)
+    }
+
+    /// Record an error for the status bar; a repeat of the newest one
+    /// only refreshes its time
+    fn push_error(&mut self, error: String) {
+        let now = Instant::now();
+        match self.errors.back_mut() {
+            Some((at, last)) if *last == error => *at = now,
+            _ => self.errors.push_back((now, error)),
+        }
+        if self.errors.len() > MAX_ERRORS {
+            self.errors.pop_front();
+        }
+    }
+
+    /// Errors for the status bar, newest first: those under ERROR_DURATION
+    /// old, plus the newest while no sample has come in after it
+    fn recent_errors(&self) -> Vec<&str> {
+        let newest = self.errors.len().saturating_sub(1);
+        self.errors
+            .iter()
+            .enumerate()
+            .rev()
+            .filter(|(i, (at, _))| {
+                at.elapsed() < ERROR_DURATION || (*i == newest && self.sample_at.is_none_or(|s| s < *at))
+            })
+            .map(|(_, (_, e))| e.as_str())
+            .take(ERRORS_SHOWN)
+            .collect()
+    }
+
+    /// Widen or narrow the history window to the next step, for every host.
+    /// Narrowing drops samples; widening only helps from now on
+    fn step_history(&mut self, wider: bool) {
+        let current = self.config.history_seconds;
+        let next = if wider {
+            HISTORY_STEPS.iter().copied().find(|&s| s > current)
+        } else {
+            HISTORY_STEPS.iter().rev().copied().find(|&s| s < current)
+        };
+        let Some(seconds) = next else {
+            let edge = if wider { "longest" } else { "shortest" };
+            self.notice = Some((format!("History: {} ({})", format_duration(Duration::from_secs(current)), edge), Instant::now()));
+            return;
+        };
+        self.config.history_seconds = seconds;
+        self.data.set_history(seconds);
+        for remote in &mut self.remotes {
+            remote.data.set_history(seconds);
+        }
+        self.notice = Some((format!("History: {}", format_duration(Duration::from_secs(seconds))), Instant::now()));
+    }
+
+    /// Move the charts' "now". Free-running charts follow the clock every
+    /// frame; synced ones jump only when a sample arrives (and not while
+    /// paused), so lines hold still instead of creeping between samples
+    fn advance_chart_time(&mut self, has_new_data: bool) {
+        if !self.config.sync_charts || (has_new_data && self.paused.is_none()) {
+            self.chart_now = Instant::now();
+        }
+    }
+
+    /// Move the chart cursor `dir` steps back in time (negative: forward),
+    /// a 40th of the chart per step. It appears at "now" and hides again
+    /// when moved past it
+    fn move_chart_cursor(&mut self, dir: f64) {
+        let idx = self.data.gpu_indices().get(self.selected_gpu).copied().unwrap_or(0);
+        let span = self.data.get_gpu(idx).map(|h| h.time_span(self.chart_now)).unwrap_or_default().max(60.0);
+        let step = (span / 40.0).max(1.0);
+        self.chart_cursor = match self.chart_cursor {
+            None if dir < 0.0 => None,
+            None => Some(0.0),
+            Some(c) if c + dir * step < 0.0 => None,
+            Some(c) => Some((c + dir * step).min(span)),
+        };
+    }
+
+    /// e.g. "CUDA_VISIBLE_DEVICES=3,1: CUDA device 0 is GPU 3, 1 is GPU 1
+    /// (all 8 GPUs shown here)", since frameworks renumber what they see
+    fn cuda_banner(&self, data: &DataStore) -> Option<String> {
+        let spec = self.cuda_visible.as_deref()?;
+        let total = data.gpu_indices().len();
+        if total == 0 {
+            return None;
+        }
+        let visible = data.cuda_visible_gpus(spec);
+        let mapping = match visible.split_first() {
+            None => "CUDA sees no GPUs".to_string(),
+            Some((first, rest)) => {
+                let mut mapping = format!("CUDA device 0 is GPU {}", first);
+                for (i, gpu) in rest.iter().enumerate() {
+                    mapping.push_str(&format!(", {} is GPU {}", i + 1, gpu));
+                }
+                mapping
+            }
+        };
+        let shown = if self.config.gpu_filter.is_some() { "" } else { "all " };
+        Some(format!(" CUDA_VISIBLE_DEVICES={}: {} ({}{} GPUs shown here)", spec, mapping, shown, total))
+    }
+
+    /// Write a drawn frame to `nvidiagpu_top-<date>-<time>.txt` in the
+    /// working directory, ANSI-colored unless `--screenshot-plain`
+    fn save_screenshot(&mut self, buffer: &Buffer) {
+        let path = format!("nvidiagpu_top-{}.txt", chrono::Local::now().format("%Y%m%d-%H%M%S"));
+        let text = buffer_text(buffer, !self.config.screenshot_plain);
+        let notice = match std::fs::write(&path, text) {
+            Ok(()) => format!("Screenshot saved to {}", path),
+            Err(e) => format!("Screenshot not saved: {}: {}", path, e),
+        };
+        self.notice = Some((notice, Instant::now()));
+    }
+
+    /// Write the topology matrix, affinities and legend as plain text
+    fn export_topology(&mut self) {
+        let Some(topo) = self.data.get_topology().filter(|t| !t.matrix.is_empty()) else {
+            self.notice = Some(("No topology to export".into(), Instant::now()));
+            return;
+        };
+        let path = format!("nvidiagpu_top-topology-{}.txt", chrono::Local::now().format("%Y%m%d-%H%M%S"));
+        let notice = match std::fs::write(&path, topology_text(topo)) {
+            Ok(()) => format!("Topology saved to {}", path),
+            Err(e) => format!("Topology not saved: {}: {}", path, e),
+        };
+        self.notice = Some((notice, Instant::now()));
+    }
+
+    /// Set the terminal title to a GPU summary every TITLE_INTERVAL. Written
+    /// through the backend between frames, so it never lands inside one
+    fn update_title(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        if self.title_at.is_some_and(|at| at.elapsed() < TITLE_INTERVAL) {
+            return Ok(());
+        }
+        self.title_at = Some(Instant::now());
+        crossterm::execute!(terminal.backend_mut(), SetTitle(self.status_title()))?;
+        Ok(())
+    }
+
+    /// e.g. "nvidiagpu_top: 3 GPUs, 412W, max 78°C", with the host when remote
+    fn status_title(&self) -> String {
+        let mut title = String::from("nvidiagpu_top");
+        if let Some(host) = self.config.hosts.first() {
+            title = format!("{}@{}", title, host);
+        }
+        let latest: Vec<&GpuSample> = self
+            .data
+            .gpu_indices()
+            .iter()
+            .filter_map(|&idx| self.data.get_gpu(idx).and_then(|h| h.latest()))
+            .collect();
+        if latest.is_empty() {
+            return format!("{}: waiting for data", title);
+        }
+
+        let plural = if latest.len() == 1 { "" } else { "s" };
+        let power: u32 = latest.iter().filter_map(|s| s.power_w).sum();
+        let mut title = format!("{}: {} GPU{}, {}W", title, latest.len(), plural, power);
+        if let Some(temp) = latest.iter().filter_map(|s| s.gpu_temp_c).max() {
+            title.push_str(&format!(", max {}°C", temp));
+        }
+        title
+    }
+
+    /// Show the next GPU once the `--carousel` interval has passed
+    fn advance_carousel(&mut self) {
+        let (Some(interval), Some(since)) = (self.config.carousel, self.carousel_since) else {
+            return;
+        };
+        let count = self.data.gpu_indices().len();
+        if since.elapsed() >= interval && count > 0 {
+            self.selected_gpu = (self.selected_gpu + 1) % count;
+            self.carousel_since = Some(Instant::now());
+        }
+    }
+
+    /// Recorded output with `--replay`, otherwise the primary host
+    fn source(&self) -> Source {
+        match &self.config.replay {
+            Some(path) => Source::Replay(path.clone()),
+            None => Source::Live(self.target.clone()),
+        }
+    }
+
+    /// Per-GPU report of the whole run, for `--summary`
+    pub fn summary(&self) -> String {
+        summary_report(&self.data)
+    }
+
+    /// Current GPU and process state, for `--dump-json`
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot::from_store(&self.data, SystemTime::now())
+    }
+
+    /// Copy the selected process's PID (when focused) or the selected GPU's
+    /// UUID, showing it in the status bar when no clipboard is reachable
+    fn copy_selection(&mut self) {
+        let (label, value) = match self.focus {
+            Focus::Processes => {
+                match self.process_rows().get(self.selected_process) {
+                    Some(p) => ("PID", p.pid.to_string()),
+                    None => return,
+                }
+            }
+            Focus::Gpus => {
+                let gpu_idx = self.data.gpu_indices().get(self.selected_gpu).copied().unwrap_or(0);
+                match self.data.get_gpu_info(gpu_idx) {
+                    Some(info) => ("UUID", info.uuid.clone()),
+                    None => return,
+                }
+            }
+        };
+
+        if self.clipboard.is_none() {
+            self.clipboard = arboard::Clipboard::new().ok();
+        }
+        let copied = self
+            .clipboard
+            .as_mut()
+            .is_some_and(|c| c.set_text(value.clone()).is_ok());
+
+        let msg = if copied {
+            format!("Copied {} {}", label, value)
+        } else {
+            format!("{}: {} (no clipboard)", label, value)
+        };
+        self.notice = Some((msg, Instant::now()));
+    }
+
+    /// Select the GPU at the position typed after `#`, clamped to the last one
+    fn jump_to_gpu(&mut self, digits: &str) {
+        let max_gpu = self.data.gpu_indices().len().saturating_sub(1);
+        self.selected_gpu = digits.parse::<usize>().unwrap_or(usize::MAX).min(max_gpu);
+        self.focus = Focus::Gpus;
+        self.notice = Some((format!("Jump to GPU #{}", digits), Instant::now()));
+    }
+
+    /// GPU index (as nvidia-smi numbers it) of the selected GPU
+    fn selected_gpu_idx(&self) -> Option<u32> {
+        self.data.gpu_indices().get(self.selected_gpu).copied()
+    }
+
+    /// Open the power limit prompt for the selected GPU
+    fn open_power_prompt(&mut self) {
+        if !self.config.allow_control {
+            self.notice = Some(("Changing the power limit needs --allow-control".into(), Instant::now()));
+            return;
+        }
+        if self.power_limit_result.is_some() {
+            self.notice = Some(("A power limit change is still running".into(), Instant::now()));
+            return;
+        }
+        self.power_prompt = Some(String::new());
+        self.show_power_prompt("");
+    }
+
+    fn show_power_prompt(&mut self, input: &str) {
+        let info = self.selected_gpu_idx().and_then(|idx| self.data.get_gpu_info(idx));
+        let range = match info.map(|i| (i.power_min_limit_w, i.power_max_limit_w)) {
+            Some((Some(min), Some(max))) => format!(" ({:.0}-{:.0} W)", min, max),
+            _ => String::new(),
+        };
+        let gpu = self.selected_gpu_idx().map(|idx| idx.to_string()).unwrap_or("?".into());
+        self.notice = Some((format!("Power limit for GPU {}{}: {}_ W", gpu, range, input), Instant::now()));
+    }
+
+    /// Validate the typed watts and run `nvidia-smi -pl` in the background
+    fn submit_power_limit(&mut self, input: &str) {
+        let Some(idx) = self.selected_gpu_idx() else { return };
+        let watts = match input.parse::<f32>() {
+            Ok(w) if w > 0.0 => w,
+            _ => {
+                self.notice = Some((format!("Not a power limit: {:?}", input), Instant::now()));
+                return;
+            }
+        };
+        if let Some(Err(e)) = self.data.get_gpu_info(idx).map(|info| info.check_power_limit(watts)) {
+            self.notice = Some((e, Instant::now()));
+            return;
+        }
+
+        let target = self.target.clone();
+        let format = self.config.query_format;
+        let (tx, rx) = oneshot::channel();
+        tokio::spawn(async move {
+            let result = match NvidiaMonitor::set_power_limit(&target, idx, watts).await {
+                Ok(()) => NvidiaMonitor::query_gpu_info(&target, format).await,
+                Err(e) => Err(e),
+            };
+            let _ = tx.send((idx, watts, result));
+        });
+        self.power_limit_result = Some(rx);
+        self.notice = Some((format!("Setting GPU {} power limit to {} W...", idx, watts), Instant::now()));
+    }
+
+    /// Report a finished `nvidia-smi -pl` and show the new limit right away
+    fn check_power_limit_result(&mut self) {
+        let Some(rx) = &mut self.power_limit_result else { return };
+        let (idx, watts, result) = match rx.try_recv() {
+            Ok(done) => done,
+            Err(oneshot::error::TryRecvError::Empty) => return,
+            Err(oneshot::error::TryRecvError::Closed) => {
+                self.power_limit_result = None;
+                return;
+            }
+        };
+        self.power_limit_result = None;
+
+        let message = match result {
+            Ok(mut info) => {
+                // BAR1 comes from a slower query; keep the last reading
+                for gpu in info.iter_mut().filter(|gpu| gpu.bar1_total_mib.is_none()) {
+                    if let Some(old) = self.data.get_gpu_info(gpu.index) {
+                        gpu.bar1_total_mib = old.bar1_total_mib;
+                        gpu.bar1_used_mib = old.bar1_used_mib;
+                    }
+                }
+                self.data.update_gpu_info(info);
+                let message = format!("GPU {} power limit set to {} W", idx, watts);
+                self.log.info(message.clone());
+                message
+            }
+            Err(e) => {
+                let message = format!("GPU {} power limit not set: {}", idx, e);
+                self.log.push(LogLevel::Warn, message.clone());
+                message
+            }
+        };
+        self.notice = Some((message, Instant::now()));
+    }
+
+    /// Freeze the display, or unfreeze it and summarize what was missed
+    fn toggle_pause(&mut self) {
+        match self.paused.take() {
+            Some(paused) => {
+                self.recap = Some(PauseRecap::new(&paused.data, &self.data, paused.since));
+                self.overlay = Overlay::Recap;
+            }
+            None => {
+                self.paused = Some(Paused {
+                    since: Instant::now(),
+                    data: self.data.clone(),
+                    remotes: self.remotes.iter().map(|r| r.data.clone()).collect(),
+                });
+            }
+        }
+    }
+
     fn handle_events(&mut self) -> Result<bool> {
         if let Event::Key(key) = event::read()? {
             if key.kind == KeyEventKind::Press {
+                // Any key but quit stops the carousel and shows the normal views
+                if self.carousel_since.is_some() && key.code != KeyCode::Char('q') {
+                    self.carousel_since = None;
+                    self.notice = Some(("Carousel paused, C to resume".into(), Instant::now()));
+                    return Ok(false);
+                }
+
                 // If overlay is open, Esc/Enter/same key closes it
                 if self.overlay != Overlay::None {
                     match key.code {
@@ -160,6 +1055,22 @@ impl App {
                                 Overlay::Topology
                             };
                         }
+                        KeyCode::Char('l') => {
+                            self.overlay = if self.overlay == Overlay::Log {
+                                Overlay::None
+                            } else {
+                                Overlay::Log
+                            };
+                        }
+                        KeyCode::Char('u') => {
+                            self.overlay = if self.overlay == Overlay::Users {
+                                Overlay::None
+                            } else {
+                                Overlay::Users
+                            };
+                        }
+                        KeyCode::Char('S') => self.screenshot_requested = true,
+                        KeyCode::Char('e') if self.overlay == Overlay::Topology => self.export_topology(),
                         KeyCode::Char('q') => {
                             self.should_quit = true;
                             return Ok(true);
@@ -169,6 +1080,49 @@ impl App {
                     return Ok(false);
                 }
 
+                // Typing watts for `L`; every key stays in the prompt until
+                // Enter or Esc
+                if let Some(mut input) = self.power_prompt.take() {
+                    match key.code {
+                        KeyCode::Enter => self.submit_power_limit(&input),
+                        KeyCode::Esc => self.notice = Some(("Power limit unchanged".into(), Instant::now())),
+                        code => {
+                            match code {
+                                KeyCode::Char(c) if c.is_ascii_digit() || c == '.' => input.push(c),
+                                KeyCode::Backspace => {
+                                    input.pop();
+                                }
+                                _ => {}
+                            }
+                            self.show_power_prompt(&input);
+                            self.power_prompt = Some(input);
+                        }
+                    }
+                    return Ok(false);
+                }
+
+                // `#` followed by digits jumps straight to that GPU position.
+                // The jump ends once another digit can't name a GPU, or after a
+                // pause, so digits typed later switch views again
+                let pending = self.gpu_jump.take().filter(|(digits, at)| digits.is_empty() || at.elapsed() < GPU_JUMP_TIMEOUT);
+                if let Some((mut digits, _)) = pending {
+                    match key.code {
+                        KeyCode::Char(c) if c.is_ascii_digit() => {
+                            digits.push(c);
+                            self.jump_to_gpu(&digits);
+                            let gpu_count = self.data.gpu_indices().len();
+                            let extendable = digits.parse::<usize>().is_ok_and(|n| n > 0 && n * 10 < gpu_count);
+                            if extendable {
+                                self.gpu_jump = Some((digits, Instant::now()));
+                            }
+                            return Ok(false);
+                        }
+                        KeyCode::Esc | KeyCode::Enter => return Ok(false),
+                        // Anything else ends the jump and is handled as usual
+                        _ => {}
+                    }
+                }
+
                 match key.code {
                     KeyCode::Char('q') | KeyCode::Esc => {
                         self.should_quit = true;
@@ -178,20 +1132,136 @@ impl App {
                         self.view_mode = self.view_mode.next();
                     }
                     KeyCode::Up | KeyCode::Char('k') => {
-                        if self.selected_gpu > 0 {
-                            self.selected_gpu -= 1;
+                        let wrap = self.config.wrap_nav;
+                        match self.focus {
+                            Focus::Gpus => self.step_gpu(step_up),
+                            Focus::Processes => {
+                                let count = self.process_rows().len();
+                                self.selected_process = step_up(self.selected_process, count, wrap);
+                            }
                         }
                     }
                     KeyCode::Down | KeyCode::Char('j') => {
-                        let max_gpu = self.data.gpu_indices().len().saturating_sub(1);
-                        if self.selected_gpu < max_gpu {
-                            self.selected_gpu += 1;
+                        let wrap = self.config.wrap_nav;
+                        match self.focus {
+                            Focus::Gpus => self.step_gpu(step_down),
+                            Focus::Processes => {
+                                let count = self.process_rows().len();
+                                self.selected_process = step_down(self.selected_process, count, wrap);
+                            }
                         }
                     }
+                    KeyCode::Char('J') if self.view_mode.has_charts() => self.step_compare_gpu(true),
+                    KeyCode::Char('K') if self.view_mode.has_charts() => self.step_compare_gpu(false),
+                    KeyCode::Char('p') => {
+                        self.focus = match self.focus {
+                            Focus::Gpus => Focus::Processes,
+                            Focus::Processes => Focus::Gpus,
+                        };
+                    }
+                    KeyCode::Enter
+                        if self.focus == Focus::Processes
+                            && !self.process_rows().is_empty() =>
+                    {
+                        self.overlay = Overlay::Process;
+                    }
+                    KeyCode::Char('c') if self.view_mode.has_charts() => {
+                        self.chart_slot = (self.chart_slot + 1) % CHART_SLOTS;
+                    }
+                    KeyCode::Char('m') if self.view_mode.has_charts() => {
+                        let metric = &mut self.chart_metrics[self.chart_slot];
+                        *metric = metric.next();
+                    }
+                    KeyCode::Char('o') if self.view_mode.has_charts() => {
+                        self.config.overlay_gpus = !self.config.overlay_gpus;
+                    }
+                    KeyCode::Char('M') if self.view_mode.has_charts() => {
+                        let metric = &mut self.chart_metrics[self.chart_slot];
+                        *metric = metric.prev();
+                    }
+                    KeyCode::Left if self.view_mode.has_charts() => self.move_chart_cursor(1.0),
+                    KeyCode::Right if self.view_mode.has_charts() => self.move_chart_cursor(-1.0),
+                    KeyCode::Char('r') => {
+                        self.data.reset_peaks();
+                        self.notice = Some(("Chart peaks reset".into(), Instant::now()));
+                    }
+                    KeyCode::Char('y') => self.copy_selection(),
+                    KeyCode::Char('S') => self.screenshot_requested = true,
+                    KeyCode::Char('L') => self.open_power_prompt(),
+                    KeyCode::Char(' ') => self.toggle_pause(),
+                    KeyCode::Char('#') => {
+                        self.gpu_jump = Some((String::new(), Instant::now()));
+                        self.notice = Some(("Jump to GPU #".into(), Instant::now()));
+                    }
+                    KeyCode::Char('g') => {
+                        self.config.merge_pids = !self.config.merge_pids;
+                        let max_process = self.process_rows().len().saturating_sub(1);
+                        self.selected_process = self.selected_process.min(max_process);
+                    }
+                    KeyCode::Char('h') => {
+                        self.config.hide_idle = !self.config.hide_idle;
+                        let max_process = self.process_rows().len().saturating_sub(1);
+                        self.selected_process = self.selected_process.min(max_process);
+                        let label = if self.config.hide_idle { "Hiding idle processes" } else { "Showing idle processes" };
+                        self.notice = Some((label.into(), Instant::now()));
+                    }
+                    KeyCode::Char('a') => {
+                        self.config.command_display = self.config.command_display.next();
+                        let label = match self.config.command_display {
+                            CommandDisplay::Name => "Command: name",
+                            CommandDisplay::Path => "Command: full path",
+                            CommandDisplay::Args => "Command: full command line",
+                        };
+                        self.notice = Some((label.into(), Instant::now()));
+                    }
+                    KeyCode::Char('T') => {
+                        self.config.time_format = self.config.time_format.next();
+                        let label = match self.config.time_format {
+                            TimeFormat::Etime => "Runtime: elapsed (ps)",
+                            TimeFormat::Relative => "Runtime: relative",
+                            TimeFormat::Started => "Runtime: start time",
+                        };
+                        self.notice = Some((label.into(), Instant::now()));
+                    }
+                    KeyCode::Char('A') => {
+                        self.config.accent = self.config.accent.next();
+                        let settings = Settings { accent: Some(self.config.accent) };
+                        let notice = match settings.save() {
+                            Ok(()) => format!("Accent: {}", self.config.accent.name()),
+                            Err(e) => format!("Accent: {} (not saved: {})", self.config.accent.name(), e),
+                        };
+                        self.notice = Some((notice, Instant::now()));
+                    }
+                    KeyCode::Char('C') if self.config.carousel.is_some() => {
+                        self.overlay = Overlay::None;
+                        self.carousel_since = Some(Instant::now());
+                    }
+                    KeyCode::Char('s') => {
+                        self.config.gpu_sort = self.config.gpu_sort.next();
+                        let notice = match self.config.gpu_sort {
+                            GpuSort::Index => "GPUs sorted by index".to_string(),
+                            sort => format!("GPUs sorted by {}", sort.column()),
+                        };
+                        self.notice = Some((notice, Instant::now()));
+                    }
+                    KeyCode::Char('G') => self.config.group_by_gpu = !self.config.group_by_gpu,
+                    KeyCode::Char('[') => self.step_history(false),
+                    KeyCode::Char(']') => self.step_history(true),
+                    KeyCode::Char('d') => {
+                        self.diff = match self.diff {
+                            Some(_) => None,
+                            None => Some(DiffHighlight::default()),
+                        };
+                    }
                     KeyCode::Char('1') => self.view_mode = ViewMode::Dashboard,
                     KeyCode::Char('2') => self.view_mode = ViewMode::Charts,
+                    KeyCode::Char('3') => self.view_mode = ViewMode::Cluster,
+                    KeyCode::Char('4') => self.view_mode = ViewMode::Split,
+                    KeyCode::Char('5') => self.view_mode = ViewMode::Rack,
                     KeyCode::Char('i') => self.overlay = Overlay::Info,
                     KeyCode::Char('t') => self.overlay = Overlay::Topology,
+                    KeyCode::Char('l') => self.overlay = Overlay::Log,
+                    KeyCode::Char('u') => self.overlay = Overlay::Users,
                     _ => {}
                 }
             }
@@ -199,7 +1269,14 @@ impl App {
         Ok(false)
     }
 
-    fn render(&self, frame: &mut Frame) {
+    fn render(&mut self, frame: &mut Frame) {
+        self.render_views(frame);
+        if is_monochrome() {
+            strip_colors(frame.buffer_mut());
+        }
+    }
+
+    fn render_views(&mut self, frame: &mut Frame) {
         let chunks = Layout::default()
             .direction(Direction::Vertical)
             .constraints([
@@ -209,42 +1286,163 @@ impl App {
             ])
             .split(frame.area());
 
+        // While paused, draw the stores as they were when the pause began
+        let (data, remote_data): (&DataStore, Vec<&DataStore>) = match &self.paused {
+            Some(paused) => (&paused.data, paused.remotes.iter().collect()),
+            None => (&self.data, self.remotes.iter().map(|r| &r.data).collect()),
+        };
+
+        let clock = chrono::Local::now().format("%H:%M:%S").to_string();
+        let host_clock = self.hostname.as_deref().map(|host| (host, clock.as_str()));
+        let versions = data.version_label();
+        let errors = self.recent_errors();
+        let window = self.config.avg_window;
+        let power_avg = (!window.is_zero())
+            .then(|| data.power_average(window))
+            .flatten()
+            .map(|w| (format_duration(window), format_power(w, self.config.power_precision)));
+
         // Status bar
         render_status_bar(
             frame,
             chunks[0],
-            self.data.total_samples(),
-            self.data.uptime(),
-            &self.view_mode,
-            self.error.as_deref(),
+            &StatusInfo {
+                samples: data.total_samples(),
+                uptime: data.uptime(),
+                view_mode: &self.view_mode,
+                errors: &errors,
+                notice: self
+                    .notice
+                    .as_ref()
+                    .filter(|(_, at)| at.elapsed() < NOTICE_DURATION)
+                    .map(|(msg, _)| msg.as_str()),
+                host_clock,
+                versions: versions.as_deref(),
+                accent: self.config.accent.color(),
+                paused: self.paused.is_some(),
+                activity: self.activity,
+                power_avg: power_avg.as_ref().map(|(window, power)| (window.as_str(), power.as_str())),
+            },
         );
 
-        // Main content
+        // Banner above the main content while CUDA_VISIBLE_DEVICES is set
+        let main = match self.cuda_banner(data) {
+            Some(banner) => {
+                let rows = Layout::default()
+                    .direction(Direction::Vertical)
+                    .constraints([Constraint::Length(1), Constraint::Min(0)])
+                    .split(chunks[1]);
+                frame.render_widget(Paragraph::new(banner).style(Style::default().fg(Color::Yellow)), rows[0]);
+                rows[1]
+            }
+            None => chunks[1],
+        };
+
+        // Main content; the carousel shows one GPU's details full-screen
+        if self.carousel_since.is_some() {
+            render_info_view(frame, main, data, self.selected_gpu, &self.config);
+            render_help_bar(frame, chunks[2], &self.view_mode, self.config.accent.color());
+            return;
+        }
+        // Gone from the GPU set (or now selected itself): back to one GPU
+        let compare_gpu = self.compare_gpu.and_then(|idx| data.gpu_indices().iter().position(|&i| i == idx));
+        // Too narrow for both halves: show the dashboard as if tabbed
+        let split_fits = main.width >= gpu_table_width(&self.config) + SPLIT_MIN_CHART_WIDTH;
         match self.view_mode {
-            ViewMode::Dashboard => {
-                render_dashboard(frame, chunks[1], &self.data, self.selected_gpu);
+            ViewMode::Split if split_fits => {
+                let halves = Layout::default()
+                    .direction(Direction::Horizontal)
+                    .constraints([Constraint::Length(gpu_table_width(&self.config)), Constraint::Min(0)])
+                    .split(main);
+                render_gpu_table(frame, halves[0], &self.config, data, Some(self.selected_gpu), " GPUs ", self.diff.as_mut());
+                render_chart_view(
+                    frame,
+                    halves[1],
+                    data,
+                    self.selected_gpu,
+                    compare_gpu,
+                    &self.chart_metrics,
+                    self.chart_slot,
+                    self.config.accent.color(),
+                    self.chart_cursor,
+                    self.config.overlay_gpus,
+                    self.chart_now,
+                );
+            }
+            ViewMode::Dashboard | ViewMode::Split => {
+                let selected_process = (self.focus == Focus::Processes).then_some(self.selected_process);
+                render_dashboard(
+                    frame,
+                    main,
+                    &self.config,
+                    data,
+                    self.selected_gpu,
+                    selected_process,
+                    self.diff.as_mut(),
+                );
+            }
+            ViewMode::Rack => render_rack_view(frame, main, &self.config, data, self.selected_gpu),
+            ViewMode::Cluster => {
+                let hosts: Vec<(&str, &DataStore)> = std::iter::once((self.target.name(), data))
+                    .chain(self.remotes.iter().map(|r| r.target.name()).zip(remote_data))
+                    .collect();
+                render_cluster_view(frame, main, &self.config, &hosts, self.selected_gpu);
             }
             ViewMode::Charts => {
-                render_chart_view(frame, chunks[1], &self.data, self.selected_gpu);
+                render_chart_view(
+                    frame,
+                    main,
+                    data,
+                    self.selected_gpu,
+                    compare_gpu,
+                    &self.chart_metrics,
+                    self.chart_slot,
+                    self.config.accent.color(),
+                    self.chart_cursor,
+                    self.config.overlay_gpus,
+                    self.chart_now,
+                );
             }
         }
 
         // Help bar
-        render_help_bar(frame, chunks[2]);
+        render_help_bar(frame, chunks[2], &self.view_mode, self.config.accent.color());
 
         // Render overlay if active
         match self.overlay {
             Overlay::None => {}
             Overlay::Info => {
                 self.render_overlay(frame, "GPU Info", |f, area| {
-                    render_info_view(f, area, &self.data, self.selected_gpu);
+                    render_info_view(f, area, data, self.selected_gpu, &self.config);
                 });
             }
             Overlay::Topology => {
-                self.render_overlay(frame, "Topology", |f, area| {
-                    render_topology_view(f, area, &self.data);
+                self.render_overlay(frame, "Topology (e: export text)", |f, area| {
+                    render_topology_view(f, area, data, self.config.accent.color());
+                });
+            }
+            Overlay::Process => {
+                self.render_overlay(frame, "Process", |f, area| {
+                    render_process_detail(f, area, data, &self.config, self.selected_process);
+                });
+            }
+            Overlay::Log => {
+                self.render_overlay(frame, "Log", |f, area| {
+                    render_log_view(f, area, &self.log);
                 });
             }
+            Overlay::Users => {
+                self.render_overlay(frame, "Users", |f, area| {
+                    render_users_view(f, area, data, &self.config);
+                });
+            }
+            Overlay::Recap => {
+                if let Some(recap) = &self.recap {
+                    self.render_overlay(frame, "Recap", |f, area| {
+                        render_pause_recap(f, area, recap, self.config.accent.color());
+                    });
+                }
+            }
         }
     }
 
@@ -266,11 +1464,10 @@ impl App {
         frame.render_widget(Clear, popup_area);
 
         // Render a border
-        let block = Block::default()
-            .borders(Borders::ALL)
-            .border_style(Style::default().fg(Color::Cyan))
+        let block = panel()
+            .border_style(Style::default().fg(self.config.accent.color()))
             .title(format!(" {} ", title))
-            .title_style(Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD));
+            .title_style(Style::default().fg(self.config.accent.color()).add_modifier(Modifier::BOLD));
 
         let inner = block.inner(popup_area);
         frame.render_widget(block, popup_area);
@@ -290,3 +1487,94 @@ impl App {
         frame.render_widget(hint, hint_area);
     }
 }
+
+/// Empty store for one host, with the process retention settings applied
+fn new_store(config: &Config) -> DataStore {
+    let mut data = DataStore::new(config.history_seconds, config.process_ttl);
+    data.set_apps_grace(config.apps_grace);
+    if let Some(filter) = &config.gpu_filter {
+        data.set_gpu_filter(&filter.indices);
+    }
+    data
+}
+
+/// Apply a message from an additional host's monitor to its store.
+/// Returns an error to surface in the status bar, if any.
+fn update_store(data: &mut DataStore, msg: NvidiaMessage) -> Option<String> {
+    match msg {
+        NvidiaMessage::GpuSample(sample) => data.add_sample(sample),
+        NvidiaMessage::ProcessSample(sample) => data.add_process_sample(sample),
+        NvidiaMessage::GpuInfo(info) => data.update_gpu_info(info),
+        NvidiaMessage::ComputeApps(apps) => {
+            data.update_compute_apps(apps);
+        }
+        NvidiaMessage::ProcessSystemInfo(infos) => data.update_process_sys_info(infos),
+        NvidiaMessage::EncoderSessions(sessions) => data.update_encoder_sessions(sessions),
+        NvidiaMessage::XidEvents(events) => {
+            data.update_xid_events(events);
+        }
+        NvidiaMessage::Error(e) => return Some(e),
+        NvidiaMessage::Exited(which) => return Some(format!("{} exited", which)),
+    }
+    None
+}
+
+/// Next `--verbose` command line; never resolves without `--verbose`
+async fn recv_command(commands: &mut Option<mpsc::UnboundedReceiver<String>>) -> Option<String> {
+    match commands {
+        Some(rx) => rx.recv().await,
+        None => std::future::pending().await,
+    }
+}
+
+/// Resolves on Ctrl-C, or on SIGTERM as sent by `systemctl stop` and `kill`
+async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        match signal(SignalKind::terminate()) {
+            Ok(mut term) => {
+                tokio::select! {
+                    _ = tokio::signal::ctrl_c() => {}
+                    _ = term.recv() => {}
+                }
+            }
+            Err(_) => {
+                let _ = tokio::signal::ctrl_c().await;
+            }
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = tokio::signal::ctrl_c().await;
+    }
+}
+
+/// Move a selection up one row, wrapping to the last row when `wrap` is set
+fn step_up(selected: usize, count: usize, wrap: bool) -> usize {
+    match selected {
+        0 if wrap => count.saturating_sub(1),
+        _ => selected.saturating_sub(1),
+    }
+}
+
+/// Move a selection down one row, wrapping to the first row when `wrap` is set
+fn step_down(selected: usize, count: usize, wrap: bool) -> usize {
+    if selected + 1 < count {
+        selected + 1
+    } else if wrap {
+        0
+    } else {
+        selected
+    }
+}
+
+/// Hostname of this machine, read once at startup
+fn local_hostname() -> String {
+    std::fs::read_to_string("/proc/sys/kernel/hostname")
+        .ok()
+        .or_else(|| std::env::var("HOSTNAME").ok())
+        .map(|h| h.trim().to_string())
+        .filter(|h| !h.is_empty())
+        .unwrap_or_else(|| "localhost".to_string())
+}
@@ -0,0 +1,420 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use ratatui::style::Color;
+use serde::{Deserialize, Serialize};
+
+/// How the process table shows each process's command
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum CommandDisplay {
+    /// Executable name only
+    #[default]
+    Name,
+    /// Full executable path from compute-apps
+    Path,
+    /// Full command line with arguments from ps
+    Args,
+}
+
+impl CommandDisplay {
+    pub fn next(&self) -> Self {
+        match self {
+            Self::Name => Self::Path,
+            Self::Path => Self::Args,
+            Self::Args => Self::Name,
+        }
+    }
+}
+
+/// How the process table shows each process's runtime
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum TimeFormat {
+    /// Elapsed time as ps prints it, e.g. `2-03:04:05`
+    #[default]
+    Etime,
+    /// Elapsed time rounded to the two largest units, e.g. `2d 3h`
+    Relative,
+    /// Wall-clock start time, e.g. `14:05` today or `14:05, Mar 3`
+    Started,
+}
+
+impl TimeFormat {
+    pub fn next(&self) -> Self {
+        match self {
+            Self::Etime => Self::Relative,
+            Self::Relative => Self::Started,
+            Self::Started => Self::Etime,
+        }
+    }
+}
+
+/// How GPU details are queried from nvidia-smi
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum QueryFormat {
+    /// `--query-gpu` CSV: fast, but positional
+    #[default]
+    Csv,
+    /// `-q -x` XML: slower, but fields are named and survive driver changes
+    Xml,
+}
+
+/// Order of rows in the GPU table; cycled with `s`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum GpuSort {
+    #[default]
+    Index,
+    /// Hottest first
+    Temp,
+    /// Highest power draw first
+    Power,
+    /// Busiest SM first
+    Sm,
+}
+
+impl GpuSort {
+    pub fn next(&self) -> Self {
+        match self {
+            Self::Index => Self::Temp,
+            Self::Temp => Self::Power,
+            Self::Power => Self::Sm,
+            Self::Sm => Self::Index,
+        }
+    }
+
+    /// GPU table column the sort applies to
+    pub fn column(&self) -> &'static str {
+        match self {
+            Self::Index => "GPU",
+            Self::Temp => "Temp",
+            Self::Power => "Power",
+            Self::Sm => "SM%",
+        }
+    }
+}
+
+/// Border style shared by every panel
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum BorderPreset {
+    #[default]
+    Plain,
+    Rounded,
+    Double,
+    /// No borders, for very small terminals
+    None,
+}
+
+/// How memory gauges are drawn
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum GaugeStyle {
+    /// Solid block fill
+    #[default]
+    Blocks,
+    /// Thin braille dots at twice the resolution, for small terminals
+    Braille,
+    /// Percentage and label only, no bar
+    Percent,
+}
+
+/// Unit for the process table's VRAM column
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum VramUnit {
+    /// MiB below 1 GiB, GiB from there up
+    #[default]
+    Auto,
+    /// Always whole MiB
+    Mib,
+    /// Always GiB
+    Gib,
+}
+
+/// Accent color for titles, headers, keys and the active tab; cycled with `A`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Accent {
+    #[default]
+    Cyan,
+    Green,
+    Magenta,
+    Yellow,
+    Blue,
+    White,
+}
+
+impl Accent {
+    pub fn color(&self) -> Color {
+        match self {
+            Self::Cyan => Color::Cyan,
+            Self::Green => Color::Green,
+            Self::Magenta => Color::Magenta,
+            Self::Yellow => Color::Yellow,
+            Self::Blue => Color::Blue,
+            Self::White => Color::White,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Cyan => "cyan",
+            Self::Green => "green",
+            Self::Magenta => "magenta",
+            Self::Yellow => "yellow",
+            Self::Blue => "blue",
+            Self::White => "white",
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            Self::Cyan => Self::Green,
+            Self::Green => Self::Magenta,
+            Self::Magenta => Self::Yellow,
+            Self::Yellow => Self::Blue,
+            Self::Blue => Self::White,
+            Self::White => Self::Cyan,
+        }
+    }
+}
+
+/// Choices that stick across runs, kept in
+/// `$XDG_CONFIG_HOME/nvidiagpu_top/settings.json`
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Settings {
+    #[serde(default)]
+    pub accent: Option<Accent>,
+}
+
+impl Settings {
+    fn path() -> Option<PathBuf> {
+        let base = std::env::var_os("XDG_CONFIG_HOME")
+            .filter(|v| !v.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+        Some(base.join("nvidiagpu_top").join("settings.json"))
+    }
+
+    /// Saved settings; a missing or unreadable file means defaults
+    pub fn load() -> Self {
+        Self::path()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = Self::path().context("Neither XDG_CONFIG_HOME nor HOME is set")?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+        }
+        let json = serde_json::to_string_pretty(self)?;
+        std::fs::write(&path, json).with_context(|| format!("Failed to write {}", path.display()))
+    }
+}
+
+/// Environment variable read for GPUs to show when `--gpus` isn't given.
+/// CUDA_VISIBLE_DEVICES isn't used: it's renumbered by CUDA_DEVICE_ORDER
+/// and may hold UUIDs, so it gets the CUDA device banner instead
+pub const GPU_ENV_VAR: &str = "NVIDIA_GPU_TOP_GPUS";
+
+/// GPUs to display and where the list came from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GpuFilter {
+    pub indices: Vec<u32>,
+    /// `--gpus` or the environment variable the list was read from
+    pub source: String,
+}
+
+/// Pick the GPUs to show: `--gpus` wins, then GPU_ENV_VAR. A malformed
+/// environment value is ignored, with a warning
+pub fn resolve_gpu_filter(cli: &[u32], env: impl Fn(&str) -> Option<String>) -> (Option<GpuFilter>, Option<String>) {
+    if !cli.is_empty() {
+        return (Some(GpuFilter { indices: cli.to_vec(), source: "--gpus".into() }), None);
+    }
+    let Some(value) = env(GPU_ENV_VAR).filter(|v| !v.trim().is_empty()) else {
+        return (None, None);
+    };
+    match value.split(',').map(|idx| idx.trim().parse::<u32>()).collect::<Result<Vec<_>, _>>() {
+        Ok(indices) => (Some(GpuFilter { indices, source: GPU_ENV_VAR.into() }), None),
+        Err(_) => (None, Some(format!("Ignoring {}={:?}: not a list of GPU indices", GPU_ENV_VAR, value))),
+    }
+}
+
+/// Parse a VRAM size like `40GiB`, `512M` or `1.5T` into MiB. Units are
+/// binary whatever the spelling (nvidia-smi reports MiB); a bare number is MiB
+pub fn parse_vram_size(s: &str) -> Result<u64, String> {
+    let s = s.trim();
+    let split = s.find(|c: char| !(c.is_ascii_digit() || c == '.')).unwrap_or(s.len());
+    let (number, unit) = s.split_at(split);
+    let number: f64 = number.parse().map_err(|_| format!("{:?} is not a size like 40GiB", s))?;
+    let scale = match unit.trim().to_ascii_lowercase().as_str() {
+        "" | "m" | "mb" | "mib" => 1.0,
+        "g" | "gb" | "gib" => 1024.0,
+        "t" | "tb" | "tib" => 1024.0 * 1024.0,
+        other => return Err(format!("unknown unit {:?} (use MiB, GiB or TiB)", other)),
+    };
+    Ok((number * scale).round() as u64)
+}
+
+/// Runtime options derived from the command line
+#[derive(Debug, Clone)]
+pub struct Config {
+    /// History retention in seconds
+    pub history_seconds: u64,
+    /// How long a process stays listed after its last pmon/compute-apps report
+    pub process_ttl: Duration,
+    /// How long an empty compute-apps result is ignored after a non-empty one
+    pub apps_grace: Duration,
+    /// Window of the status bar's average total power; zero hides it
+    pub avg_window: Duration,
+    /// Print one JSON object per dmon sample to stdout
+    pub stream_json: bool,
+    /// Run without the TUI (stdout is reserved for streamed output)
+    pub headless: bool,
+    /// Show power and temperature sparklines in the GPU table
+    pub power_temp_sparklines: bool,
+    /// Briefly highlight GPU table values that changed (like `watch -d`)
+    pub highlight_changes: bool,
+    /// Show the hostname and wall-clock time in the status bar
+    pub clock: bool,
+    /// Keep the terminal window/tab title set to a GPU status summary
+    pub set_title: bool,
+    /// Screenshots taken with `S` leave out the ANSI color escapes
+    pub screenshot_plain: bool,
+    /// Poll dmesg for Xid errors; opt-in since reading the kernel log
+    /// usually needs extra permissions
+    pub xid_dmesg: bool,
+    /// Charts plot the focused metric for all GPUs on one chart; toggled
+    /// at runtime with `o`
+    pub overlay_gpus: bool,
+    /// Charts advance only when a sample arrives rather than every frame
+    pub sync_charts: bool,
+    /// Hosts to monitor over ssh; empty means the local machine
+    pub hosts: Vec<String>,
+    /// GPUs to show, from `--gpus` or the environment; None shows all
+    pub gpu_filter: Option<GpuFilter>,
+    /// Why a GPU list in the environment was ignored, for the event log
+    pub gpu_filter_warning: Option<String>,
+    /// Ring the terminal bell when a busy GPU goes idle
+    pub bell_on_idle: bool,
+    /// Ring the terminal bell when a GPU reaches critical temperature
+    pub bell_on_crit: bool,
+    /// Blink GPU table rows of GPUs at critical temperature
+    pub flash_crit: bool,
+    /// Percentage of the power limit from which power draw shows yellow
+    pub power_warn_pct: f32,
+    /// Percentage of the power limit from which power draw shows red
+    pub power_crit_pct: f32,
+    /// Enable keys that change GPU settings (power limit with `L`)
+    pub allow_control: bool,
+    /// Border style for all panels
+    pub border: BorderPreset,
+    /// GPU table row order; cycled at runtime with `s`
+    pub gpu_sort: GpuSort,
+    /// Leave processes with no pmon activity and steady VRAM out of the
+    /// process table; toggled at runtime with `h`
+    pub hide_idle: bool,
+    /// Process command column: name, path, or full args; cycled with `a`
+    pub command_display: CommandDisplay,
+    /// Process runtime column: ps etime, relative, or start time; cycled with `T`
+    pub time_format: TimeFormat,
+    /// Merge process rows sharing a PID across GPUs; toggled at runtime with `g`
+    pub merge_pids: bool,
+    /// Group process rows under a per-GPU subheader; toggled at runtime with `G`
+    pub group_by_gpu: bool,
+    /// Decimal places for power readings
+    pub power_precision: usize,
+    /// Show clocks of 1000 MHz and up in GHz
+    pub clock_ghz: bool,
+    /// Accent color, from `--accent` or the saved settings
+    pub accent: Accent,
+    /// ASCII-only sparklines and gauges
+    pub ascii: bool,
+    /// Modifiers instead of colors (`--no-color`, `NO_COLOR`, `TERM=dumb`)
+    pub no_color: bool,
+    /// Memory gauge style; braille falls back to blocks with `--ascii`
+    pub gauge_style: GaugeStyle,
+    /// Unit of the process table's VRAM column
+    pub vram_unit: VramUnit,
+    /// Decimal places for GiB in the process table's VRAM column
+    pub vram_precision: usize,
+    /// Processes using at least this much VRAM (MiB) are shown in red
+    pub proc_vram_warn_mib: Option<u64>,
+    /// Build samples from periodic `--query-gpu` calls instead of dmon/pmon
+    pub poll_only: bool,
+    /// No periodic GPU details, compute-apps, ps or encoder queries
+    pub no_query: bool,
+    /// Format used for the periodic GPU details query
+    pub query_format: QueryFormat,
+    /// Ask dmon for per-line timestamps and space samples by them
+    pub dmon_timestamps: bool,
+    /// j/k wrap around at the ends of the GPU and process lists
+    pub wrap_nav: bool,
+    /// Print a per-GPU report of the whole run on exit
+    pub summary: bool,
+    /// Log the command lines run on each target
+    pub verbose: bool,
+    /// Cycle through GPUs full-screen, one per interval, for wall displays
+    pub carousel: Option<Duration>,
+    /// Shortest time between frames (`--max-fps`); None draws every pass
+    pub min_frame_interval: Option<Duration>,
+    /// Recorded nvidia-smi output to play back instead of running nvidia-smi
+    pub replay: Option<PathBuf>,
+    /// Where to write a JSON snapshot on exit
+    pub dump_json: Option<PathBuf>,
+}
+
+impl Config {
+    /// Warning color for power draw against the limit: red from
+    /// `--power-crit`, yellow from `--power-warn`, None below or unknown
+    pub fn power_color(&self, draw: Option<f32>, limit: Option<f32>) -> Option<Color> {
+        let pct = match (draw, limit) {
+            (Some(draw), Some(limit)) if limit > 0.0 => draw / limit * 100.0,
+            _ => return None,
+        };
+        if pct >= self.power_crit_pct {
+            Some(Color::Red)
+        } else if pct >= self.power_warn_pct {
+            Some(Color::Yellow)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_gpu_filter() {
+        let env = |value: &'static str| move |name: &str| (name == GPU_ENV_VAR).then(|| value.to_string());
+        let indices = |(filter, _): (Option<GpuFilter>, Option<String>)| filter.map(|f| (f.indices, f.source));
+
+        // --gpus overrides the environment
+        assert_eq!(indices(resolve_gpu_filter(&[0], env("1"))), Some((vec![0], "--gpus".into())));
+        assert_eq!(indices(resolve_gpu_filter(&[], env("2, 3"))), Some((vec![2, 3], GPU_ENV_VAR.into())));
+        assert_eq!(resolve_gpu_filter(&[], env("")), (None, None));
+        assert_eq!(resolve_gpu_filter(&[], |_: &str| None), (None, None));
+
+        // Malformed values show everything, with a warning
+        for bad in ["0,x", "GPU-8f3a", "1,,2", "-1"] {
+            let (filter, warning) = resolve_gpu_filter(&[], env(bad));
+            assert_eq!(filter, None);
+            assert!(warning.is_some_and(|w| w.contains(GPU_ENV_VAR)), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn test_parse_vram_size() {
+        assert_eq!(parse_vram_size("40GiB"), Ok(40 * 1024));
+        assert_eq!(parse_vram_size("40g"), Ok(40 * 1024));
+        assert_eq!(parse_vram_size("1.5 GB"), Ok(1536));
+        assert_eq!(parse_vram_size("512"), Ok(512));
+        assert_eq!(parse_vram_size("512MiB"), Ok(512));
+        assert_eq!(parse_vram_size("1T"), Ok(1024 * 1024));
+        for bad in ["", "GiB", "40 parsecs", "-5G", "1.2.3G"] {
+            assert!(parse_vram_size(bad).is_err(), "{}", bad);
+        }
+    }
+}
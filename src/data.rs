@@ -1,7 +1,13 @@
-use std::collections::{HashMap, VecDeque};
-use std::time::Instant;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::time::{Duration, Instant};
 
-use crate::parser::{GpuSample, ProcessSample, GpuInfo, GpuTopology, ComputeApp, ProcessSystemInfo};
+use chrono::{DateTime, Local};
+
+use crate::config::{CommandDisplay, GpuSort, TimeFormat};
+use crate::parser::{GpuSample, ProcessSample, GpuInfo, GpuTopology, ComputeApp, EncoderSession, ProcessSystemInfo, XidEvent};
+
+/// Buckets of `GpuHistory::sm_histogram`
+pub const SM_HISTOGRAM_BUCKETS: usize = 10;
 
 /// A timestamped GPU sample
 #[derive(Debug, Clone)]
@@ -10,8 +16,15 @@ pub struct TimestampedSample {
     pub timestamp: Instant,
 }
 
+impl TimestampedSample {
+    /// Time from `earlier` to this sample; zero if `earlier` is actually later
+    pub fn interval_since(&self, earlier: &TimestampedSample) -> Duration {
+        self.timestamp.saturating_duration_since(earlier.timestamp)
+    }
+}
+
 /// Ring buffer for storing historical GPU data
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct GpuHistory {
     samples: VecDeque<TimestampedSample>,
     max_samples: usize,
@@ -19,26 +32,56 @@ pub struct GpuHistory {
 
 impl GpuHistory {
     pub fn new(max_samples: usize) -> Self {
+        // Keep two samples even with a tiny --history so last_interval works
+        let max_samples = max_samples.max(2);
         Self {
             samples: VecDeque::with_capacity(max_samples),
             max_samples,
         }
     }
 
+    /// Resize the ring, dropping the oldest samples beyond the new size
+    pub fn set_capacity(&mut self, max_samples: usize) {
+        self.max_samples = max_samples.max(2);
+        let excess = self.samples.len().saturating_sub(self.max_samples);
+        self.samples.drain(..excess);
+    }
+
     pub fn push(&mut self, sample: GpuSample) {
+        let timestamp = self.device_timestamp(&sample).unwrap_or_else(Instant::now);
         if self.samples.len() >= self.max_samples {
             self.samples.pop_front();
         }
-        self.samples.push_back(TimestampedSample {
-            sample,
-            timestamp: Instant::now(),
-        });
+        self.samples.push_back(TimestampedSample { sample, timestamp });
+    }
+
+    /// When both this and the previous sample carry dmon's device time, space
+    /// them by that instead of by arrival time, so a briefly blocked UI
+    /// doesn't bunch points together. Never runs ahead of the local clock.
+    fn device_timestamp(&self, sample: &GpuSample) -> Option<Instant> {
+        let prev = self.samples.back()?;
+        let (prev_time, time) = (prev.sample.device_time?, sample.device_time?);
+        // signed_duration_since wraps at midnight into a negative delta
+        let mut delta = time.signed_duration_since(prev_time);
+        if delta < chrono::TimeDelta::zero() {
+            delta += chrono::TimeDelta::days(1);
+        }
+        let stamped = prev.timestamp + delta.to_std().ok()?;
+        Some(stamped.min(Instant::now()))
     }
 
     pub fn latest(&self) -> Option<&GpuSample> {
         self.samples.back().map(|ts| &ts.sample)
     }
 
+    /// Real time between the two newest samples (device time with
+    /// `--dmon-timestamps`), for rates and energy instead of assuming 1s
+    pub fn last_interval(&self) -> Option<Duration> {
+        let mut newest = self.samples.iter().rev();
+        let (last, prev) = (newest.next()?, newest.next()?);
+        Some(last.interval_since(prev))
+    }
+
     #[allow(dead_code)]
     pub fn len(&self) -> usize {
         self.samples.len()
@@ -49,6 +92,37 @@ impl GpuHistory {
         self.samples.is_empty()
     }
 
+    /// Seconds between the oldest retained sample and `now`
+    pub fn time_span(&self, now: Instant) -> f64 {
+        self.samples
+            .front()
+            .map(|ts| now.saturating_duration_since(ts.timestamp).as_secs_f64())
+            .unwrap_or(0.0)
+    }
+
+    /// Retained samples per 10%-wide SM utilization bucket (0-9%, ...,
+    /// 90-100%), to tell a mostly idle GPU with spikes from a steadily
+    /// loaded one
+    pub fn sm_histogram(&self) -> [u32; SM_HISTOGRAM_BUCKETS] {
+        let mut buckets = [0; SM_HISTOGRAM_BUCKETS];
+        for sm in self.samples.iter().filter_map(|ts| ts.sample.sm_util) {
+            buckets[(sm as usize / 10).min(SM_HISTOGRAM_BUCKETS - 1)] += 1;
+        }
+        buckets
+    }
+
+    /// Highest value of a series among samples taken at or after `since`
+    pub fn peak_since<F>(&self, since: Instant, extractor: F) -> Option<u32>
+    where
+        F: Fn(&GpuSample) -> Option<u32>,
+    {
+        self.samples
+            .iter()
+            .filter(|ts| ts.timestamp >= since)
+            .filter_map(|ts| extractor(&ts.sample))
+            .max()
+    }
+
     pub fn recent_values<F>(&self, count: usize, extractor: F) -> Vec<f64>
     where
         F: Fn(&GpuSample) -> Option<u32>,
@@ -64,20 +138,134 @@ impl GpuHistory {
             .collect()
     }
 
-    pub fn chart_data<F>(&self, extractor: F) -> Vec<(f64, f64)>
+    /// Mean of a series over samples taken at or after `since`
+    pub fn average_since<F>(&self, since: Instant, extractor: F) -> Option<f64>
+    where
+        F: Fn(&GpuSample) -> Option<u32>,
+    {
+        let values: Vec<u32> = self
+            .samples
+            .iter()
+            .filter(|ts| ts.timestamp >= since)
+            .filter_map(|ts| extractor(&ts.sample))
+            .collect();
+        (!values.is_empty()).then(|| values.iter().map(|&v| v as f64).sum::<f64>() / values.len() as f64)
+    }
+
+    /// Sample nearest to `secs_ago` (before `now`) that has a value for the
+    /// series, as (its seconds ago, value); for reading a chart at a cursor
+    pub fn value_at<F>(&self, now: Instant, secs_ago: f64, extractor: F) -> Option<(f64, u32)>
     where
         F: Fn(&GpuSample) -> Option<u32>,
     {
-        let now = Instant::now();
         self.samples
+            .iter()
+            .filter_map(|ts| extractor(&ts.sample).map(|v| (now.saturating_duration_since(ts.timestamp).as_secs_f64(), v)))
+            .min_by(|(a, _), (b, _)| (a - secs_ago).abs().total_cmp(&(b - secs_ago).abs()))
+    }
+
+    /// Chart points as (seconds before `now`, value), downsampled to at
+    /// most `max_points`
+    pub fn chart_data<F>(&self, max_points: usize, now: Instant, extractor: F) -> Vec<(f64, f64)>
+    where
+        F: Fn(&GpuSample) -> Option<u32>,
+    {
+        let points = self
+            .samples
             .iter()
             .filter_map(|ts| {
                 extractor(&ts.sample).map(|v| {
-                    let secs_ago = now.duration_since(ts.timestamp).as_secs_f64();
+                    let secs_ago = now.saturating_duration_since(ts.timestamp).as_secs_f64();
                     (-secs_ago, v as f64)
                 })
             })
-            .collect()
+            .collect();
+        downsample(points, max_points)
+    }
+}
+
+/// Reduce a series to at most `max_points` by averaging evenly sized buckets.
+/// Each bucket keeps the x position of its newest point so "now" stays pinned.
+fn downsample(points: Vec<(f64, f64)>, max_points: usize) -> Vec<(f64, f64)> {
+    if max_points == 0 || points.len() <= max_points {
+        return points;
+    }
+
+    let bucket_size = points.len().div_ceil(max_points);
+    points
+        .chunks(bucket_size)
+        .map(|bucket| {
+            let x = bucket[bucket.len() - 1].0;
+            let y = bucket.iter().map(|(_, y)| y).sum::<f64>() / bucket.len() as f64;
+            (x, y)
+        })
+        .collect()
+}
+
+/// Min/max/average of one metric over the whole run
+#[derive(Debug, Clone, Default)]
+pub struct MetricStats {
+    pub min: Option<u32>,
+    pub max: Option<u32>,
+    sum: u64,
+    count: u64,
+}
+
+impl MetricStats {
+    fn record(&mut self, value: Option<u32>) {
+        if let Some(v) = value {
+            self.min = Some(self.min.map_or(v, |m| m.min(v)));
+            self.max = Some(self.max.map_or(v, |m| m.max(v)));
+            self.sum += v as u64;
+            self.count += 1;
+        }
+    }
+
+    pub fn avg(&self) -> Option<f64> {
+        (self.count > 0).then(|| self.sum as f64 / self.count as f64)
+    }
+}
+
+/// Aggregates for one GPU since startup; unlike `GpuHistory` these are not
+/// limited to the retention window
+#[derive(Debug, Clone)]
+pub struct GpuRunStats {
+    pub started: Instant,
+    pub samples: u64,
+    pub temp: MetricStats,
+    pub power: MetricStats,
+    pub sm: MetricStats,
+    pub energy_wh: f64,
+    pub peak_vram_mib: u64,
+    // Previous power reading, integrated over the gap to the next one
+    last_power: Option<u32>,
+}
+
+impl GpuRunStats {
+    fn new(now: Instant) -> Self {
+        Self {
+            started: now,
+            samples: 0,
+            temp: MetricStats::default(),
+            power: MetricStats::default(),
+            sm: MetricStats::default(),
+            energy_wh: 0.0,
+            peak_vram_mib: 0,
+            last_power: None,
+        }
+    }
+
+    /// Add a sample that arrived `interval` after the previous one
+    fn record(&mut self, sample: &GpuSample, interval: Option<Duration>) {
+        self.samples += 1;
+        self.temp.record(sample.gpu_temp_c);
+        self.power.record(sample.power_w);
+        self.sm.record(sample.sm_util);
+
+        if let (Some(watts), Some(interval)) = (self.last_power, interval) {
+            self.energy_wh += watts as f64 * interval.as_secs_f64() / 3600.0;
+        }
+        self.last_power = sample.power_w;
     }
 }
 
@@ -88,70 +276,363 @@ pub struct ProcessInfo {
     pub last_seen: Instant,
 }
 
+/// A compute app that showed up in, or dropped out of, compute-apps
+#[derive(Debug, Clone)]
+pub enum ProcessEvent {
+    Started(ComputeApp),
+    Ended(ComputeApp),
+}
+
 /// Combined process data from multiple sources
 #[derive(Debug, Clone)]
 pub struct EnrichedProcess {
     pub pid: u32,
-    pub command: String,
+    pub command: String,        // Executable name
+    pub path: String,           // Executable path as compute-apps reports it
+    pub args: String,           // Full command line from ps (may be empty)
+    pub container: Option<String>, // Short container id, None for host processes
+    pub user: Option<String>,   // Owner from ps; None until ps reports the process
     pub gpu_idx: u32,
-    pub vram_mib: u64,          // From compute-apps
+    pub gpus: Vec<u32>,         // Every GPU this row covers (several when merged by PID)
+    pub vram_mib: Option<u64>,  // From compute-apps; None for processes only pmon reports
     pub sm_util: Option<u32>,   // From pmon (instantaneous)
+    pub mem_util: Option<u32>,  // From pmon (memory bandwidth, not allocation)
+    pub enc_sessions: Option<u32>, // NVENC sessions held; None where the GPU doesn't report them
     pub cpu_percent: f32,       // From ps
     pub rss_mb: u64,            // System RAM from ps
-    pub elapsed: String,        // Runtime
+    pub elapsed: String,        // Runtime as ps prints it
+    pub runtime: Option<Duration>, // `elapsed` parsed
+}
+
+impl EnrichedProcess {
+    /// Command as chosen with `--command`, falling back to the path when
+    /// ps hasn't reported the arguments yet
+    pub fn command_text(&self, display: CommandDisplay) -> &str {
+        match display {
+            CommandDisplay::Name => &self.command,
+            CommandDisplay::Path => &self.path,
+            CommandDisplay::Args if !self.args.is_empty() => &self.args,
+            CommandDisplay::Args => &self.path,
+        }
+    }
+
+    /// Runtime as chosen with `--time-format`; ps's own text when it
+    /// couldn't be parsed
+    pub fn runtime_text(&self, format: TimeFormat) -> String {
+        match (format, self.runtime) {
+            (TimeFormat::Etime, _) | (_, None) => self.elapsed.clone(),
+            (TimeFormat::Relative, Some(runtime)) => format_relative(runtime),
+            (TimeFormat::Started, Some(runtime)) => format_started(runtime, Local::now()),
+        }
+    }
+
+    /// GPU column text, e.g. "1" or "0,1,2" for a merged row
+    pub fn gpu_label(&self) -> String {
+        self.gpus.iter().map(|g| g.to_string()).collect::<Vec<_>>().join(",")
+    }
+}
+
+/// Direction a series is heading
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Trend {
+    Rising,
+    Falling,
+    Steady,
+}
+
+/// Direction of `values` (oldest first) from the slope of a least-squares
+/// line through them. Slopes under `steady_pct` percent of the mean per
+/// sample count as steady, so one threshold suits both 40°C and 400W
+pub fn trend(values: &[f64], steady_pct: f64) -> Trend {
+    if values.len() < 2 {
+        return Trend::Steady;
+    }
+    let n = values.len() as f64;
+    let mean_x = (n - 1.0) / 2.0;
+    let mean_y = values.iter().sum::<f64>() / n;
+    let (mut cov, mut var) = (0.0, 0.0);
+    for (i, &y) in values.iter().enumerate() {
+        let dx = i as f64 - mean_x;
+        cov += dx * (y - mean_y);
+        var += dx * dx;
+    }
+    let slope = cov / var;
+    let steady = mean_y.abs() * steady_pct / 100.0;
+    if slope > steady {
+        Trend::Rising
+    } else if slope < -steady {
+        Trend::Falling
+    } else {
+        Trend::Steady
+    }
+}
+
+/// Position in `now` for a GPU selection at position `selected` in `prev`
+/// (both sorted GPU indices): the same GPU where it's still present, so a
+/// GPU appearing before it doesn't move the selection, otherwise the same
+/// position clamped to the last GPU
+pub fn reselect_gpu(prev: &[u32], now: &[u32], selected: usize) -> usize {
+    let same = prev.get(selected).and_then(|idx| now.iter().position(|i| i == idx));
+    same.unwrap_or(selected).min(now.len().saturating_sub(1))
+}
+
+/// Two largest units of a duration, e.g. "2d 3h", "3h 12m", "45s"
+fn format_relative(runtime: Duration) -> String {
+    let secs = runtime.as_secs();
+    let (days, hours, minutes) = (secs / 86400, secs / 3600 % 24, secs / 60 % 60);
+    match (days, hours, minutes) {
+        (0, 0, 0) => format!("{}s", secs),
+        (0, 0, m) => format!("{}m {}s", m, secs % 60),
+        (0, h, m) => format!("{}h {}m", h, m),
+        (d, h, _) => format!("{}d {}h", d, h),
+    }
+}
+
+/// Wall-clock time a process started `runtime` before `now`: "14:05" for
+/// today, "14:05, Mar 3" before that
+fn format_started(runtime: Duration, now: DateTime<Local>) -> String {
+    let start = now - chrono::Duration::from_std(runtime).unwrap_or_default();
+    if start.date_naive() == now.date_naive() {
+        start.format("%H:%M").to_string()
+    } else {
+        start.format("%H:%M, %b %-d").to_string()
+    }
+}
+
+/// Raise each field of `peak` to `sample`'s reading where that is higher
+fn raise_peaks(peak: &mut GpuSample, sample: &GpuSample) {
+    for (peak, value) in [
+        (&mut peak.power_w, sample.power_w),
+        (&mut peak.gpu_temp_c, sample.gpu_temp_c),
+        (&mut peak.mem_temp_c, sample.mem_temp_c),
+        (&mut peak.sm_util, sample.sm_util),
+        (&mut peak.mem_util, sample.mem_util),
+        (&mut peak.enc_util, sample.enc_util),
+        (&mut peak.dec_util, sample.dec_util),
+        (&mut peak.jpg_util, sample.jpg_util),
+        (&mut peak.ofa_util, sample.ofa_util),
+        (&mut peak.mem_clock_mhz, sample.mem_clock_mhz),
+        (&mut peak.gpu_clock_mhz, sample.gpu_clock_mhz),
+    ] {
+        *peak = (*peak).max(value);
+    }
+}
+
+/// Shown for processes whose owner ps hasn't reported (yet)
+pub const UNKNOWN_USER: &str = "unknown";
+
+/// One user's share of the node's GPUs, for the users overlay
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UserUsage {
+    pub user: String,
+    pub processes: usize,
+    pub gpus: Vec<u32>,
+    pub vram_mib: u64,
+}
+
+/// Merge rows sharing a PID: VRAM and encoder sessions are summed,
+/// utilization keeps the busiest GPU.
+/// Rows stay in the order of each PID's first appearance.
+fn merge_by_pid(processes: Vec<EnrichedProcess>) -> Vec<EnrichedProcess> {
+    let mut merged: Vec<EnrichedProcess> = Vec::new();
+    let mut by_pid: HashMap<u32, usize> = HashMap::new();
+
+    for p in processes {
+        match by_pid.get(&p.pid) {
+            Some(&i) => {
+                let row = &mut merged[i];
+                row.gpus.extend(p.gpus);
+                row.vram_mib = match (row.vram_mib, p.vram_mib) {
+                    (Some(a), Some(b)) => Some(a + b),
+                    (a, b) => a.or(b),
+                };
+                row.sm_util = row.sm_util.max(p.sm_util);
+                row.mem_util = row.mem_util.max(p.mem_util);
+                row.enc_sessions = match (row.enc_sessions, p.enc_sessions) {
+                    (Some(a), Some(b)) => Some(a + b),
+                    (a, b) => a.or(b),
+                };
+            }
+            None => {
+                by_pid.insert(p.pid, merged.len());
+                merged.push(p);
+            }
+        }
+    }
+
+    for row in &mut merged {
+        row.gpus.sort();
+        row.gpus.dedup();
+    }
+    merged
 }
 
 /// Data store for all GPUs
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct DataStore {
     // Historical samples from dmon
     gpus: HashMap<u32, GpuHistory>,
     max_samples: usize,
     total_samples: u64,
     start_time: Instant,
+    run_stats: HashMap<u32, GpuRunStats>,
+    // Highest reading of every dmon field per GPU since the last peak reset
+    // (`r`); kept apart from the history so resetting keeps the charts
+    peaks: HashMap<u32, GpuSample>,
+    // When each idle GPU's SM utilization last dropped to zero, and which
+    // GPUs have been busy at all since startup
+    idle_since: HashMap<u32, Instant>,
+    seen_active: HashSet<u32>,
 
     // Process monitoring from pmon
     processes: HashMap<(u32, u32), ProcessInfo>, // (gpu_idx, pid) -> info
 
-    // Compute apps (VRAM per process) - key is (gpu_uuid, pid)
-    compute_apps: Vec<ComputeApp>,
+    // How long a process survives after it was last reported
+    process_ttl: Duration,
+
+    // Compute apps (VRAM per process) with when each was last reported
+    compute_apps: HashMap<(String, u32), (ComputeApp, Instant)>, // (gpu_uuid, pid) -> app
+    // Apps whose VRAM went up in the latest compute-apps report
+    vram_growing: HashSet<(String, u32)>,
+    // Whether a compute-apps query has been merged yet; the first one only
+    // records what was already running
+    compute_apps_seeded: bool,
+    // How long an empty compute-apps result is distrusted (the driver
+    // sometimes answers empty while busy), and when the last non-empty one came
+    apps_grace: Duration,
+    apps_seen_at: Option<Instant>,
+    // GPUs to keep data for; None keeps all
+    gpu_filter: Option<HashSet<u32>>,
 
     // System info per process
     process_sys_info: HashMap<u32, ProcessSystemInfo>, // pid -> info
 
+    // NVENC sessions per (gpu_idx, pid); None until `encodersessions` has
+    // worked once, i.e. where session accounting is unavailable
+    encoder_sessions: Option<HashMap<(u32, u32), u32>>,
+
+    // Xid reports in the kernel log; None unless `--xid-dmesg` has read it
+    xid_events: Option<Vec<XidEvent>>,
+
     // Static GPU info from query-gpu
     gpu_info: HashMap<u32, GpuInfo>,
 
     // Topology
     topology: Option<GpuTopology>,
+
+    // CUDA version reported by nvidia-smi, queried once at startup
+    cuda_version: Option<String>,
 }
 
 impl DataStore {
-    pub fn new(history_seconds: u64) -> Self {
+    pub fn new(history_seconds: u64, process_ttl: Duration) -> Self {
         let max_samples = history_seconds as usize;
         Self {
             gpus: HashMap::new(),
             max_samples,
             total_samples: 0,
             start_time: Instant::now(),
+            run_stats: HashMap::new(),
+            peaks: HashMap::new(),
+            idle_since: HashMap::new(),
+            seen_active: HashSet::new(),
             processes: HashMap::new(),
-            compute_apps: Vec::new(),
+            process_ttl,
+            compute_apps: HashMap::new(),
+            vram_growing: HashSet::new(),
+            compute_apps_seeded: false,
+            apps_grace: Duration::ZERO,
+            apps_seen_at: None,
+            gpu_filter: None,
             process_sys_info: HashMap::new(),
+            encoder_sessions: None,
+            xid_events: None,
             gpu_info: HashMap::new(),
             topology: None,
+            cuda_version: None,
         }
     }
 
     // ========== DMON data ==========
     pub fn add_sample(&mut self, sample: GpuSample) {
         let gpu_idx = sample.gpu_idx;
-        self.gpus
+        if !self.shows_gpu(gpu_idx) {
+            return;
+        }
+        let peak = self.peaks.entry(gpu_idx).or_insert_with(|| GpuSample {
+            gpu_idx,
+            ..Default::default()
+        });
+        raise_peaks(peak, &sample);
+        match sample.sm_util {
+            Some(0) => {
+                self.idle_since.entry(gpu_idx).or_insert_with(Instant::now);
+            }
+            Some(_) => {
+                self.idle_since.remove(&gpu_idx);
+                self.seen_active.insert(gpu_idx);
+            }
+            None => {}
+        }
+        let history = self
+            .gpus
             .entry(gpu_idx)
-            .or_insert_with(|| GpuHistory::new(self.max_samples))
-            .push(sample);
+            .or_insert_with(|| GpuHistory::new(self.max_samples));
+        history.push(sample);
+        // Same spacing as the history, so energy follows --dmon-timestamps too
+        let interval = history.last_interval();
+        if let Some(sample) = history.latest() {
+            self.run_stats
+                .entry(gpu_idx)
+                .or_insert_with(|| GpuRunStats::new(Instant::now()))
+                .record(sample, interval);
+        }
         self.total_samples += 1;
     }
 
+    /// Field-wise highest readings for a GPU since startup or the last
+    /// `reset_peaks`
+    pub fn peaks(&self, idx: u32) -> Option<&GpuSample> {
+        self.peaks.get(&idx)
+    }
+
+    /// Change the retention window for every GPU, including ones that show
+    /// up later. Shrinking drops the oldest samples
+    pub fn set_history(&mut self, history_seconds: u64) {
+        self.max_samples = history_seconds as usize;
+        for history in self.gpus.values_mut() {
+            history.set_capacity(self.max_samples);
+        }
+    }
+
+    /// Start fresh high-water marks, e.g. for a new experiment; history and
+    /// run stats are untouched
+    pub fn reset_peaks(&mut self) {
+        self.peaks.clear();
+    }
+
+    /// Total power draw averaged over the last `window`: each GPU's mean
+    /// over its samples in the window, summed. None without any readings
+    pub fn power_average(&self, window: Duration) -> Option<f64> {
+        let since = Instant::now().checked_sub(window).unwrap_or(self.start_time);
+        self.gpus
+            .values()
+            .filter_map(|h| h.average_since(since, |s| s.power_w))
+            .fold(None, |total, avg| Some(total.unwrap_or(0.0) + avg))
+    }
+
+    /// How long a GPU has been at 0% SM utilization; None while it's busy
+    /// or before its first sample
+    pub fn idle_for(&self, idx: u32) -> Option<Duration> {
+        self.idle_since.get(&idx).map(|since| since.elapsed())
+    }
+
+    /// Whether a GPU has been idle ever since monitoring started, so its
+    /// idle time is only a lower bound
+    pub fn idle_since_start(&self, idx: u32) -> bool {
+        self.idle_since.contains_key(&idx) && !self.seen_active.contains(&idx)
+    }
+
     pub fn get_gpu(&self, idx: u32) -> Option<&GpuHistory> {
         self.gpus.get(&idx)
     }
@@ -162,6 +643,26 @@ impl DataStore {
         indices
     }
 
+    /// GPU indices in GPU table order: by index, or highest reading first.
+    /// The sort is stable, so ties and GPUs without samples keep index order
+    pub fn sorted_gpu_indices(&self, sort: GpuSort) -> Vec<u32> {
+        let mut indices = self.gpu_indices();
+        let extractor: fn(&GpuSample) -> Option<u32> = match sort {
+            GpuSort::Index => return indices,
+            GpuSort::Temp => |s| s.gpu_temp_c,
+            GpuSort::Power => |s| s.power_w,
+            GpuSort::Sm => |s| s.sm_util,
+        };
+        let value = |idx: &u32| {
+            self.get_gpu(*idx)
+                .and_then(|h| h.latest())
+                .and_then(extractor)
+                .unwrap_or(0)
+        };
+        indices.sort_by_key(|idx| std::cmp::Reverse(value(idx)));
+        indices
+    }
+
     pub fn total_samples(&self) -> u64 {
         self.total_samples
     }
@@ -172,14 +673,18 @@ impl DataStore {
 
     // ========== PMON data ==========
     pub fn add_process_sample(&mut self, sample: ProcessSample) {
+        if !self.shows_gpu(sample.gpu_idx) {
+            return;
+        }
         let key = (sample.gpu_idx, sample.pid);
         self.processes.insert(key, ProcessInfo {
             sample,
             last_seen: Instant::now(),
         });
 
-        let cutoff = Instant::now() - std::time::Duration::from_secs(5);
-        self.processes.retain(|_, v| v.last_seen > cutoff);
+        let now = Instant::now();
+        let ttl = self.process_ttl;
+        self.processes.retain(|_, v| now.duration_since(v.last_seen) <= ttl);
     }
 
     #[allow(dead_code)]
@@ -190,21 +695,158 @@ impl DataStore {
     }
 
     // ========== Compute Apps ==========
-    pub fn update_compute_apps(&mut self, apps: Vec<ComputeApp>) {
-        self.compute_apps = apps;
+    /// Merge a fresh compute-apps query. Apps missing from it are kept until
+    /// the process TTL expires, so one flaky query doesn't blank the table.
+    /// Returns the apps that appeared or expired, for the event log.
+    pub fn update_compute_apps(&mut self, apps: Vec<ComputeApp>) -> Vec<ProcessEvent> {
+        let now = Instant::now();
+        if apps.is_empty() && self.holding_apps() {
+            return Vec::new();
+        }
+        if !apps.is_empty() {
+            self.apps_seen_at = Some(now);
+        }
+
+        let mut events = Vec::new();
+        for app in apps {
+            let key = (app.gpu_uuid.clone(), app.pid);
+            match self.compute_apps.get(&key) {
+                Some((prev, _)) if app.vram_used_mib > prev.vram_used_mib => {
+                    self.vram_growing.insert(key.clone());
+                }
+                Some(_) => {
+                    self.vram_growing.remove(&key);
+                }
+                None if self.compute_apps_seeded => events.push(ProcessEvent::Started(app.clone())),
+                None => {}
+            }
+            self.compute_apps.insert(key, (app, now));
+        }
+        self.compute_apps_seeded = true;
+
+        let ttl = self.process_ttl;
+        self.compute_apps.retain(|_, (app, seen)| {
+            let alive = now.duration_since(*seen) <= ttl;
+            if !alive {
+                events.push(ProcessEvent::Ended(app.clone()));
+            }
+            alive
+        });
+        let apps = &self.compute_apps;
+        self.vram_growing.retain(|key| apps.contains_key(key));
+        events
+    }
+
+    /// Keep data only for these GPUs (`--gpus`); the rest are dropped as
+    /// they arrive, so every view and total covers just the chosen slice.
+    /// Their details are still kept for UUID and PCI lookups
+    pub fn set_gpu_filter(&mut self, indices: &[u32]) {
+        self.gpu_filter = Some(indices.iter().copied().collect());
+    }
+
+    pub fn shows_gpu(&self, idx: u32) -> bool {
+        self.gpu_filter.as_ref().is_none_or(|f| f.contains(&idx))
+    }
+
+    /// Keep listed apps through empty compute-apps results for up to
+    /// `grace` after the last non-empty one
+    pub fn set_apps_grace(&mut self, grace: Duration) {
+        self.apps_grace = grace;
+    }
+
+    /// Whether an empty compute-apps result (and the empty ps query that
+    /// follows it) should be ignored as a likely driver hiccup
+    fn holding_apps(&self) -> bool {
+        !self.compute_apps.is_empty() && self.apps_seen_at.is_some_and(|at| at.elapsed() < self.apps_grace)
+    }
+
+    /// GPU index for a UUID reported by compute-apps
+    pub fn gpu_index_for_uuid(&self, uuid: &str) -> Option<u32> {
+        self.gpu_info.values().find(|g| g.uuid == uuid).map(|g| g.index)
+    }
+
+    /// GPUs CUDA would see under `CUDA_VISIBLE_DEVICES=spec`, in CUDA's
+    /// order (CUDA device 0 first). Entries are indices or (prefixes of)
+    /// UUIDs; like CUDA, the list ends at the first entry that matches no
+    /// GPU, so "" and "-1" hide them all
+    pub fn cuda_visible_gpus(&self, spec: &str) -> Vec<u32> {
+        let mut gpus = Vec::new();
+        for entry in spec.split(',').map(str::trim) {
+            let gpu = match entry.parse::<u32>() {
+                Ok(idx) => self.gpu_info.contains_key(&idx).then_some(idx),
+                Err(_) if entry.starts_with("GPU-") => {
+                    let mut matches = self.gpu_info.values().filter(|g| g.uuid.starts_with(entry));
+                    // An ambiguous prefix is invalid to CUDA too
+                    match (matches.next(), matches.next()) {
+                        (Some(g), None) => Some(g.index),
+                        _ => None,
+                    }
+                }
+                Err(_) => None,
+            };
+            match gpu {
+                Some(idx) if !gpus.contains(&idx) => gpus.push(idx),
+                _ => break,
+            }
+        }
+        gpus
     }
 
     // ========== Process System Info ==========
     pub fn update_process_sys_info(&mut self, infos: Vec<ProcessSystemInfo>) {
+        if infos.is_empty() && self.holding_apps() {
+            return;
+        }
         self.process_sys_info.clear();
         for info in infos {
             self.process_sys_info.insert(info.pid, info);
         }
     }
 
+    pub fn update_encoder_sessions(&mut self, sessions: Vec<EncoderSession>) {
+        let mut counts = HashMap::new();
+        for session in sessions {
+            *counts.entry((session.gpu_idx, session.pid)).or_insert(0) += 1;
+        }
+        self.encoder_sessions = Some(counts);
+    }
+
+    /// Replace the Xid reports with the kernel log's current ones, returning
+    /// those not seen before. A repeated report counts once per repeat
+    pub fn update_xid_events(&mut self, events: Vec<XidEvent>) -> Vec<XidEvent> {
+        let mut seen = self.xid_events.take().unwrap_or_default();
+        let new = events
+            .iter()
+            .filter(|event| match seen.iter().position(|s| s == *event) {
+                Some(i) => {
+                    seen.swap_remove(i);
+                    false
+                }
+                None => true,
+            })
+            .cloned()
+            .collect();
+        self.xid_events = Some(events);
+        new
+    }
+
+    /// Xid reports for a GPU, oldest first; None when the kernel log isn't read
+    pub fn xid_events(&self, gpu_idx: u32) -> Option<Vec<&XidEvent>> {
+        let info = self.gpu_info.get(&gpu_idx);
+        let events = self.xid_events.as_ref()?;
+        Some(events.iter().filter(|e| info.is_some_and(|g| g.pci_matches(&e.pci))).collect())
+    }
+
+    /// The GPU a kernel log PCI address belongs to
+    pub fn gpu_index_for_pci(&self, pci: &str) -> Option<u32> {
+        self.gpu_info.values().find(|g| g.pci_matches(pci)).map(|g| g.index)
+    }
+
     // ========== Enriched Process View ==========
-    /// Get enriched process data combining all sources
-    pub fn get_enriched_processes(&self) -> Vec<EnrichedProcess> {
+    /// Get enriched process data combining all sources. With `hide_idle`,
+    /// processes pmon reports no SM or memory activity for (all `-`) and
+    /// whose VRAM isn't growing are left out
+    pub fn get_enriched_processes(&self, hide_idle: bool) -> Vec<EnrichedProcess> {
         let mut result = Vec::new();
 
         // Build GPU index lookup from UUID
@@ -214,30 +856,86 @@ impl DataStore {
             .collect();
 
         // Group compute apps by (pid, gpu_idx)
-        for app in &self.compute_apps {
+        for (key, (app, _)) in &self.compute_apps {
             let gpu_idx = uuid_to_idx.get(app.gpu_uuid.as_str()).copied().unwrap_or(0);
+            if !self.shows_gpu(gpu_idx) {
+                continue;
+            }
 
             // Get pmon data if available
             let pmon = self.processes.get(&(gpu_idx, app.pid));
 
+            if hide_idle {
+                let busy = pmon.is_some_and(|p| p.sample.sm_util.unwrap_or(0) > 0 || p.sample.mem_util.unwrap_or(0) > 0);
+                if !busy && !self.vram_growing.contains(key) {
+                    continue;
+                }
+            }
+
             // Get system info if available
             let sys_info = self.process_sys_info.get(&app.pid);
 
             let enriched = EnrichedProcess {
                 pid: app.pid,
-                command: app.name.split('/').last().unwrap_or(&app.name).to_string(),
+                command: app.name.split('/').next_back().unwrap_or(&app.name).to_string(),
+                path: app.name.clone(),
+                args: sys_info.map(|s| s.args.clone()).unwrap_or_default(),
+                container: sys_info.and_then(|s| s.container.clone()),
+                user: sys_info.map(|s| s.user.clone()),
                 gpu_idx,
-                vram_mib: app.vram_used_mib,
+                gpus: vec![gpu_idx],
+                vram_mib: Some(app.vram_used_mib),
                 sm_util: pmon.and_then(|p| p.sample.sm_util),
+                mem_util: pmon.and_then(|p| p.sample.mem_util),
+                enc_sessions: self
+                    .encoder_sessions
+                    .as_ref()
+                    .map(|counts| counts.get(&(gpu_idx, app.pid)).copied().unwrap_or(0)),
                 cpu_percent: sys_info.map(|s| s.cpu_percent).unwrap_or(0.0),
                 rss_mb: sys_info.map(|s| s.rss_kb / 1024).unwrap_or(0),
                 elapsed: sys_info.map(|s| s.elapsed.clone()).unwrap_or_default(),
+                runtime: sys_info.and_then(|s| s.runtime),
             };
 
             result.push(enriched);
         }
 
-        // Sort by GPU then by VRAM usage (descending)
+        // pmon also sees processes compute-apps doesn't list (e.g. graphics
+        // clients); show them with their utilization and unknown VRAM
+        let listed: HashSet<(u32, u32)> = result.iter().map(|p| (p.gpu_idx, p.pid)).collect();
+        for (&(gpu_idx, pid), info) in &self.processes {
+            if listed.contains(&(gpu_idx, pid)) {
+                continue;
+            }
+            let pmon = &info.sample;
+            if hide_idle && pmon.sm_util.unwrap_or(0) == 0 && pmon.mem_util.unwrap_or(0) == 0 {
+                continue;
+            }
+            let sys_info = self.process_sys_info.get(&pid);
+            result.push(EnrichedProcess {
+                pid,
+                command: pmon.command.clone(),
+                path: pmon.command.clone(),
+                args: sys_info.map(|s| s.args.clone()).unwrap_or_default(),
+                container: sys_info.and_then(|s| s.container.clone()),
+                user: sys_info.map(|s| s.user.clone()),
+                gpu_idx,
+                gpus: vec![gpu_idx],
+                vram_mib: None,
+                sm_util: pmon.sm_util,
+                mem_util: pmon.mem_util,
+                enc_sessions: self
+                    .encoder_sessions
+                    .as_ref()
+                    .map(|counts| counts.get(&(gpu_idx, pid)).copied().unwrap_or(0)),
+                cpu_percent: sys_info.map(|s| s.cpu_percent).unwrap_or(0.0),
+                rss_mb: sys_info.map(|s| s.rss_kb / 1024).unwrap_or(0),
+                elapsed: sys_info.map(|s| s.elapsed.clone()).unwrap_or_default(),
+                runtime: sys_info.and_then(|s| s.runtime),
+            });
+        }
+
+        // Sort by GPU then by VRAM usage (descending), unknown VRAM last
         result.sort_by(|a, b| {
             a.gpu_idx.cmp(&b.gpu_idx)
                 .then(b.vram_mib.cmp(&a.vram_mib))
@@ -246,19 +944,83 @@ impl DataStore {
         result
     }
 
+    /// Rows for the process table, optionally merging a PID seen on several
+    /// GPUs (e.g. under MPS) into a single row
+    pub fn get_process_rows(&self, merge_pids: bool, hide_idle: bool) -> Vec<EnrichedProcess> {
+        let processes = self.get_enriched_processes(hide_idle);
+        if merge_pids {
+            merge_by_pid(processes)
+        } else {
+            processes
+        }
+    }
+
+    /// GPU processes grouped by owner across all GPUs, most VRAM first.
+    /// A process spanning several GPUs counts once
+    pub fn usage_by_user(&self) -> Vec<UserUsage> {
+        let mut users: Vec<UserUsage> = Vec::new();
+        for p in merge_by_pid(self.get_enriched_processes(false)) {
+            let user = p.user.as_deref().unwrap_or(UNKNOWN_USER);
+            let pos = match users.iter().position(|u| u.user == user) {
+                Some(pos) => pos,
+                None => {
+                    users.push(UserUsage { user: user.to_string(), processes: 0, gpus: Vec::new(), vram_mib: 0 });
+                    users.len() - 1
+                }
+            };
+            let usage = &mut users[pos];
+            usage.processes += 1;
+            usage.gpus.extend(p.gpus);
+            usage.vram_mib += p.vram_mib.unwrap_or(0);
+        }
+        for usage in &mut users {
+            usage.gpus.sort();
+            usage.gpus.dedup();
+        }
+        users.sort_by(|a, b| b.vram_mib.cmp(&a.vram_mib).then_with(|| a.user.cmp(&b.user)));
+        users
+    }
+
     // ========== Query GPU data ==========
     pub fn update_gpu_info(&mut self, info: Vec<GpuInfo>) {
+        let now = Instant::now();
         for gpu in info {
+            if self.shows_gpu(gpu.index) {
+                let stats = self.run_stats.entry(gpu.index).or_insert_with(|| GpuRunStats::new(now));
+                stats.peak_vram_mib = stats.peak_vram_mib.max(gpu.memory_used_mib);
+            }
             self.gpu_info.insert(gpu.index, gpu);
         }
     }
 
+    /// Whole-run aggregates for a GPU
+    pub fn run_stats(&self, idx: u32) -> Option<&GpuRunStats> {
+        self.run_stats.get(&idx)
+    }
+
     pub fn get_gpu_info(&self, idx: u32) -> Option<&GpuInfo> {
         self.gpu_info.get(&idx)
     }
 
+    /// VRAM in use on a GPU beyond what compute-apps lists for it: driver
+    /// reservations, graphics clients, processes in other PID namespaces.
+    /// None until compute-apps has been read
+    pub fn unaccounted_vram(&self, idx: u32) -> Option<u64> {
+        if !self.compute_apps_seeded {
+            return None;
+        }
+        let info = self.gpu_info.get(&idx)?;
+        let listed: u64 = self
+            .compute_apps
+            .values()
+            .filter(|(app, _)| app.gpu_uuid == info.uuid)
+            .map(|(app, _)| app.vram_used_mib)
+            .sum();
+        Some(info.memory_used_mib.saturating_sub(listed))
+    }
+
     pub fn all_gpu_info(&self) -> Vec<&GpuInfo> {
-        let mut infos: Vec<_> = self.gpu_info.values().collect();
+        let mut infos: Vec<_> = self.gpu_info.values().filter(|g| self.shows_gpu(g.index)).collect();
         infos.sort_by_key(|i| i.index);
         infos
     }
@@ -271,4 +1033,635 @@ impl DataStore {
     pub fn get_topology(&self) -> Option<&GpuTopology> {
         self.topology.as_ref()
     }
+
+    // ========== Versions ==========
+    pub fn set_cuda_version(&mut self, version: Option<String>) {
+        self.cuda_version = version;
+    }
+
+    /// "Driver 550.54.15 / CUDA 12.4", or whichever half is known
+    pub fn version_label(&self) -> Option<String> {
+        let driver = self
+            .all_gpu_info()
+            .into_iter()
+            .map(|g| g.driver_version.as_str())
+            .find(|v| !v.is_empty());
+        match (driver, self.cuda_version.as_deref()) {
+            (Some(driver), Some(cuda)) => Some(format!("Driver {} / CUDA {}", driver, cuda)),
+            (Some(driver), None) => Some(format!("Driver {}", driver)),
+            (None, Some(cuda)) => Some(format!("CUDA {}", cuda)),
+            (None, None) => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn history_with(count: u32) -> GpuHistory {
+        let mut history = GpuHistory::new(count as usize);
+        for i in 0..count {
+            history.push(GpuSample {
+                sm_util: Some(i % 100),
+                ..Default::default()
+            });
+        }
+        history
+    }
+
+    #[test]
+    fn test_device_time_spaces_samples() {
+        let at = |secs| GpuSample {
+            device_time: chrono::NaiveTime::from_num_seconds_from_midnight_opt(secs, 0),
+            ..Default::default()
+        };
+        let mut history = GpuHistory::new(10);
+        history.push(at(86_398));
+        std::thread::sleep(Duration::from_millis(20));
+        // Arrives 20ms later but was stamped 2s later, across midnight
+        history.push(at(0));
+        let first = history.samples[0].timestamp;
+        let second = history.samples[1].timestamp;
+        // Capped at arrival time, since the device time is ahead of it
+        assert!(second.duration_since(first) < Duration::from_secs(2));
+        assert_eq!(history.last_interval(), Some(second.duration_since(first)));
+
+        let mut history = GpuHistory::new(10);
+        history.push(at(100));
+        std::thread::sleep(Duration::from_millis(20));
+        history.push(at(100));
+        // Same device second: the arrival jitter is dropped
+        assert_eq!(history.samples[0].timestamp, history.samples[1].timestamp);
+    }
+
+    #[test]
+    fn test_run_stats_integrate_energy() {
+        let start = Instant::now();
+        let mut stats = GpuRunStats::new(start);
+        let sample = |power, sm| GpuSample {
+            power_w: Some(power),
+            sm_util: Some(sm),
+            ..Default::default()
+        };
+        let half_hour = Some(Duration::from_secs(1800));
+        stats.record(&sample(100, 0), None);
+        stats.record(&sample(300, 50), half_hour);
+        stats.record(&sample(200, 100), half_hour);
+
+        // 100W for half an hour, then 300W for half an hour
+        assert!((stats.energy_wh - 200.0).abs() < 1e-9);
+        assert_eq!(stats.samples, 3);
+        assert_eq!(stats.power.min, Some(100));
+        assert_eq!(stats.power.max, Some(300));
+        assert_eq!(stats.sm.avg(), Some(50.0));
+        assert_eq!(stats.temp.avg(), None);
+    }
+
+    #[test]
+    fn test_peak_since_ignores_older_samples() {
+        let mut history = history_with(50);
+        let since = Instant::now();
+        assert_eq!(history.peak_since(since, |s| s.sm_util), None);
+        history.push(GpuSample { sm_util: Some(7), ..Default::default() });
+        history.push(GpuSample { sm_util: None, ..Default::default() });
+        assert_eq!(history.peak_since(since, |s| s.sm_util), Some(7));
+    }
+
+    fn process(pid: u32, gpu_idx: u32, vram_mib: Option<u64>) -> EnrichedProcess {
+        EnrichedProcess {
+            pid,
+            command: "python".into(),
+            path: "/usr/bin/python".into(),
+            args: String::new(),
+            container: None,
+            user: None,
+            gpu_idx,
+            gpus: vec![gpu_idx],
+            vram_mib,
+            sm_util: Some(gpu_idx * 10),
+            mem_util: None,
+            enc_sessions: None,
+            cpu_percent: 0.0,
+            rss_mb: 0,
+            elapsed: String::new(),
+            runtime: None,
+        }
+    }
+
+    #[test]
+    fn test_merge_by_pid_sums_vram_across_gpus() {
+        let rows = merge_by_pid(vec![
+            process(100, 0, Some(1000)),
+            process(200, 0, Some(500)),
+            process(100, 1, Some(2000)),
+            process(100, 2, Some(3000)),
+            // pmon-only on this GPU: adds no VRAM
+            process(100, 3, None),
+        ]);
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].pid, 100);
+        assert_eq!(rows[0].vram_mib, Some(6000));
+        assert_eq!(rows[0].gpu_label(), "0,1,2,3");
+        assert_eq!(rows[0].sm_util, Some(30));
+        assert_eq!(rows[1].gpu_label(), "0");
+    }
+
+    #[test]
+    fn test_chart_data_fits_width() {
+        let history = history_with(300);
+        assert_eq!(history.chart_data(1000, Instant::now(), |s| s.sm_util).len(), 300);
+        assert!(history.chart_data(100, Instant::now(), |s| s.sm_util).len() <= 100);
+    }
+
+    #[test]
+    fn test_set_history_resizes() {
+        let mut data = DataStore::new(10, Duration::from_secs(5));
+        let sample = |sm| GpuSample { sm_util: Some(sm), ..Default::default() };
+        for sm in 0..10 {
+            data.add_sample(sample(sm));
+        }
+
+        // Shrinking keeps the newest samples
+        data.set_history(4);
+        let history = data.get_gpu(0).unwrap();
+        assert_eq!(history.len(), 4);
+        assert_eq!(history.latest().unwrap().sm_util, Some(9));
+
+        // Growing lets new samples accumulate past the old size
+        data.set_history(20);
+        for sm in 10..25 {
+            data.add_sample(sample(sm));
+        }
+        assert_eq!(data.get_gpu(0).unwrap().len(), 19);
+        data.add_sample(sample(25));
+        assert_eq!(data.get_gpu(0).unwrap().len(), 20);
+
+        // GPUs first seen later get the new size too
+        for _ in 0..30 {
+            data.add_sample(GpuSample { gpu_idx: 1, ..Default::default() });
+        }
+        assert_eq!(data.get_gpu(1).unwrap().len(), 20);
+    }
+
+    #[test]
+    fn test_chart_data_follows_resize() {
+        let history = history_with(300);
+        let narrow = history.chart_data(40, Instant::now(), |s| s.sm_util);
+        let wide = history.chart_data(160, Instant::now(), |s| s.sm_util);
+        assert!(narrow.len() <= 40);
+        assert!(wide.len() <= 160);
+        assert!(wide.len() > narrow.len());
+        // The newest point stays at the right edge regardless of width
+        assert_eq!(narrow.last().map(|p| p.0 <= 0.0), Some(true));
+        assert!((narrow.last().unwrap().0 - wide.last().unwrap().0).abs() < 1.0);
+    }
+
+    #[test]
+    fn test_runtime_formats() {
+        assert_eq!(format_relative(Duration::from_secs(45)), "45s");
+        assert_eq!(format_relative(Duration::from_secs(12 * 60 + 5)), "12m 5s");
+        assert_eq!(format_relative(Duration::from_secs(3 * 3600 + 12 * 60 + 9)), "3h 12m");
+        assert_eq!(format_relative(Duration::from_secs(2 * 86400 + 3 * 3600 + 59)), "2d 3h");
+
+        let now = Local.with_ymd_and_hms(2026, 3, 4, 9, 30, 0).unwrap();
+        assert_eq!(format_started(Duration::from_secs(3600), now), "08:30");
+        assert_eq!(format_started(Duration::from_secs(86400 + 3600), now), "08:30, Mar 3");
+
+        let mut p = process(1, 0, Some(0));
+        p.elapsed = "01:02:03".into();
+        assert_eq!(p.runtime_text(TimeFormat::Relative), "01:02:03");
+        p.runtime = Some(Duration::from_secs(3723));
+        assert_eq!(p.runtime_text(TimeFormat::Etime), "01:02:03");
+        assert_eq!(p.runtime_text(TimeFormat::Relative), "1h 2m");
+    }
+
+    #[test]
+    fn test_cuda_visible_gpus_maps_indices_and_uuids() {
+        let mut data = DataStore::new(60, Duration::from_secs(5));
+        data.update_gpu_info(
+            ["GPU-aaaa", "GPU-abbb", "GPU-cccc", "GPU-dddd"]
+                .iter()
+                .enumerate()
+                .map(|(i, uuid)| GpuInfo { index: i as u32, uuid: uuid.to_string(), ..Default::default() })
+                .collect(),
+        );
+
+        assert_eq!(data.cuda_visible_gpus("3,1"), vec![3, 1]);
+        assert_eq!(data.cuda_visible_gpus("GPU-cccc, 0"), vec![2, 0]);
+        assert_eq!(data.cuda_visible_gpus("GPU-d"), vec![3]);
+        // Ambiguous prefix, unknown index: the list stops there
+        assert_eq!(data.cuda_visible_gpus("2,GPU-a,0"), vec![2]);
+        assert_eq!(data.cuda_visible_gpus("1,7,0"), vec![1]);
+        assert!(data.cuda_visible_gpus("").is_empty());
+        assert!(data.cuda_visible_gpus("-1").is_empty());
+    }
+
+    #[test]
+    fn test_sm_histogram_buckets() {
+        let mut history = GpuHistory::new(10);
+        for sm in [Some(0), Some(9), Some(10), Some(55), Some(100), None] {
+            history.push(GpuSample {
+                sm_util: sm,
+                ..Default::default()
+            });
+        }
+        // 100% lands in the top bucket; samples without a reading are skipped
+        assert_eq!(history.sm_histogram(), [2, 1, 0, 0, 0, 1, 0, 0, 0, 1]);
+    }
+
+    #[test]
+    fn test_value_at_picks_nearest_sample() {
+        let mut history = GpuHistory::new(10);
+        for temp in [Some(80), None, Some(84)] {
+            history.push(GpuSample {
+                gpu_temp_c: temp,
+                ..Default::default()
+            });
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        // Newest first: 84 (~20ms ago), no reading, 80 (~60ms ago)
+        assert_eq!(history.value_at(Instant::now(), 0.0, |s| s.gpu_temp_c).map(|(_, v)| v), Some(84));
+        assert_eq!(history.value_at(Instant::now(), 10.0, |s| s.gpu_temp_c).map(|(_, v)| v), Some(80));
+        let (ago, _) = history.value_at(Instant::now(), 10.0, |s| s.gpu_temp_c).unwrap();
+        assert!((0.06..1.0).contains(&ago));
+        assert_eq!(history.value_at(Instant::now(), 0.0, |s| s.sm_util), None);
+    }
+
+    #[test]
+    fn test_reset_peaks_keeps_history() {
+        let mut data = DataStore::new(60, Duration::from_secs(5));
+        for (power, temp) in [(300, 50), (100, 70)] {
+            data.add_sample(GpuSample {
+                power_w: Some(power),
+                gpu_temp_c: Some(temp),
+                ..Default::default()
+            });
+        }
+        let peaks = data.peaks(0).unwrap();
+        assert_eq!((peaks.power_w, peaks.gpu_temp_c, peaks.sm_util), (Some(300), Some(70), None));
+
+        data.reset_peaks();
+        assert!(data.peaks(0).is_none());
+        assert_eq!(data.get_gpu(0).unwrap().len(), 2);
+
+        data.add_sample(GpuSample {
+            power_w: Some(150),
+            ..Default::default()
+        });
+        assert_eq!(data.peaks(0).unwrap().power_w, Some(150));
+    }
+
+    #[test]
+    fn test_hide_idle_processes() {
+        let app = |pid, vram_used_mib| ComputeApp {
+            pid,
+            name: "python".into(),
+            gpu_uuid: "GPU-aaaa".into(),
+            vram_used_mib,
+        };
+        let pmon = |pid, sm_util| ProcessSample {
+            gpu_idx: 0,
+            pid,
+            process_type: "C".into(),
+            sm_util,
+            mem_util: None,
+            enc_util: None,
+            dec_util: None,
+            command: "python".into(),
+        };
+
+        let mut data = DataStore::new(60, Duration::from_secs(60));
+        data.update_compute_apps(vec![app(1, 500), app(2, 500), app(3, 500)]);
+        data.update_compute_apps(vec![app(1, 500), app(2, 500), app(3, 900)]);
+        data.add_process_sample(pmon(1, Some(30)));
+        // All `-`: present but idle
+        data.add_process_sample(pmon(2, None));
+
+        let pids = |procs: Vec<EnrichedProcess>| {
+            let mut pids: Vec<u32> = procs.iter().map(|p| p.pid).collect();
+            pids.sort();
+            pids
+        };
+        assert_eq!(pids(data.get_enriched_processes(false)), vec![1, 2, 3]);
+        assert_eq!(pids(data.get_enriched_processes(true)), vec![1, 3]);
+
+        // VRAM stopped growing
+        data.update_compute_apps(vec![app(1, 500), app(2, 500), app(3, 900)]);
+        assert_eq!(pids(data.get_enriched_processes(true)), vec![1]);
+    }
+
+    #[test]
+    fn test_gpu_filter() {
+        let mut data = DataStore::new(60, Duration::from_secs(60));
+        data.set_gpu_filter(&[1]);
+        for gpu_idx in [0, 1] {
+            data.add_sample(GpuSample { gpu_idx, sm_util: Some(50), ..Default::default() });
+        }
+        data.update_gpu_info(vec![
+            GpuInfo { index: 0, uuid: "GPU-aaaa".into(), ..Default::default() },
+            GpuInfo { index: 1, uuid: "GPU-bbbb".into(), ..Default::default() },
+        ]);
+        assert_eq!(data.gpu_indices(), vec![1]);
+        assert_eq!(data.all_gpu_info().len(), 1);
+        assert!(data.run_stats(0).is_none());
+        // Hidden GPUs still resolve, e.g. for the CUDA_VISIBLE_DEVICES banner
+        assert_eq!(data.gpu_index_for_uuid("GPU-aaaa"), Some(0));
+    }
+
+    #[test]
+    fn test_pmon_only_processes() {
+        let mut data = DataStore::new(60, Duration::from_secs(60));
+        data.update_compute_apps(vec![ComputeApp {
+            pid: 1,
+            name: "/usr/bin/python".into(),
+            gpu_uuid: "GPU-aaaa".into(),
+            vram_used_mib: 500,
+        }]);
+        let pmon = |pid, process_type: &str, command: &str, sm_util| ProcessSample {
+            gpu_idx: 0,
+            pid,
+            process_type: process_type.into(),
+            sm_util,
+            mem_util: None,
+            enc_util: None,
+            dec_util: None,
+            command: command.into(),
+        };
+        data.add_process_sample(pmon(1, "C", "python", Some(40)));
+        // A graphics client compute-apps never lists
+        data.add_process_sample(pmon(2, "G", "Xorg", Some(3)));
+        data.add_process_sample(pmon(3, "G", "gnome-shell", None));
+
+        let procs = data.get_enriched_processes(false);
+        assert_eq!(procs.len(), 3);
+        assert_eq!((procs[0].pid, procs[0].vram_mib, procs[0].sm_util), (1, Some(500), Some(40)));
+        let xorg = procs.iter().find(|p| p.pid == 2).unwrap();
+        assert_eq!((xorg.command.as_str(), xorg.vram_mib, xorg.sm_util), ("Xorg", None, Some(3)));
+
+        // Idle pmon-only processes have no VRAM growth to keep them listed
+        let busy: Vec<u32> = data.get_enriched_processes(true).iter().map(|p| p.pid).collect();
+        assert_eq!(busy, vec![1, 2]);
+    }
+
+    #[test]
+    fn test_usage_by_user() {
+        let mut data = DataStore::new(60, Duration::from_secs(60));
+        data.update_gpu_info(vec![
+            GpuInfo { index: 0, uuid: "GPU-aaaa".into(), ..Default::default() },
+            GpuInfo { index: 1, uuid: "GPU-bbbb".into(), ..Default::default() },
+        ]);
+        let app = |pid, gpu_uuid: &str, vram_used_mib| ComputeApp {
+            pid,
+            name: "python".into(),
+            gpu_uuid: gpu_uuid.into(),
+            vram_used_mib,
+        };
+        data.update_compute_apps(vec![
+            app(1, "GPU-aaaa", 70_000),
+            app(1, "GPU-bbbb", 5_000),
+            app(2, "GPU-bbbb", 1_000),
+            app(3, "GPU-bbbb", 2_000),
+            app(4, "GPU-aaaa", 500),
+        ]);
+        let ps = |line: &str| ProcessSystemInfo::parse_ps_line(line).unwrap();
+        data.update_process_sys_info(vec![
+            ps("1 alice 99.0 1024 01:00 python train.py"),
+            ps("2 bob 1.0 1024 01:00 python eval.py"),
+            ps("3 alice 1.0 1024 01:00 python notebook.py"),
+        ]);
+
+        let usage = data.usage_by_user();
+        let summary: Vec<_> = usage.iter().map(|u| (u.user.as_str(), u.processes, u.gpus.clone(), u.vram_mib)).collect();
+        assert_eq!(
+            summary,
+            vec![
+                // PID 1 spans both GPUs but is one process
+                ("alice", 2, vec![0, 1], 77_000),
+                ("bob", 1, vec![1], 1_000),
+                // ps hasn't reported PID 4
+                (UNKNOWN_USER, 1, vec![0], 500),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unaccounted_vram() {
+        let mut data = DataStore::new(60, Duration::from_secs(60));
+        data.update_gpu_info(vec![
+            GpuInfo { index: 0, uuid: "GPU-aaaa".into(), memory_used_mib: 12_000, ..Default::default() },
+            GpuInfo { index: 1, uuid: "GPU-bbbb".into(), memory_used_mib: 300, ..Default::default() },
+        ]);
+        assert_eq!(data.unaccounted_vram(0), None);
+
+        let app = |pid, gpu_uuid: &str, vram_used_mib| ComputeApp {
+            pid,
+            name: "python".into(),
+            gpu_uuid: gpu_uuid.into(),
+            vram_used_mib,
+        };
+        data.update_compute_apps(vec![app(1, "GPU-aaaa", 1500), app(2, "GPU-aaaa", 500)]);
+        assert_eq!(data.unaccounted_vram(0), Some(10_000));
+        assert_eq!(data.unaccounted_vram(1), Some(300));
+        assert_eq!(data.unaccounted_vram(2), None);
+
+        // Readings taken at different moments can list more than is used
+        data.update_compute_apps(vec![app(1, "GPU-bbbb", 400)]);
+        assert_eq!(data.unaccounted_vram(1), Some(0));
+    }
+
+    #[test]
+    fn test_compute_apps_survive_until_ttl() {
+        let app = ComputeApp {
+            pid: 42,
+            name: "/usr/bin/python".into(),
+            gpu_uuid: "GPU-aaaa".into(),
+            vram_used_mib: 1024,
+        };
+
+        let mut data = DataStore::new(60, Duration::from_secs(60));
+        data.update_compute_apps(vec![app.clone()]);
+        data.update_compute_apps(Vec::new());
+        assert_eq!(data.get_enriched_processes(false).len(), 1);
+
+        let mut data = DataStore::new(60, Duration::ZERO);
+        data.update_compute_apps(vec![app]);
+        std::thread::sleep(Duration::from_millis(5));
+        data.update_compute_apps(Vec::new());
+        assert!(data.get_enriched_processes(false).is_empty());
+    }
+
+    #[test]
+    fn test_empty_compute_apps_held_for_grace() {
+        let app = ComputeApp {
+            pid: 42,
+            name: "/usr/bin/python".into(),
+            gpu_uuid: "GPU-aaaa".into(),
+            vram_used_mib: 1024,
+        };
+        let ps = ProcessSystemInfo::parse_ps_line("42 alice 98.5 1048576 01:02:03 python train.py").unwrap();
+
+        // Past the TTL, but within the grace window: the empty query and
+        // the empty ps batch after it are ignored
+        let mut data = DataStore::new(60, Duration::ZERO);
+        data.set_apps_grace(Duration::from_secs(60));
+        data.update_compute_apps(vec![app.clone()]);
+        data.update_process_sys_info(vec![ps]);
+        std::thread::sleep(Duration::from_millis(5));
+        assert!(data.update_compute_apps(Vec::new()).is_empty());
+        data.update_process_sys_info(Vec::new());
+        let procs = data.get_enriched_processes(false);
+        assert_eq!(procs.len(), 1);
+        assert_eq!(procs[0].args, "python train.py");
+
+        // Without a grace window the TTL alone decides
+        let mut data = DataStore::new(60, Duration::ZERO);
+        data.update_compute_apps(vec![app]);
+        std::thread::sleep(Duration::from_millis(5));
+        assert_eq!(data.update_compute_apps(Vec::new()).len(), 1);
+    }
+
+    #[test]
+    fn test_compute_app_lifecycle_events() {
+        let app = |pid| ComputeApp {
+            pid,
+            name: "/usr/bin/python".into(),
+            gpu_uuid: "GPU-aaaa".into(),
+            vram_used_mib: 1024,
+        };
+
+        let mut data = DataStore::new(60, Duration::ZERO);
+        // Already running at startup: no event
+        assert!(data.update_compute_apps(vec![app(1)]).is_empty());
+
+        let events = data.update_compute_apps(vec![app(1), app(2)]);
+        assert!(matches!(events.as_slice(), [ProcessEvent::Started(a)] if a.pid == 2));
+
+        std::thread::sleep(Duration::from_millis(5));
+        let events = data.update_compute_apps(vec![app(2)]);
+        assert!(matches!(events.as_slice(), [ProcessEvent::Ended(a)] if a.pid == 1));
+    }
+
+    #[test]
+    fn test_new_xid_events() {
+        let xid = |code| XidEvent { pci: "0000:3b:00".into(), code, detail: String::new() };
+        let mut data = DataStore::new(60, Duration::from_secs(5));
+        data.update_gpu_info(vec![GpuInfo { index: 0, pci_bus_id: "00000000:3B:00.0".into(), ..Default::default() }]);
+        assert_eq!(data.xid_events(0), None);
+
+        assert_eq!(data.update_xid_events(vec![xid(79)]).len(), 1);
+        // Same log, then the same error again and a new one
+        assert!(data.update_xid_events(vec![xid(79)]).is_empty());
+        assert_eq!(data.update_xid_events(vec![xid(79), xid(79), xid(48)]), vec![xid(79), xid(48)]);
+
+        assert_eq!(data.xid_events(0).unwrap().len(), 3);
+        assert_eq!(data.gpu_index_for_pci("0000:3b:00"), Some(0));
+        assert_eq!(data.gpu_index_for_pci("0000:41:00"), None);
+    }
+
+    #[test]
+    fn test_idle_since_last_busy_sample() {
+        let sample = |sm| GpuSample { gpu_idx: 0, sm_util: sm, ..Default::default() };
+        let mut data = DataStore::new(60, Duration::from_secs(5));
+        assert_eq!(data.idle_for(0), None);
+
+        data.add_sample(sample(Some(0)));
+        assert!(data.idle_for(0).is_some());
+        assert!(data.idle_since_start(0));
+
+        data.add_sample(sample(Some(35)));
+        assert_eq!(data.idle_for(0), None);
+
+        // Idle time counts from the first idle sample after the busy one;
+        // a missing reading changes nothing
+        data.add_sample(sample(Some(0)));
+        std::thread::sleep(Duration::from_millis(5));
+        data.add_sample(sample(None));
+        data.add_sample(sample(Some(0)));
+        assert!(data.idle_for(0).unwrap() >= Duration::from_millis(5));
+        assert!(!data.idle_since_start(0));
+    }
+
+    #[test]
+    fn test_reselect_gpu_on_changed_gpu_set() {
+        // GPU 2 selected; GPU 1 goes away, then GPUs 2 and 3
+        assert_eq!(reselect_gpu(&[0, 1, 2, 3], &[0, 2, 3], 2), 1);
+        assert_eq!(reselect_gpu(&[0, 2, 3], &[0], 1), 0);
+        // The selected GPU itself goes away: nearest position that exists
+        assert_eq!(reselect_gpu(&[0, 1, 2, 3], &[0, 1, 2], 3), 2);
+        assert_eq!(reselect_gpu(&[0, 1, 2, 3], &[0, 1, 3], 2), 2);
+        assert_eq!(reselect_gpu(&[0, 1], &[], 1), 0);
+        // GPUs reported late don't move the selection off its GPU
+        assert_eq!(reselect_gpu(&[2, 3], &[0, 1, 2, 3], 1), 3);
+    }
+
+    #[test]
+    fn test_poll_only_samples_match_dmon() {
+        let dmon = GpuSample::parse_line("    0     62     40      -     12     30      0      0      -      -   3615   1531").unwrap();
+        let query = GpuSample::from_query("0, 62.31, 40, 12, 30, 3615, 1531").unwrap();
+
+        let latest = |sample| {
+            let mut data = DataStore::new(60, Duration::from_secs(5));
+            data.add_sample(sample);
+            let s = data.get_gpu(0).unwrap().latest().unwrap().clone();
+            (s.power_w, s.gpu_temp_c, s.sm_util, s.mem_util, s.mem_clock_mhz, s.gpu_clock_mhz)
+        };
+        let expected = (Some(62), Some(40), Some(12), Some(30), Some(3615), Some(1531));
+        assert_eq!(latest(dmon), expected);
+        assert_eq!(latest(query), expected);
+    }
+
+    #[test]
+    fn test_power_average_over_window() {
+        let sample = |gpu_idx, power| GpuSample { gpu_idx, power_w: power, ..Default::default() };
+        let mut data = DataStore::new(60, Duration::from_secs(5));
+        assert_eq!(data.power_average(Duration::from_secs(30)), None);
+
+        data.add_sample(sample(0, Some(100)));
+        std::thread::sleep(Duration::from_millis(20));
+        data.add_sample(sample(0, Some(300)));
+        data.add_sample(sample(0, None));
+        data.add_sample(sample(1, Some(50)));
+        assert_eq!(data.power_average(Duration::from_secs(30)), Some(250.0));
+
+        // Only the newer samples are inside a short window
+        assert_eq!(data.power_average(Duration::from_millis(10)), Some(350.0));
+    }
+
+    #[test]
+    fn test_trend() {
+        assert_eq!(trend(&[60.0, 61.0, 62.0, 64.0, 65.0], 0.3), Trend::Rising);
+        assert_eq!(trend(&[300.0, 280.0, 250.0, 240.0], 1.0), Trend::Falling);
+        // Noise around a level, and a change too small for the threshold
+        assert_eq!(trend(&[250.0, 262.0, 244.0, 258.0, 249.0], 1.0), Trend::Steady);
+        assert_eq!(trend(&[70.0, 70.0, 70.0, 70.5], 0.3), Trend::Steady);
+        assert_eq!(trend(&[70.0], 0.3), Trend::Steady);
+        assert_eq!(trend(&[], 0.3), Trend::Steady);
+    }
+
+    #[test]
+    fn test_sorted_gpu_indices() {
+        let mut data = DataStore::new(60, Duration::from_secs(5));
+        for (gpu_idx, temp, power) in [(0, 40, 300), (1, 70, 100), (2, 55, 300)] {
+            data.add_sample(GpuSample {
+                gpu_idx,
+                gpu_temp_c: Some(temp),
+                power_w: Some(power),
+                ..Default::default()
+            });
+        }
+
+        assert_eq!(data.sorted_gpu_indices(GpuSort::Index), vec![0, 1, 2]);
+        assert_eq!(data.sorted_gpu_indices(GpuSort::Temp), vec![1, 2, 0]);
+        // Ties keep index order
+        assert_eq!(data.sorted_gpu_indices(GpuSort::Power), vec![0, 2, 1]);
+        assert_eq!(data.sorted_gpu_indices(GpuSort::Sm), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn test_downsample_averages_buckets() {
+        let points = vec![(-3.0, 10.0), (-2.0, 20.0), (-1.0, 30.0), (0.0, 40.0)];
+        assert_eq!(downsample(points, 2), vec![(-2.0, 15.0), (0.0, 35.0)]);
+    }
 }
@@ -0,0 +1,72 @@
+use std::collections::VecDeque;
+
+use chrono::{DateTime, Local};
+
+/// Entries kept before the oldest are dropped
+const MAX_ENTRIES: usize = 500;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogLevel {
+    Info,
+    Warn,
+}
+
+#[derive(Debug, Clone)]
+pub struct LogEntry {
+    pub time: DateTime<Local>,
+    pub level: LogLevel,
+    pub message: String,
+}
+
+/// Bounded, timestamped history of notable events (process start/exit, warnings)
+#[derive(Debug, Default)]
+pub struct EventLog {
+    entries: VecDeque<LogEntry>,
+}
+
+impl EventLog {
+    pub fn push(&mut self, level: LogLevel, message: String) {
+        if self.entries.len() >= MAX_ENTRIES {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(LogEntry {
+            time: Local::now(),
+            level,
+            message,
+        });
+    }
+
+    pub fn info(&mut self, message: String) {
+        self.push(LogLevel::Info, message);
+    }
+
+    /// Newest entries first
+    pub fn newest_first(&self) -> impl Iterator<Item = &LogEntry> {
+        self.entries.iter().rev()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_log_drops_oldest_past_capacity() {
+        let mut log = EventLog::default();
+        for i in 0..MAX_ENTRIES + 3 {
+            log.info(format!("event {}", i));
+        }
+        assert_eq!(log.len(), MAX_ENTRIES);
+        let newest: Vec<_> = log.newest_first().map(|e| e.message.as_str()).collect();
+        assert_eq!(newest[0], format!("event {}", MAX_ENTRIES + 2));
+        assert_eq!(newest[MAX_ENTRIES - 1], "event 3");
+    }
+}
@@ -1,11 +1,29 @@
+mod alert;
 mod app;
+mod config;
 mod data;
+mod events;
+mod output;
 mod parser;
 mod process;
+mod replay;
 mod ui;
 
-use anyhow::Result;
-use clap::Parser;
+use anyhow::{Context, Result};
+use clap::error::ErrorKind;
+use clap::{CommandFactory, Parser};
+use std::io::{IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+use crate::app::{POP_TITLE, PUSH_TITLE};
+use crate::config::{
+    parse_vram_size, resolve_gpu_filter, Accent, BorderPreset, CommandDisplay, Config, GaugeStyle, GpuSort, QueryFormat,
+    Settings, TimeFormat, VramUnit,
+};
+use crate::output::{diff_snapshots, QueryMetric, Snapshot};
+use crate::process::{NvidiaMonitor, Target};
 
 #[derive(Parser, Debug)]
 #[command(name = "nvidiagpu_top")]
@@ -14,21 +32,444 @@ struct Args {
     /// History retention in seconds
     #[arg(long, default_value = "300")]
     history: u64,
+
+    /// Seconds a process stays listed after it was last reported. pmon reports
+    /// every second and compute-apps is polled every 2s, so keep this above 2
+    #[arg(long, default_value = "5", value_name = "SECS")]
+    process_ttl: u64,
+
+    /// Seconds of total power draw averaged in the status bar (0 to hide)
+    #[arg(long, default_value = "30", value_name = "SECS")]
+    avg_window: u64,
+
+    /// Seconds to keep the process table through empty compute-apps results,
+    /// which a busy driver sometimes returns (0 to trust every result)
+    #[arg(long, default_value = "10", value_name = "SECS")]
+    apps_grace: u64,
+
+    /// Print one JSON object per dmon sample to stdout instead of running
+    /// the terminal UI
+    #[arg(long)]
+    stream_json: bool,
+
+    /// Run as a background service: no terminal UI or terminal access at all,
+    /// shutting down cleanly on SIGTERM. Combine with --stream-json,
+    /// --dump-json or --summary for output
+    #[arg(long)]
+    daemon: bool,
+
+    /// Add power and temperature sparkline columns to the GPU table
+    #[arg(long)]
+    power_temp_sparklines: bool,
+
+    /// Briefly highlight GPU table values that changed (toggle with d)
+    #[arg(long)]
+    highlight_changes: bool,
+
+    /// Show the hostname and wall-clock time in the status bar
+    #[arg(long)]
+    clock: bool,
+
+    /// Set the terminal window/tab title to a GPU summary (count, total power, max temperature)
+    #[arg(long)]
+    set_title: bool,
+
+    /// Read NVIDIA Xid errors from the kernel log with dmesg (may need
+    /// root or kernel.dmesg_restrict=0) and show them in the info overlay
+    #[arg(long)]
+    xid_dmesg: bool,
+
+    /// Save screenshots (S) as plain text instead of ANSI-colored text
+    #[arg(long)]
+    screenshot_plain: bool,
+
+    /// Start the charts view with all GPUs overlaid on one chart (toggle with o)
+    #[arg(long)]
+    overlay_gpus: bool,
+
+    /// Advance the charts' time axis only when a new sample arrives, so
+    /// lines hold still between samples instead of creeping left
+    #[arg(long)]
+    sync_charts: bool,
+
+    /// Monitor a remote host over ssh; repeat to watch several hosts in the cluster view
+    #[arg(long = "host", value_name = "HOST")]
+    hosts: Vec<String>,
+
+    /// Only show these GPU indices, e.g. 0,2. Defaults to NVIDIA_GPU_TOP_GPUS
+    #[arg(long, value_delimiter = ',', value_name = "IDX,...")]
+    gpus: Vec<u32>,
+
+    /// Write the GPU topology as a Graphviz DOT graph to PATH ("-" for stdout) and exit
+    #[arg(long, value_name = "PATH")]
+    topo_dot: Option<PathBuf>,
+
+    /// Ring the terminal bell when a busy GPU goes idle (e.g. a job finished)
+    #[arg(long)]
+    bell_on_idle: bool,
+
+    /// Ring the terminal bell when a GPU reaches critical temperature
+    #[arg(long)]
+    bell_on_crit: bool,
+
+    /// Blink the GPU table row of a GPU at critical temperature
+    #[arg(long)]
+    flash_crit: bool,
+
+    /// Show power draw yellow from PCT percent of the power limit
+    #[arg(long, value_name = "PCT", default_value = "70")]
+    power_warn: f32,
+
+    /// Show power draw red from PCT percent of the power limit
+    #[arg(long, value_name = "PCT", default_value = "90")]
+    power_crit: f32,
+
+    /// Enable `L` to set the selected GPU's power limit (runs nvidia-smi -pl; needs root)
+    #[arg(long)]
+    allow_control: bool,
+
+    /// Panel border style
+    #[arg(long, value_enum, default_value_t = BorderPreset::Plain)]
+    border: BorderPreset,
+
+    /// Sort the GPU table by temp, power or SM utilization, highest first (cycle with s)
+    #[arg(long, value_enum, default_value_t = GpuSort::Index)]
+    sort_gpus: GpuSort,
+
+    /// Hide processes with no SM/memory activity and no growing VRAM (toggle with h)
+    #[arg(long)]
+    hide_idle: bool,
+
+    /// What the process table shows as the command (cycle with a)
+    #[arg(long, value_enum, default_value_t = CommandDisplay::Name)]
+    command: CommandDisplay,
+
+    /// How the process table shows runtimes: etime, relative ("2d 3h") or started ("14:05, Mar 3") (cycle with T)
+    #[arg(long, value_enum, default_value_t = TimeFormat::Etime)]
+    time_format: TimeFormat,
+
+    /// Start with processes merged into one row per PID across GPUs (toggle with g)
+    #[arg(long)]
+    merge_pids: bool,
+
+    /// Start with the process table grouped under a subheader per GPU (toggle with G)
+    #[arg(long)]
+    group_by_gpu: bool,
+
+    /// Decimal places for power readings (dmon reports whole watts, so the GPU
+    /// table switches to query-gpu's power draw when this is above 0)
+    #[arg(long, value_name = "DIGITS", default_value = "0")]
+    power_precision: usize,
+
+    /// Show clocks of 1000 MHz and up in GHz (e.g. 1.53 GHz)
+    #[arg(long)]
+    ghz: bool,
+
+    /// Skip the periodic GPU details and process queries: only dmon and
+    /// pmon run, and VRAM, the process table and the info overlay are empty
+    #[arg(long)]
+    no_query: bool,
+
+    /// Only poll `nvidia-smi --query-gpu`; never start dmon or pmon
+    #[arg(long)]
+    poll_only: bool,
+
+    /// Query GPU details as CSV (fast) or with `nvidia-smi -q -x` XML (more
+    /// robust across driver versions)
+    #[arg(long, value_enum, default_value_t = QueryFormat::Csv)]
+    query_format: QueryFormat,
+
+    /// Run dmon with `-o T` and place chart points by the device timestamps
+    /// rather than arrival time
+    #[arg(long)]
+    dmon_timestamps: bool,
+
+    /// Make j/k wrap from the last row to the first and back
+    #[arg(long)]
+    wrap_nav: bool,
+
+    /// Print a per-GPU summary (min/avg/max, energy, peak VRAM) on exit
+    #[arg(long)]
+    summary: bool,
+
+    /// Log each distinct nvidia-smi, ps, grep and dmesg command line as it is
+    /// first run (event log, or stderr without the TUI)
+    #[arg(long)]
+    verbose: bool,
+
+    /// Show one GPU's details full-screen and advance every SECS seconds
+    /// (for wall-mounted displays); any key pauses, C resumes
+    #[arg(long, value_name = "SECS")]
+    carousel: Option<u64>,
+
+    /// Draw at most FPS frames a second, and only when something changed,
+    /// to keep the monitor light on shared login nodes
+    #[arg(long, value_name = "FPS", value_parser = clap::value_parser!(u32).range(1..))]
+    max_fps: Option<u32>,
+
+    /// Accent color for titles, headers and keys (cycle with A; the choice is saved)
+    #[arg(long, value_enum)]
+    accent: Option<Accent>,
+
+    /// Draw sparklines and gauges with ASCII characters only (for serial
+    /// consoles and fonts without block elements)
+    #[arg(long)]
+    ascii: bool,
+
+    /// Draw without colors, using bold, dim and reverse video instead. Also
+    /// on when NO_COLOR is set or TERM=dumb
+    #[arg(long)]
+    no_color: bool,
+
+    /// Memory gauge style: block fill, thin braille bar, or percentage only
+    #[arg(long, value_enum, default_value_t = GaugeStyle::Blocks, value_name = "STYLE")]
+    gauge_style: GaugeStyle,
+
+    /// Unit for the process table's VRAM column: MiB below 1 GiB and GiB
+    /// above (auto), or always one unit so rows compare at a glance
+    #[arg(long, value_enum, default_value_t = VramUnit::Auto, value_name = "UNIT")]
+    vram_unit: VramUnit,
+
+    /// Decimal places for GiB in the process table's VRAM column
+    #[arg(long, value_name = "DIGITS", default_value = "1")]
+    vram_precision: usize,
+
+    /// Show processes using at least SIZE of VRAM in red, e.g. 40GiB
+    /// (units MiB, GiB, TiB; a bare number is MiB)
+    #[arg(long, value_name = "SIZE", value_parser = parse_vram_size)]
+    proc_vram_warn: Option<u64>,
+
+    /// Write a JSON snapshot of GPU and process state to PATH on exit
+    #[arg(long, value_name = "PATH")]
+    dump_json: Option<PathBuf>,
+
+    /// Play back recorded nvidia-smi output instead of running nvidia-smi
+    /// (for development and demos without a GPU)
+    #[arg(long, hide = true, value_name = "FILE")]
+    replay: Option<PathBuf>,
+
+    /// Print the current value of one metric as a bare number and exit;
+    /// exits nonzero if the GPU doesn't report it
+    #[arg(long, value_enum, value_name = "METRIC")]
+    query: Option<QueryMetric>,
+
+    /// GPU index for --query
+    #[arg(long, value_name = "IDX", default_value = "0", requires = "query")]
+    gpu: u32,
+
+    /// Compare two --dump-json snapshots and exit
+    #[arg(long, num_args = 2, value_names = ["A", "B"])]
+    diff: Option<Vec<PathBuf>>,
+}
+
+impl Args {
+    fn into_config(self) -> Config {
+        let (gpu_filter, gpu_filter_warning) = resolve_gpu_filter(&self.gpus, |var| std::env::var(var).ok());
+        Config {
+            history_seconds: self.history,
+            process_ttl: Duration::from_secs(self.process_ttl),
+            apps_grace: Duration::from_secs(self.apps_grace),
+            avg_window: Duration::from_secs(self.avg_window),
+            stream_json: self.stream_json,
+            // Streamed output owns stdout, so it can't share it with the TUI
+            headless: self.stream_json || self.daemon,
+            power_temp_sparklines: self.power_temp_sparklines,
+            highlight_changes: self.highlight_changes,
+            clock: self.clock,
+            set_title: self.set_title,
+            screenshot_plain: self.screenshot_plain,
+            xid_dmesg: self.xid_dmesg,
+            overlay_gpus: self.overlay_gpus,
+            sync_charts: self.sync_charts,
+            hosts: self.hosts,
+            gpu_filter,
+            gpu_filter_warning,
+            bell_on_idle: self.bell_on_idle,
+            bell_on_crit: self.bell_on_crit,
+            flash_crit: self.flash_crit,
+            power_warn_pct: self.power_warn,
+            power_crit_pct: self.power_crit,
+            allow_control: self.allow_control,
+            border: self.border,
+            gpu_sort: self.sort_gpus,
+            hide_idle: self.hide_idle,
+            command_display: self.command,
+            time_format: self.time_format,
+            merge_pids: self.merge_pids,
+            group_by_gpu: self.group_by_gpu,
+            power_precision: self.power_precision,
+            clock_ghz: self.ghz,
+            accent: self.accent.or_else(|| Settings::load().accent).unwrap_or_default(),
+            ascii: self.ascii,
+            no_color: self.no_color || no_color_env(),
+            gauge_style: self.gauge_style,
+            vram_unit: self.vram_unit,
+            vram_precision: self.vram_precision,
+            proc_vram_warn_mib: self.proc_vram_warn,
+            poll_only: self.poll_only,
+            no_query: self.no_query,
+            query_format: self.query_format,
+            dmon_timestamps: self.dmon_timestamps,
+            wrap_nav: self.wrap_nav,
+            summary: self.summary,
+            verbose: self.verbose,
+            dump_json: self.dump_json,
+            replay: self.replay,
+            carousel: self.carousel.map(Duration::from_secs),
+            min_frame_interval: self.max_fps.map(|fps| Duration::from_secs(1) / fps),
+        }
+    }
+}
+
+/// Colors are unwanted per the NO_COLOR convention (set and non-empty) or
+/// unsupported by the terminal
+fn no_color_env() -> bool {
+    std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty())
+        || std::env::var("TERM").is_ok_and(|term| term == "dumb")
+}
+
+/// Target for the one-shot modes, tracing its commands with `--verbose`
+fn one_shot_target(args: &Args) -> (Target, Option<mpsc::UnboundedReceiver<String>>) {
+    let target = args.hosts.first().map(|h| Target::remote(h)).unwrap_or_else(Target::local);
+    if !args.verbose {
+        return (target, None);
+    }
+    let (tx, rx) = mpsc::unbounded_channel();
+    (target.traced(tx), Some(rx))
+}
+
+/// Print the traced command lines to stderr, once the one-shot mode is done
+fn print_commands(commands: Option<mpsc::UnboundedReceiver<String>>) {
+    let Some(mut rx) = commands else { return };
+    while let Ok(line) = rx.try_recv() {
+        eprintln!("nvidiagpu_top: $ {}", line);
+    }
+}
+
+/// Query the topology once and write it as DOT
+async fn export_topology_dot(target: &Target, path: &Path) -> Result<()> {
+    let dot = NvidiaMonitor::query_topology(target).await?.to_dot();
+    if path == Path::new("-") {
+        print!("{}", dot);
+    } else {
+        std::fs::write(path, dot).with_context(|| format!("Failed to write {}", path.display()))?;
+    }
+    Ok(())
+}
+
+/// Fetch one reading for `--query`
+async fn query_metric(target: &Target, metric: QueryMetric, gpu: u32, format: QueryFormat) -> Result<u64> {
+    let value = if metric.needs_info() {
+        let infos = NvidiaMonitor::query_gpu_info(target, format).await?;
+        infos.iter().find(|i| i.index == gpu).and_then(|i| metric.info_value(i))
+    } else {
+        let samples = NvidiaMonitor::query_gpu_samples(target).await?;
+        samples.iter().find(|s| s.gpu_idx == gpu).and_then(|s| metric.sample_value(s))
+    };
+    let name = format!("{:?}", metric).to_lowercase();
+    value.with_context(|| format!("{} is not available for GPU {}", name, gpu))
+}
+
+fn load_snapshot(path: &Path) -> Result<Snapshot> {
+    let json = std::fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    serde_json::from_str(&json).with_context(|| format!("{} is not a --dump-json snapshot", path.display()))
+}
+
+fn write_snapshot(path: &Path, snapshot: &Snapshot) -> Result<()> {
+    let json = serde_json::to_string_pretty(snapshot)?;
+    std::fs::write(path, json).with_context(|| format!("Failed to write {}", path.display()))
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
     let args = Args::parse();
+    if args.power_warn > args.power_crit {
+        Args::command()
+            .error(ErrorKind::ArgumentConflict, "--power-warn must not be above --power-crit")
+            .exit();
+    }
+
+    if let Some(path) = &args.topo_dot {
+        let (target, commands) = one_shot_target(&args);
+        let result = export_topology_dot(&target, path).await;
+        print_commands(commands);
+        return result;
+    }
+
+    if let Some(metric) = args.query {
+        let (target, commands) = one_shot_target(&args);
+        let result = query_metric(&target, metric, args.gpu, args.query_format).await;
+        print_commands(commands);
+        println!("{}", result?);
+        return Ok(());
+    }
+
+    if let Some([a, b]) = args.diff.as_deref() {
+        print!("{}", diff_snapshots(&load_snapshot(a)?, &load_snapshot(b)?));
+        return Ok(());
+    }
+
+    let config = args.into_config();
+
+    let summary = config.summary;
+    let dump_json = config.dump_json.clone();
+
+    if config.headless {
+        let mut app = app::App::new(config);
+        let result = app.run_headless().await;
+        if summary {
+            print!("{}", app.summary());
+        }
+        if let Some(path) = &dump_json {
+            write_snapshot(path, &app.snapshot())?;
+        }
+        return result;
+    }
+
+    // Without a terminal the TUI would write escape sequences into a pipe or
+    // file (e.g. from cron), so point at the modes meant for that instead
+    if !std::io::stdout().is_terminal() {
+        anyhow::bail!(
+            "stdout is not a terminal, so the TUI can't run. For scripts and cron use one of:\n  \
+             --query <METRIC> --gpu <N>      print one reading and exit\n  \
+             --stream-json                   print one JSON object per sample\n  \
+             --daemon --dump-json <PATH>     run until SIGTERM, then write a snapshot\n  \
+             --daemon --summary              run until SIGTERM, then print a per-GPU report"
+        );
+    }
+
+    ui::set_border_preset(config.border);
+    ui::set_ascii(config.ascii);
+    ui::set_monochrome(config.no_color);
+
+    // Save the terminal title on xterm's title stack so it can be put back
+    let set_title = config.set_title;
+    if set_title {
+        print!("{}", PUSH_TITLE);
+        let _ = std::io::stdout().flush();
+    }
 
     // Initialize terminal
     let terminal = ratatui::init();
 
     // Run app
-    let app = app::App::new(args.history);
+    let mut app = app::App::new(config);
     let result = app.run(terminal).await;
 
     // Restore terminal
     ratatui::restore();
+    if set_title {
+        print!("{}", POP_TITLE);
+        let _ = std::io::stdout().flush();
+    }
+
+    // Printed only now so it lands on the normal screen, not the TUI's
+    if summary {
+        print!("{}", app.summary());
+    }
+    if let Some(path) = &dump_json {
+        write_snapshot(path, &app.snapshot())?;
+    }
 
     result
 }
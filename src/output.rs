@@ -0,0 +1,494 @@
+//! Non-TUI output formats written to stdout
+
+use clap::ValueEnum;
+use ratatui::buffer::Buffer;
+use ratatui::style::{Color, Modifier, Style};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::data::{DataStore, GpuRunStats, MetricStats};
+use crate::parser::{GpuInfo, GpuSample};
+use crate::ui::status::format_duration;
+
+/// One line of `--stream-json` output
+#[derive(Debug, Serialize)]
+struct StreamRecord<'a> {
+    /// Unix time in seconds when the sample was received
+    timestamp: f64,
+    #[serde(flatten)]
+    sample: &'a GpuSample,
+}
+
+/// Serialize a dmon sample as a single JSON line (without trailing newline)
+pub fn sample_json_line(sample: &GpuSample, at: SystemTime) -> String {
+    let timestamp = at
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs_f64())
+        .unwrap_or(0.0);
+    serde_json::to_string(&StreamRecord { timestamp, sample })
+        .unwrap_or_else(|_| "{}".to_string())
+}
+
+/// End-of-run report for `--summary`, one block per GPU
+pub fn summary_report(data: &DataStore) -> String {
+    let mut report = String::new();
+    for idx in data.gpu_indices() {
+        let Some(stats) = data.run_stats(idx) else {
+            continue;
+        };
+        let name = data.get_gpu_info(idx).map(|g| g.name.as_str()).unwrap_or("unknown");
+        write_gpu_summary(&mut report, idx, name, stats);
+    }
+    if report.is_empty() {
+        report.push_str("No GPU samples were received\n");
+    }
+    report
+}
+
+fn write_gpu_summary(out: &mut String, idx: u32, name: &str, stats: &GpuRunStats) {
+    let line = |m: &MetricStats, unit: &str| match (m.min, m.avg(), m.max) {
+        (Some(min), Some(avg), Some(max)) => {
+            format!("min {}{unit}  avg {:.1}{unit}  max {}{unit}", min, avg, max)
+        }
+        _ => "-".to_string(),
+    };
+
+    let _ = writeln!(
+        out,
+        "GPU {} ({}): {}, {} samples",
+        idx,
+        name,
+        format_duration(stats.started.elapsed()),
+        stats.samples
+    );
+    let _ = writeln!(out, "  Temp       {}", line(&stats.temp, "°C"));
+    let _ = writeln!(out, "  Power      {}", line(&stats.power, "W"));
+    let _ = writeln!(out, "  SM         {}", line(&stats.sm, "%"));
+    let _ = writeln!(out, "  Energy     {:.2} Wh", stats.energy_wh);
+    let _ = writeln!(out, "  Peak VRAM  {} MiB", stats.peak_vram_mib);
+}
+
+/// Point-in-time state written by `--dump-json` and compared by `--diff`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Snapshot {
+    /// Unix time in seconds when the snapshot was taken
+    pub timestamp: f64,
+    pub gpus: Vec<GpuSnapshot>,
+    pub processes: Vec<ProcessSnapshot>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GpuSnapshot {
+    pub index: u32,
+    pub name: String,
+    pub vram_used_mib: Option<u64>,
+    pub vram_total_mib: Option<u64>,
+    pub power_w: Option<u32>,
+    pub temp_c: Option<u32>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProcessSnapshot {
+    pub pid: u32,
+    pub gpu_idx: u32,
+    pub command: String,
+    /// None for processes pmon reports but compute-apps doesn't
+    pub vram_mib: Option<u64>,
+}
+
+impl Snapshot {
+    pub fn from_store(data: &DataStore, at: SystemTime) -> Self {
+        let gpus = data
+            .gpu_indices()
+            .into_iter()
+            .map(|index| {
+                let info = data.get_gpu_info(index);
+                let latest = data.get_gpu(index).and_then(|h| h.latest());
+                GpuSnapshot {
+                    index,
+                    name: info.map(|g| g.name.clone()).unwrap_or_default(),
+                    vram_used_mib: info.map(|g| g.memory_used_mib),
+                    vram_total_mib: info.map(|g| g.memory_total_mib),
+                    power_w: latest.and_then(|s| s.power_w),
+                    temp_c: latest.and_then(|s| s.gpu_temp_c),
+                }
+            })
+            .collect();
+        let processes = data
+            .get_enriched_processes(false)
+            .into_iter()
+            .map(|p| ProcessSnapshot {
+                pid: p.pid,
+                gpu_idx: p.gpu_idx,
+                command: p.command,
+                vram_mib: p.vram_mib,
+            })
+            .collect();
+
+        Self {
+            timestamp: at.duration_since(UNIX_EPOCH).map(|d| d.as_secs_f64()).unwrap_or(0.0),
+            gpus,
+            processes,
+        }
+    }
+}
+
+/// Report for `--diff`: per-GPU VRAM/power/temp deltas, then processes that
+/// appeared, went away or changed VRAM between `a` and `b`
+pub fn diff_snapshots(a: &Snapshot, b: &Snapshot) -> String {
+    let mut out = String::new();
+    let delta = |label: &str, old: Option<u64>, new: Option<u64>, unit: &str| -> String {
+        match (old, new) {
+            (Some(old), Some(new)) => {
+                format!("  {:<6} {} -> {} {} ({:+})\n", label, old, new, unit, new as i64 - old as i64)
+            }
+            _ => format!("  {:<6} -\n", label),
+        }
+    };
+
+    let old_gpus: BTreeMap<u32, &GpuSnapshot> = a.gpus.iter().map(|g| (g.index, g)).collect();
+    let new_gpus: BTreeMap<u32, &GpuSnapshot> = b.gpus.iter().map(|g| (g.index, g)).collect();
+    let mut indices: Vec<u32> = old_gpus.keys().chain(new_gpus.keys()).copied().collect();
+    indices.sort_unstable();
+    indices.dedup();
+
+    for idx in indices {
+        match (old_gpus.get(&idx), new_gpus.get(&idx)) {
+            (Some(old), Some(new)) => {
+                let _ = writeln!(out, "GPU {} ({})", idx, new.name);
+                out.push_str(&delta("VRAM", old.vram_used_mib, new.vram_used_mib, "MiB"));
+                out.push_str(&delta("Power", old.power_w.map(u64::from), new.power_w.map(u64::from), "W"));
+                out.push_str(&delta("Temp", old.temp_c.map(u64::from), new.temp_c.map(u64::from), "°C"));
+            }
+            (Some(_), None) => {
+                let _ = writeln!(out, "GPU {}: only in A", idx);
+            }
+            _ => {
+                let _ = writeln!(out, "GPU {}: only in B", idx);
+            }
+        }
+    }
+
+    let procs = |s: &Snapshot| -> BTreeMap<(u32, u32), (String, Option<u64>)> {
+        s.processes
+            .iter()
+            .map(|p| ((p.pid, p.gpu_idx), (p.command.clone(), p.vram_mib)))
+            .collect()
+    };
+    let (old, new) = (procs(a), procs(b));
+    let mut changes = Vec::new();
+    let mib = |vram: &Option<u64>| vram.map_or("?".to_string(), |v| v.to_string());
+    for ((pid, gpu), (command, vram)) in &old {
+        match new.get(&(*pid, *gpu)) {
+            None => changes.push(format!("  - {} {} on GPU {} ({} MiB)", pid, command, gpu, mib(vram))),
+            Some((_, new_vram)) if new_vram != vram => changes.push(format!(
+                "  ~ {} {} on GPU {}: {} -> {} MiB",
+                pid, command, gpu, mib(vram), mib(new_vram)
+            )),
+            Some(_) => {}
+        }
+    }
+    for ((pid, gpu), (command, vram)) in &new {
+        if !old.contains_key(&(*pid, *gpu)) {
+            changes.push(format!("  + {} {} on GPU {} ({} MiB)", pid, command, gpu, mib(vram)));
+        }
+    }
+
+    out.push_str("Processes\n");
+    if changes.is_empty() {
+        out.push_str("  no changes\n");
+    }
+    for change in changes {
+        let _ = writeln!(out, "{}", change);
+    }
+    out
+}
+
+/// Single reading printed by `--query`, for shell scripts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum QueryMetric {
+    /// Power draw in W
+    Power,
+    /// GPU temperature in °C
+    Temp,
+    /// Memory (HBM) temperature in °C
+    Memtemp,
+    /// SM utilization in %
+    Sm,
+    /// Memory bandwidth utilization in %
+    Mem,
+    /// VRAM used in MiB
+    Memused,
+    /// VRAM free in MiB
+    Memfree,
+    /// VRAM total in MiB
+    Memtotal,
+    /// Fan speed in %
+    Fan,
+    /// SM clock in MHz
+    Pclk,
+    /// Memory clock in MHz
+    Mclk,
+}
+
+impl QueryMetric {
+    /// Whether the value comes from `GpuInfo` rather than a `GpuSample`,
+    /// so only one nvidia-smi query is needed
+    pub fn needs_info(&self) -> bool {
+        matches!(self, Self::Memused | Self::Memfree | Self::Memtotal | Self::Fan)
+    }
+
+    pub fn sample_value(&self, sample: &GpuSample) -> Option<u64> {
+        let value = match self {
+            Self::Power => sample.power_w,
+            Self::Temp => sample.gpu_temp_c,
+            Self::Memtemp => sample.mem_temp_c,
+            Self::Sm => sample.sm_util,
+            Self::Mem => sample.mem_util,
+            Self::Pclk => sample.gpu_clock_mhz,
+            Self::Mclk => sample.mem_clock_mhz,
+            _ => None,
+        };
+        value.map(u64::from)
+    }
+
+    pub fn info_value(&self, info: &GpuInfo) -> Option<u64> {
+        // A GPU in an error state reports zeros that aren't real readings
+        if info.error.is_some() {
+            return None;
+        }
+        match self {
+            Self::Memused => Some(info.memory_used_mib),
+            Self::Memfree => Some(info.memory_free_mib),
+            Self::Memtotal => Some(info.memory_total_mib),
+            Self::Fan => info.fan_speed_pct.map(u64::from),
+            _ => None,
+        }
+    }
+}
+
+/// A rendered frame as text, one line per row with trailing blanks
+/// trimmed. With `ansi`, styles are kept as SGR escapes so `cat` or
+/// `less -R` shows the frame as it looked on screen
+pub fn buffer_text(buf: &Buffer, ansi: bool) -> String {
+    let area = buf.area;
+    let plain = sgr(Style::default());
+    let mut out = String::new();
+    for y in area.top()..area.bottom() {
+        let cells: Vec<_> = (area.left()..area.right()).map(|x| &buf[(x, y)]).collect();
+        // Blank default-styled cells at the end of the row are padding
+        let len = cells
+            .iter()
+            .rposition(|c| c.symbol() != " " || (ansi && sgr(c.style()) != plain))
+            .map_or(0, |i| i + 1);
+
+        let mut current = plain.clone();
+        let mut skip = 0usize;
+        for cell in &cells[..len] {
+            // Cells covered by a wide character before them
+            if skip > 0 || cell.skip {
+                skip = skip.saturating_sub(1);
+                continue;
+            }
+            if ansi {
+                let code = sgr(cell.style());
+                if code != current {
+                    out.push_str(&code);
+                    current = code;
+                }
+            }
+            out.push_str(cell.symbol());
+            skip = unicode_width::UnicodeWidthStr::width(cell.symbol()).saturating_sub(1);
+        }
+        if ansi && current != plain {
+            out.push_str(&plain);
+        }
+        out.push('\n');
+    }
+    out
+}
+
+/// SGR escape that resets and then applies `style`
+fn sgr(style: Style) -> String {
+    let mut codes = vec!["0".to_string()];
+    let modifiers = [
+        (Modifier::BOLD, "1"),
+        (Modifier::DIM, "2"),
+        (Modifier::ITALIC, "3"),
+        (Modifier::UNDERLINED, "4"),
+        (Modifier::SLOW_BLINK, "5"),
+        (Modifier::RAPID_BLINK, "6"),
+        (Modifier::REVERSED, "7"),
+        (Modifier::HIDDEN, "8"),
+        (Modifier::CROSSED_OUT, "9"),
+    ];
+    for (modifier, code) in modifiers {
+        if style.add_modifier.contains(modifier) {
+            codes.push(code.to_string());
+        }
+    }
+    if let Some(fg) = style.fg.and_then(|c| color_code(c, false)) {
+        codes.push(fg);
+    }
+    if let Some(bg) = style.bg.and_then(|c| color_code(c, true)) {
+        codes.push(bg);
+    }
+    format!("\x1b[{}m", codes.join(";"))
+}
+
+/// SGR parameter for a foreground or background color; None for Reset,
+/// which the leading 0 already covers
+fn color_code(color: Color, background: bool) -> Option<String> {
+    let base = if background { 40 } else { 30 };
+    let code = match color {
+        Color::Reset => return None,
+        Color::Black => base,
+        Color::Red => base + 1,
+        Color::Green => base + 2,
+        Color::Yellow => base + 3,
+        Color::Blue => base + 4,
+        Color::Magenta => base + 5,
+        Color::Cyan => base + 6,
+        Color::Gray => base + 7,
+        Color::DarkGray => base + 60,
+        Color::LightRed => base + 61,
+        Color::LightGreen => base + 62,
+        Color::LightYellow => base + 63,
+        Color::LightBlue => base + 64,
+        Color::LightMagenta => base + 65,
+        Color::LightCyan => base + 66,
+        Color::White => base + 67,
+        Color::Indexed(i) => return Some(format!("{};5;{}", base + 8, i)),
+        Color::Rgb(r, g, b) => return Some(format!("{};2;{};{};{}", base + 8, r, g, b)),
+    };
+    Some(code.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_query_metric_values() {
+        let sample = GpuSample {
+            power_w: Some(62),
+            gpu_temp_c: Some(40),
+            ..Default::default()
+        };
+        assert_eq!(QueryMetric::Power.sample_value(&sample), Some(62));
+        assert_eq!(QueryMetric::Temp.sample_value(&sample), Some(40));
+        assert_eq!(QueryMetric::Memtemp.sample_value(&sample), None);
+
+        let mut info = GpuInfo {
+            memory_free_mib: 80896,
+            ..Default::default()
+        };
+        assert!(QueryMetric::Memfree.needs_info());
+        assert_eq!(QueryMetric::Memfree.info_value(&info), Some(80896));
+        assert_eq!(QueryMetric::Fan.info_value(&info), None);
+        info.error = Some("[Unknown Error]".into());
+        assert_eq!(QueryMetric::Memfree.info_value(&info), None);
+    }
+
+    #[test]
+    fn test_sample_json_line() {
+        let sample = GpuSample {
+            gpu_idx: 1,
+            power_w: Some(69),
+            sm_util: Some(100),
+            ..Default::default()
+        };
+        let line = sample_json_line(&sample, UNIX_EPOCH + Duration::from_millis(1500));
+        let value: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(value["gpu_idx"], 1);
+        assert_eq!(value["timestamp"], 1.5);
+        assert_eq!(value["power_w"], 69);
+        assert!(value["mem_util"].is_null());
+        assert!(!line.contains('\n'));
+    }
+
+    #[test]
+    fn test_summary_report() {
+        let mut data = DataStore::new(60, Duration::from_secs(5));
+        assert_eq!(summary_report(&data), "No GPU samples were received\n");
+
+        for sm in [20, 40] {
+            data.add_sample(GpuSample {
+                gpu_idx: 0,
+                power_w: Some(100),
+                sm_util: Some(sm),
+                ..Default::default()
+            });
+        }
+        let report = summary_report(&data);
+        assert!(report.starts_with("GPU 0 (unknown): "));
+        assert!(report.contains("2 samples"));
+        assert!(report.contains("SM         min 20%  avg 30.0%  max 40%"));
+        assert!(report.contains("Temp       -"));
+    }
+
+    #[test]
+    fn test_diff_snapshots() {
+        let gpu = |index, vram, power| GpuSnapshot {
+            index,
+            name: "A100".into(),
+            vram_used_mib: Some(vram),
+            vram_total_mib: Some(81920),
+            power_w: Some(power),
+            temp_c: None,
+        };
+        let proc = |pid, vram| ProcessSnapshot {
+            pid,
+            gpu_idx: 0,
+            command: "python".into(),
+            vram_mib: Some(vram),
+        };
+        let a = Snapshot {
+            timestamp: 0.0,
+            gpus: vec![gpu(0, 1000, 300)],
+            processes: vec![proc(1, 500), proc(2, 500)],
+        };
+        let b = Snapshot {
+            timestamp: 60.0,
+            gpus: vec![gpu(0, 600, 250), gpu(1, 0, 60)],
+            processes: vec![proc(2, 100), proc(3, 400)],
+        };
+
+        let report = diff_snapshots(&a, &b);
+        assert!(report.contains("  VRAM   1000 -> 600 MiB (-400)"));
+        assert!(report.contains("  Power  300 -> 250 W (-50)"));
+        assert!(report.contains("  Temp   -"));
+        assert!(report.contains("GPU 1: only in B"));
+        assert!(report.contains("  - 1 python on GPU 0 (500 MiB)"));
+        assert!(report.contains("  ~ 2 python on GPU 0: 500 -> 100 MiB"));
+        assert!(report.contains("  + 3 python on GPU 0 (400 MiB)"));
+
+        // Round-trips through the --dump-json format
+        let json = serde_json::to_string(&b).unwrap();
+        let back: Snapshot = serde_json::from_str(&json).unwrap();
+        assert!(back.processes.iter().any(|p| p.pid == 3));
+        assert!(diff_snapshots(&b, &back).ends_with("Processes\n  no changes\n"));
+    }
+
+    #[test]
+    fn test_buffer_text() {
+        use ratatui::layout::Rect;
+
+        let mut buf = Buffer::empty(Rect::new(0, 0, 10, 2));
+        buf.set_string(0, 0, "GPU ", Style::default());
+        buf.set_string(4, 0, "78°C", Style::default().fg(Color::Red).add_modifier(Modifier::BOLD));
+        buf.set_string(0, 1, "日本", Style::default());
+
+        assert_eq!(buffer_text(&buf, false), "GPU 78°C\n日本\n");
+        assert_eq!(
+            buffer_text(&buf, true),
+            "GPU \x1b[0;1;31m78°C\x1b[0m\n日本\n"
+        );
+
+        buf.set_style(Rect::new(8, 1, 2, 1), Style::default().bg(Color::Rgb(1, 2, 3)));
+        assert!(buffer_text(&buf, true).ends_with("日本    \x1b[0;48;2;1;2;3m  \x1b[0m\n"));
+    }
+}
@@ -1,4 +1,10 @@
-/// Parsers for nvidia-smi output formats
+//! Parsers for nvidia-smi output formats
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use chrono::NaiveTime;
+use serde::Serialize;
 
 // ============================================================================
 // DMON Parser (device monitoring)
@@ -7,9 +13,11 @@
 /// # gpu    pwr  gtemp  mtemp     sm    mem    enc    dec    jpg    ofa   mclk   pclk
 /// # Idx      W      C      C      %      %      %      %      %      %    MHz    MHz
 ///     0     69     13      -    100     30      0      0      -      -   3615   1531
-
+///
+/// With `-o T` every data line starts with the device time, e.g. `16:05:11`.
+///
 /// A single GPU sample from nvidia-smi dmon
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize)]
 #[allow(dead_code)]
 pub struct GpuSample {
     pub gpu_idx: u32,
@@ -24,6 +32,9 @@ pub struct GpuSample {
     pub ofa_util: Option<u32>,
     pub mem_clock_mhz: Option<u32>,
     pub gpu_clock_mhz: Option<u32>,
+    /// Time dmon stamped the line with (`-o T`), free of capture jitter
+    #[serde(skip)]
+    pub device_time: Option<NaiveTime>,
 }
 
 impl GpuSample {
@@ -47,18 +58,36 @@ impl GpuSample {
             return None;
         }
 
-        let parts: Vec<&str> = line.split_whitespace().collect();
+        let mut parts: Vec<&str> = line.split_whitespace().collect();
+
+        // A leading HH:MM:SS column means dmon was run with `-o T`
+        let device_time = parts
+            .first()
+            .and_then(|first| NaiveTime::parse_from_str(first, "%H:%M:%S").ok());
+        if device_time.is_some() {
+            parts.remove(0);
+        }
 
         // We expect at least 12 fields
         if parts.len() < 12 {
             return None;
         }
 
+        // Some dmon versions interleave warnings with the samples. Those
+        // can be 12+ words long, so only a numeric GPU index followed by
+        // numeric (or `-`) metrics counts as a sample. Negative or huge
+        // values still count; they're dropped as implausible below
+        let gpu_idx = parts[0].parse().ok()?;
+        let is_metric = |p: &&str| *p == "-" || p.parse::<i64>().is_ok() || is_error_sentinel(p);
+        if !parts[1..12].iter().all(is_metric) {
+            return None;
+        }
+
         Some(Self {
-            gpu_idx: parts[0].parse().ok()?,
-            power_w: Self::parse_optional(parts[1]),
-            gpu_temp_c: Self::parse_optional(parts[2]),
-            mem_temp_c: Self::parse_optional(parts[3]),
+            gpu_idx,
+            power_w: plausible_power(Self::parse_optional(parts[1]).map(|w| w as f32)).map(|w| w as u32),
+            gpu_temp_c: plausible_temp(Self::parse_optional(parts[2])),
+            mem_temp_c: plausible_temp(Self::parse_optional(parts[3])),
             sm_util: Self::parse_optional(parts[4]),
             mem_util: Self::parse_optional(parts[5]),
             enc_util: Self::parse_optional(parts[6]),
@@ -67,6 +96,33 @@ impl GpuSample {
             ofa_util: Self::parse_optional(parts[9]),
             mem_clock_mhz: Self::parse_optional(parts[10]),
             gpu_clock_mhz: Self::parse_optional(parts[11]),
+            device_time,
+        })
+    }
+
+    /// Parse a line of `--query-gpu` CSV in the order used by poll-only mode:
+    /// index, power.draw, temperature.gpu, utilization.gpu, utilization.memory,
+    /// clocks.mem, clocks.sm, temperature.memory (optional)
+    pub fn from_query(line: &str) -> Option<Self> {
+        let parts: Vec<&str> = line.split(',').map(|s| s.trim()).collect();
+        if parts.len() < 7 {
+            return None;
+        }
+
+        // Unsupported fields come back as "[N/A]" and simply fail to parse
+        let parse_u32 = |s: &str| -> Option<u32> { s.parse().ok() };
+        let parse_watts = |s: &str| -> Option<u32> { plausible_power(s.parse().ok()).map(|w| w.round() as u32) };
+
+        Some(Self {
+            gpu_idx: parts[0].parse().ok()?,
+            power_w: parse_watts(parts[1]),
+            gpu_temp_c: plausible_temp(parse_u32(parts[2])),
+            sm_util: parse_u32(parts[3]),
+            mem_util: parse_u32(parts[4]),
+            mem_clock_mhz: parse_u32(parts[5]),
+            gpu_clock_mhz: parse_u32(parts[6]),
+            mem_temp_c: plausible_temp(parts.get(7).and_then(|s| parse_u32(s))),
+            ..Default::default()
         })
     }
 }
@@ -100,11 +156,69 @@ mod tests {
         assert!(GpuSample::parse_line("# Idx      W      C      C").is_none());
     }
 
+    #[test]
+    fn test_skip_warning_lines() {
+        let warning = "Warning: persistence mode is disabled on device 00000000:01:00.0. See the Known Issues section of the nvidia-smi(1) man page for more information.";
+        assert!(warning.split_whitespace().count() >= 12);
+        assert!(GpuSample::parse_line(warning).is_none());
+        // Starting with a number isn't enough to pass for a sample
+        assert!(GpuSample::parse_line("1 GPU reported a warning: clocks may be throttled by the driver at times").is_none());
+        // Error sentinels in metric columns still make a sample
+        let errored = GpuSample::parse_line("    1   ERR!   ERR!      -      -      -      -      -      -      -      -      -").unwrap();
+        assert_eq!((errored.gpu_idx, errored.power_w), (1, None));
+    }
+
     #[test]
     fn test_skip_empty_lines() {
         assert!(GpuSample::parse_line("").is_none());
         assert!(GpuSample::parse_line("   ").is_none());
     }
+
+    #[test]
+    fn test_parse_timestamped_line() {
+        let line = "16:05:11      0     69     13      -    100     30      0      0      -      -   3615   1531";
+        let sample = GpuSample::parse_line(line).unwrap();
+        assert_eq!(sample.device_time, NaiveTime::from_hms_opt(16, 5, 11));
+        assert_eq!(sample.gpu_idx, 0);
+        assert_eq!(sample.gpu_clock_mhz, Some(1531));
+        assert!(GpuSample::parse_line("#Time        gpu    pwr  gtemp").is_none());
+    }
+
+    #[test]
+    fn test_parse_query_sample() {
+        let sample = GpuSample::from_query("1, 68.52, 41, 97, 23, 1593, [N/A]").unwrap();
+        assert_eq!(sample.gpu_idx, 1);
+        assert_eq!(sample.power_w, Some(69));
+        assert_eq!(sample.gpu_temp_c, Some(41));
+        assert_eq!(sample.sm_util, Some(97));
+        assert_eq!(sample.mem_util, Some(23));
+        assert_eq!(sample.mem_clock_mhz, Some(1593));
+        assert_eq!(sample.gpu_clock_mhz, None);
+        assert_eq!(sample.enc_util, None);
+        assert_eq!(sample.mem_temp_c, None);
+        assert!(GpuSample::from_query("1, 68.52").is_none());
+
+        // HBM temperature, on data-center parts that report it
+        let sample = GpuSample::from_query("0, 512.10, 61, 100, 80, 2619, 1980, 78").unwrap();
+        assert_eq!(sample.mem_temp_c, Some(78));
+    }
+
+    #[test]
+    fn test_implausible_readings_dropped() {
+        // An unsigned -1 and an overflowing power reading during init
+        let sample = GpuSample::parse_line("    0 4294967295 4294967295     -1     12      3      0      0      0      0   1593    210").unwrap();
+        assert_eq!((sample.power_w, sample.gpu_temp_c, sample.mem_temp_c), (None, None, None));
+        // The rest of the sample is kept
+        assert_eq!((sample.sm_util, sample.gpu_clock_mhz), (Some(12), Some(210)));
+
+        // The limits themselves are still readings
+        let sample = GpuSample::parse_line("    0   2000    150    151      0      0      0      0      0      0   1593    210").unwrap();
+        assert_eq!((sample.power_w, sample.gpu_temp_c, sample.mem_temp_c), (Some(2000), Some(150), None));
+
+        let sample = GpuSample::from_query("0, -3.50, 4294967295, 97, 23, 1593, 1410, 200").unwrap();
+        assert_eq!((sample.power_w, sample.gpu_temp_c, sample.mem_temp_c), (None, None, None));
+        assert_eq!(sample.sm_util, Some(97));
+    }
 }
 
 // ============================================================================
@@ -158,26 +272,36 @@ impl ComputeApp {
 
         Some(Self {
             pid: parts[0].parse().ok()?,
-            name: parts[1].to_string(),
+            name: sanitize_command(parts[1]),
             gpu_uuid: parts[2].to_string(),
             vram_used_mib: vram_str.trim().parse().unwrap_or(0),
         })
     }
 }
 
+/// Replace control characters (tabs, escapes, stray newlines) in a process
+/// name with `?`, as ps does, so odd or hostile names can't break the table
+fn sanitize_command(s: &str) -> String {
+    s.chars().map(|c| if c.is_control() { '?' } else { c }).collect()
+}
+
 // ============================================================================
 // Process System Info (from /proc via ps)
 // ============================================================================
 #[derive(Debug, Clone, Default)]
 pub struct ProcessSystemInfo {
     pub pid: u32,
+    pub user: String,       // Owner's name, or uid when it has no passwd entry
     pub cpu_percent: f32,
     pub rss_kb: u64,        // System RAM in KB
-    pub elapsed: String,    // Runtime
+    pub elapsed: String,    // Runtime as ps prints it
+    pub runtime: Option<Duration>, // `elapsed` parsed
+    pub args: String,       // Full command line (empty if ps didn't report it)
+    pub container: Option<String>, // Short container id from /proc/<pid>/cgroup
 }
 
 impl ProcessSystemInfo {
-    /// Parse output from: ps -p <pids> -o pid,pcpu,rss,etime --no-headers
+    /// Parse output from: ps -p <pids> -o pid,user:32,pcpu,rss,etime,args --no-headers
     pub fn parse_ps_line(line: &str) -> Option<Self> {
         let line = line.trim();
         if line.is_empty() {
@@ -186,15 +310,133 @@ impl ProcessSystemInfo {
 
         let mut parts = line.split_whitespace();
         let pid: u32 = parts.next()?.parse().ok()?;
+        let user = parts.next()?.to_string();
         let cpu_str = parts.next()?;
         let rss_str = parts.next()?;
         let elapsed = parts.next()?.to_string();
+        // args is last so everything left over belongs to it
+        let args = sanitize_command(&parts.collect::<Vec<_>>().join(" "));
 
         Some(Self {
             pid,
+            user,
             cpu_percent: cpu_str.parse().unwrap_or(0.0),
             rss_kb: rss_str.parse().unwrap_or(0),
+            runtime: parse_etime(&elapsed),
             elapsed,
+            args,
+            container: None,
+        })
+    }
+}
+
+/// Parse ps's etime, `[[dd-]hh:]mm:ss`
+pub fn parse_etime(etime: &str) -> Option<Duration> {
+    let (days, clock) = match etime.split_once('-') {
+        Some((days, clock)) => (days.parse::<u64>().ok()?, clock),
+        None => (0, etime),
+    };
+    let fields: Vec<u64> = clock.split(':').map(|f| f.parse().ok()).collect::<Option<_>>()?;
+    let (hours, minutes, seconds) = match fields[..] {
+        [m, s] => (0, m, s),
+        [h, m, s] => (h, m, s),
+        _ => return None,
+    };
+    Some(Duration::from_secs(((days * 24 + hours) * 60 + minutes) * 60 + seconds))
+}
+
+/// Runtime prefixes on the cgroup directory of a container's processes,
+/// e.g. `docker-<id>.scope` under systemd or `cri-containerd-<id>.scope` on
+/// Kubernetes nodes
+const CONTAINER_SCOPE_PREFIXES: [&str; 4] = ["docker-", "cri-containerd-", "crio-", "libpod-"];
+
+/// Short (12 character) container id from the contents of /proc/<pid>/cgroup,
+/// or None for a host process. Handles cgroup v1 (`/docker/<id>`,
+/// `/kubepods/.../<id>`) and v2 systemd scopes (`docker-<id>.scope`)
+pub fn parse_container_id(cgroup: &str) -> Option<String> {
+    cgroup.lines().find_map(|line| {
+        // hierarchy-id:controllers:path
+        let path = line.splitn(3, ':').nth(2)?;
+        let leaf = path.rsplit('/').next()?;
+        let leaf = leaf.strip_suffix(".scope").unwrap_or(leaf);
+        let id = CONTAINER_SCOPE_PREFIXES
+            .iter()
+            .find_map(|prefix| leaf.strip_prefix(prefix))
+            .unwrap_or(leaf);
+        // Container ids are 64 hex digits; anything else is a host slice
+        (id.len() == 64 && id.chars().all(|c| c.is_ascii_hexdigit())).then(|| id[..12].to_string())
+    })
+}
+
+/// Container ids per PID from `grep -H '' /proc/<pid>/cgroup ...`, whose
+/// lines are prefixed with the file they came from
+pub fn parse_cgroup_listing(output: &str) -> HashMap<u32, String> {
+    let mut cgroups: HashMap<u32, String> = HashMap::new();
+    for line in output.lines() {
+        let Some((file, entry)) = line.split_once(':') else {
+            continue;
+        };
+        let Some(pid) = file.strip_prefix("/proc/").and_then(|f| f.strip_suffix("/cgroup")) else {
+            continue;
+        };
+        if let Ok(pid) = pid.parse() {
+            let text = cgroups.entry(pid).or_default();
+            text.push_str(entry);
+            text.push('\n');
+        }
+    }
+    cgroups
+        .into_iter()
+        .filter_map(|(pid, text)| parse_container_id(&text).map(|id| (pid, id)))
+        .collect()
+}
+
+/// Column positions in pmon output, read from its `# gpu pid type ...` header.
+/// The layout varies by driver: older ones omit jpg/ofa, `-s um` adds fb/ccpm.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PmonColumns {
+    gpu: usize,
+    pid: usize,
+    process_type: Option<usize>,
+    sm: Option<usize>,
+    mem: Option<usize>,
+    enc: Option<usize>,
+    dec: Option<usize>,
+    command: usize,
+}
+
+impl Default for PmonColumns {
+    /// Layout of current drivers, used until a header has been seen
+    fn default() -> Self {
+        Self {
+            gpu: 0,
+            pid: 1,
+            process_type: Some(2),
+            sm: Some(3),
+            mem: Some(4),
+            enc: Some(5),
+            dec: Some(6),
+            command: 9,
+        }
+    }
+}
+
+impl PmonColumns {
+    /// Parse the column-name header line. Returns None for data lines and
+    /// for the units line (`# Idx  #  C/G  % ...`), which names no columns.
+    pub fn from_header(line: &str) -> Option<Self> {
+        let names: Vec<&str> = line.trim().strip_prefix('#')?.split_whitespace().collect();
+        let find = |name: &str| names.iter().position(|n| n.eq_ignore_ascii_case(name));
+
+        Some(Self {
+            gpu: find("gpu")?,
+            pid: find("pid")?,
+            process_type: find("type"),
+            sm: find("sm"),
+            mem: find("mem"),
+            enc: find("enc"),
+            dec: find("dec"),
+            command: find("command")?,
         })
     }
 }
@@ -209,31 +451,212 @@ impl ProcessSample {
         }
     }
 
+    /// Parse a data line using the default (current driver) column layout
+    #[allow(dead_code)]
     pub fn parse_line(line: &str) -> Option<Self> {
+        Self::parse_line_with(line, &PmonColumns::default())
+    }
+
+    /// Parse a data line using columns read from pmon's header
+    pub fn parse_line_with(line: &str, columns: &PmonColumns) -> Option<Self> {
         let line = line.trim();
         if line.starts_with('#') || line.is_empty() {
             return None;
         }
 
         let parts: Vec<&str> = line.split_whitespace().collect();
-        if parts.len() < 10 {
+        if parts.len() <= columns.command {
             return None;
         }
 
+        let field = |idx: Option<usize>| idx.and_then(|i| parts.get(i)).and_then(|s| Self::parse_optional(s));
+
+        Some(Self {
+            gpu_idx: parts[columns.gpu].parse().ok()?,
+            pid: parts[columns.pid].parse().ok()?,
+            process_type: columns
+                .process_type
+                .and_then(|i| parts.get(i))
+                .map(|s| s.to_string())
+                .unwrap_or_default(),
+            sm_util: field(columns.sm),
+            mem_util: field(columns.mem),
+            enc_util: field(columns.enc),
+            dec_util: field(columns.dec),
+            command: sanitize_command(&parts[columns.command..].join(" ")),
+        })
+    }
+}
+
+#[cfg(test)]
+mod pmon_tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_default_layout() {
+        let line = "    1  27581     C     99     14      -      -      -      -    python";
+        let sample = ProcessSample::parse_line(line).unwrap();
+        assert_eq!(sample.gpu_idx, 1);
+        assert_eq!(sample.pid, 27581);
+        assert_eq!(sample.process_type, "C");
+        assert_eq!(sample.sm_util, Some(99));
+        assert_eq!(sample.mem_util, Some(14));
+        assert_eq!(sample.enc_util, None);
+        assert_eq!(sample.command, "python");
+    }
+
+    #[test]
+    fn test_parse_older_driver_without_jpg_ofa() {
+        let header = "# gpu        pid  type    sm   mem   enc   dec   command";
+        let units = "# Idx          #   C/G     %     %     %     %   name";
+        let columns = PmonColumns::from_header(header).unwrap();
+        assert!(PmonColumns::from_header(units).is_none());
+
+        let line = "    0      12345     C    45    12     3     -   python3";
+        assert!(ProcessSample::parse_line(line).is_none());
+        let sample = ProcessSample::parse_line_with(line, &columns).unwrap();
+        assert_eq!(sample.pid, 12345);
+        assert_eq!(sample.process_type, "C");
+        assert_eq!(sample.sm_util, Some(45));
+        assert_eq!(sample.enc_util, Some(3));
+        assert_eq!(sample.dec_util, None);
+        assert_eq!(sample.command, "python3");
+    }
+
+    #[test]
+    fn test_parse_memory_columns_variant() {
+        // nvidia-smi pmon -s um adds framebuffer and ccpm columns before command
+        let header = "# gpu         pid   type     sm    mem    enc    dec    jpg    ofa     fb   ccpm    command";
+        let columns = PmonColumns::from_header(header).unwrap();
+
+        let line = "    0       4242    C+G     20     5      -      -      -      -    812      0    Xorg";
+        let sample = ProcessSample::parse_line_with(line, &columns).unwrap();
+        assert_eq!(sample.gpu_idx, 0);
+        assert_eq!(sample.process_type, "C+G");
+        assert_eq!(sample.sm_util, Some(20));
+        assert_eq!(sample.mem_util, Some(5));
+        assert_eq!(sample.command, "Xorg");
+    }
+
+    #[test]
+    fn test_skip_idle_gpu_row() {
+        let line = "    1          -     -      -      -      -      -      -      -    -";
+        assert!(ProcessSample::parse_line(line).is_none());
+    }
+}
+
+// ============================================================================
+// Encoder Sessions Parser (NVENC sessions per process)
+// ============================================================================
+/// One line of: nvidia-smi encodersessions
+
+#[derive(Debug, Clone)]
+#[allow(dead_code)]
+pub struct EncoderSession {
+    pub gpu_idx: u32,
+    pub session_id: u32,
+    pub pid: u32,
+    pub codec: String,
+}
+
+impl EncoderSession {
+    /// Parse a session line; headers and the all-`-` line printed for a GPU
+    /// without sessions give None
+    pub fn parse_line(line: &str) -> Option<Self> {
+        if line.trim_start().starts_with('#') {
+            return None;
+        }
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        if parts.len() < 4 {
+            return None;
+        }
         Some(Self {
             gpu_idx: parts[0].parse().ok()?,
-            pid: parts[1].parse().ok()?,
-            process_type: parts[2].to_string(),
-            sm_util: Self::parse_optional(parts[3]),
-            mem_util: Self::parse_optional(parts[4]),
-            enc_util: Self::parse_optional(parts[5]),
-            dec_util: Self::parse_optional(parts[6]),
-            // Skip jpg (7) and ofa (8)
-            command: parts[9..].join(" "),
+            session_id: parts[1].parse().ok()?,
+            pid: parts[2].parse().ok()?,
+            codec: parts[3].to_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod encoder_tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_encoder_sessions() {
+        let output = "\
+# GPU  Session  Process   Codec       H       V Average    Average
+# Idx       ID       ID    Type     Res     Res     FPS   Latency(us)
+    0        1    23456   H.264    1920    1080      30       1234
+    0        2    23456    HEVC    3840    2160      60        950
+    1        -        -       -       -       -       -          -
+";
+        let sessions: Vec<EncoderSession> = output.lines().filter_map(EncoderSession::parse_line).collect();
+        assert_eq!(sessions.len(), 2);
+        assert_eq!((sessions[0].gpu_idx, sessions[0].session_id, sessions[0].pid), (0, 1, 23456));
+        assert_eq!(sessions[1].codec, "HEVC");
+    }
+}
+
+// ============================================================================
+// Xid Parser (kernel log)
+// ============================================================================
+/// An NVIDIA driver error from the kernel log, parsed from lines like
+/// `NVRM: Xid (PCI:0000:3b:00): 79, pid=1234, name=python, GPU has fallen off the bus.`
+#[derive(Debug, Clone, PartialEq)]
+pub struct XidEvent {
+    /// PCI address as the driver prints it, e.g. `0000:3b:00`
+    pub pci: String,
+    pub code: u32,
+    /// Rest of the line after the code
+    pub detail: String,
+}
+
+impl XidEvent {
+    /// Parse a kernel log line; anything that isn't an Xid report gives None
+    pub fn parse_line(line: &str) -> Option<Self> {
+        let (_, rest) = line.split_once("NVRM: Xid (")?;
+        let (pci, rest) = rest.split_once("):")?;
+        let pci = pci.strip_prefix("PCI:").unwrap_or(pci);
+        let rest = rest.trim_start();
+        let (code, detail) = rest.split_once(',').unwrap_or((rest, ""));
+        Some(Self {
+            pci: pci.to_string(),
+            code: code.trim().parse().ok()?,
+            detail: detail.trim().to_string(),
         })
     }
 }
 
+#[cfg(test)]
+mod xid_tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_xid_lines() {
+        let dmesg = "\
+[ 1234.567890] NVRM: loading NVIDIA UNIX x86_64 Kernel Module  550.54.15
+[ 5678.000001] NVRM: Xid (PCI:0000:3b:00): 79, pid=1234, name=python, GPU has fallen off the bus.
+[ 5679.100000] NVRM: Xid (PCI:0000:41:00): 48, pid='<unknown>', name=<unknown>, An uncorrectable double bit error (DBE) has been detected on GPU in the framebuffer at partition 6, subpartition 0.
+[ 5680.000000] NVRM: Xid (PCI:0000:41:00): bogus
+";
+        let events: Vec<XidEvent> = dmesg.lines().filter_map(XidEvent::parse_line).collect();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].pci, "0000:3b:00");
+        assert_eq!(events[0].code, 79);
+        assert_eq!(events[0].detail, "pid=1234, name=python, GPU has fallen off the bus.");
+        assert_eq!(events[1].code, 48);
+
+        let gpu = GpuInfo {
+            pci_bus_id: "00000000:3B:00.0".into(),
+            ..Default::default()
+        };
+        assert!(gpu.pci_matches(&events[0].pci));
+        assert!(!gpu.pci_matches(&events[1].pci));
+    }
+}
+
 // ============================================================================
 // Query GPU Parser (static and memory info)
 // ============================================================================
@@ -250,6 +673,9 @@ pub struct GpuInfo {
     pub memory_used_mib: u64,
     pub memory_free_mib: u64,
     pub power_limit_w: Option<f32>,
+    /// Range `nvidia-smi -pl` accepts
+    pub power_min_limit_w: Option<f32>,
+    pub power_max_limit_w: Option<f32>,
     pub power_draw_w: Option<f32>,
     pub temperature_c: Option<u32>,
     pub temperature_limit_c: Option<u32>,
@@ -260,6 +686,28 @@ pub struct GpuInfo {
     pub fan_speed_pct: Option<u32>,
     pub pstate: String,
     pub throttle_reasons: Vec<String>,
+    pub clock_sm_mhz: Option<u32>,
+    pub clock_mem_mhz: Option<u32>,
+    pub clock_max_sm_mhz: Option<u32>,
+    pub clock_max_mem_mhz: Option<u32>,
+    /// BAR1 aperture (CPU/peer-mapped VRAM) size and usage; only in
+    /// `nvidia-smi -q`, so filled in separately from the CSV query
+    pub bar1_total_mib: Option<u64>,
+    pub bar1_used_mib: Option<u64>,
+    /// PCI address, e.g. `00000000:3B:00.0`; matches Xid reports to GPUs
+    pub pci_bus_id: String,
+    /// VRAM pages retired after single-bit and double-bit ECC errors, and
+    /// whether a retirement is waiting for the next driver reload. None
+    /// on GPUs without page retirement
+    pub retired_pages_sbe: Option<u32>,
+    pub retired_pages_dbe: Option<u32>,
+    pub retired_pages_pending: bool,
+    /// Error text nvidia-smi reported in place of readings (e.g. a GPU that
+    /// fell off the bus)
+    pub error: Option<String>,
+    /// Some readings came back as `[Insufficient Permissions]` (e.g. power
+    /// draw without root on some drivers) and are missing for that reason
+    pub insufficient_permissions: bool,
 }
 
 impl GpuInfo {
@@ -270,15 +718,9 @@ impl GpuInfo {
             return None;
         }
 
-        let parse_u32 = |s: &str| -> Option<u32> {
-            s.trim().replace("[Not Supported]", "").replace("[N/A]", "").parse().ok()
-        };
-        let parse_u64 = |s: &str| -> Option<u64> {
-            s.trim().replace("[Not Supported]", "").replace("[N/A]", "").parse().ok()
-        };
-        let parse_f32 = |s: &str| -> Option<f32> {
-            s.trim().replace("[Not Supported]", "").replace("[N/A]", "").parse().ok()
-        };
+        let parse_u32 = |s: &str| -> Option<u32> { strip_unavailable(s).parse().ok() };
+        let parse_u64 = |s: &str| -> Option<u64> { strip_unavailable(s).parse().ok() };
+        let parse_f32 = |s: &str| -> Option<f32> { strip_unavailable(s).parse().ok() };
 
         Some(Self {
             index,
@@ -289,8 +731,10 @@ impl GpuInfo {
             memory_used_mib: parse_u64(parts[4]).unwrap_or(0),
             memory_free_mib: parse_u64(parts[5]).unwrap_or(0),
             power_limit_w: parse_f32(parts[6]),
-            power_draw_w: parse_f32(parts[7]),
-            temperature_c: parse_u32(parts[8]),
+            power_min_limit_w: parts.get(21).and_then(|s| parse_f32(s)),
+            power_max_limit_w: parts.get(22).and_then(|s| parse_f32(s)),
+            power_draw_w: plausible_power(parse_f32(parts[7])),
+            temperature_c: plausible_temp(parse_u32(parts[8])),
             temperature_limit_c: parse_u32(parts[9]),
             pcie_gen_current: parse_u32(parts[10]),
             pcie_gen_max: parse_u32(parts[11]),
@@ -298,9 +742,716 @@ impl GpuInfo {
             pcie_width_max: parse_u32(parts[13]),
             fan_speed_pct: parse_u32(parts[14]),
             pstate: parts[15].to_string(),
-            throttle_reasons: Vec::new(),
+            throttle_reasons: parts.get(20).map(|s| parse_throttle_reasons(s)).unwrap_or_default(),
+            // Clock columns are optional so older query lists still parse
+            clock_sm_mhz: parts.get(16).and_then(|s| parse_u32(s)),
+            clock_mem_mhz: parts.get(17).and_then(|s| parse_u32(s)),
+            clock_max_sm_mhz: parts.get(18).and_then(|s| parse_u32(s)),
+            clock_max_mem_mhz: parts.get(19).and_then(|s| parse_u32(s)),
+            bar1_total_mib: None,
+            bar1_used_mib: None,
+            retired_pages_sbe: parts.get(23).and_then(|s| parse_u32(s)),
+            retired_pages_dbe: parts.get(24).and_then(|s| parse_u32(s)),
+            retired_pages_pending: parts.get(25).is_some_and(|s| s.eq_ignore_ascii_case("Yes")),
+            pci_bus_id: parts.get(26).map(|s| s.to_string()).unwrap_or_default(),
+            error: parts.iter().find(|p| is_error_sentinel(p)).map(|p| p.to_string()),
+            insufficient_permissions: parts.contains(&INSUFFICIENT_PERMISSIONS),
         })
     }
+
+    /// Composite 0-100 score for how much more work this GPU can take,
+    /// from temperature margin, power margin and active throttling.
+    /// Readings that aren't reported are left out of the average.
+    pub fn headroom_score(&self) -> Option<u32> {
+        let mut parts: Vec<(f64, f64)> = Vec::new(); // (score 0..1, weight)
+
+        if let Some(temp) = self.temperature_c {
+            // Fall back to a typical slowdown point when no usable limit is reported
+            let limit = self.temperature_limit_c.filter(|&l| l > temp).unwrap_or(HEADROOM_TEMP_LIMIT_C);
+            let margin = limit.saturating_sub(temp) as f64;
+            parts.push(((margin / HEADROOM_TEMP_SPAN_C).min(1.0), 0.4));
+        }
+
+        if let (Some(draw), Some(limit)) = (self.power_draw_w, self.power_limit_w) {
+            if limit > 0.0 {
+                parts.push(((1.0 - draw as f64 / limit as f64).clamp(0.0, 1.0), 0.4));
+            }
+        }
+
+        if parts.is_empty() {
+            return None;
+        }
+
+        // Older drivers don't report reasons; an empty list counts as unthrottled
+        let throttled = self.throttle_reasons.iter().any(|r| !BENIGN_THROTTLE_REASONS.contains(&r.as_str()));
+        parts.push((if throttled { 0.0 } else { 1.0 }, 0.2));
+
+        let weight: f64 = parts.iter().map(|(_, w)| w).sum();
+        let score = parts.iter().map(|(s, w)| s * w).sum::<f64>() / weight;
+        Some((score * 100.0).round() as u32)
+    }
+
+    /// Fan reported (nearly) stopped while the GPU is hot: likely a stuck or
+    /// failing fan. Passively cooled cards report no fan speed and never match
+    pub fn fan_stalled(&self) -> bool {
+        match (self.fan_speed_pct, self.temperature_c) {
+            (Some(fan), Some(temp)) => fan <= FAN_STALL_MAX_PCT && temp >= FAN_STALL_TEMP_C,
+            _ => false,
+        }
+    }
+
+    /// Check a requested power limit against the range the GPU reports.
+    /// Bounds that aren't reported are left for nvidia-smi to enforce
+    pub fn check_power_limit(&self, watts: f32) -> Result<(), String> {
+        if self.power_min_limit_w.is_some_and(|min| watts < min) || self.power_max_limit_w.is_some_and(|max| watts > max) {
+            let bound = |b: Option<f32>| b.map(|w| format!("{:.0}", w)).unwrap_or("?".into());
+            return Err(format!(
+                "{:.0} W is outside GPU {}'s range of {}-{} W",
+                watts,
+                self.index,
+                bound(self.power_min_limit_w),
+                bound(self.power_max_limit_w)
+            ));
+        }
+        Ok(())
+    }
+
+    /// Double-bit ECC errors have retired VRAM pages, or a retirement is
+    /// pending: the memory is failing and the GPU should be looked at
+    pub fn memory_failing(&self) -> bool {
+        self.retired_pages_dbe.is_some_and(|n| n > 0) || self.retired_pages_pending
+    }
+
+    /// Whether a kernel log PCI address like `0000:3b:00` is this GPU's
+    /// (`00000000:3B:00.0` in nvidia-smi's longer form)
+    pub fn pci_matches(&self, pci: &str) -> bool {
+        // (domain, bus, device) as numbers, ignoring the function
+        let parse = |id: &str| -> Option<(u32, u32, u32)> {
+            let mut parts = id.split(':');
+            let domain = u32::from_str_radix(parts.next()?, 16).ok()?;
+            let bus = u32::from_str_radix(parts.next()?, 16).ok()?;
+            let device = parts.next()?.split('.').next()?;
+            Some((domain, bus, u32::from_str_radix(device, 16).ok()?))
+        };
+        parse(&self.pci_bus_id).is_some_and(|id| parse(pci) == Some(id))
+    }
+
+    /// Current SM clock as a percentage of the max boost clock
+    pub fn sm_clock_pct(&self) -> Option<f64> {
+        match (self.clock_sm_mhz, self.clock_max_sm_mhz) {
+            (Some(cur), Some(max)) if max > 0 => Some(cur as f64 / max as f64 * 100.0),
+            _ => None,
+        }
+    }
+}
+
+/// Placeholder for a reading the user isn't allowed to query
+const INSUFFICIENT_PERMISSIONS: &str = "[Insufficient Permissions]";
+
+/// A query field with nvidia-smi's placeholders for unavailable readings
+/// removed, leaving "" to fail parsing
+fn strip_unavailable(field: &str) -> String {
+    field
+        .trim()
+        .replace("[Not Supported]", "")
+        .replace("[N/A]", "")
+        .replace(INSUFFICIENT_PERMISSIONS, "")
+}
+
+/// Highest temperature and power draw taken as real readings. Some drivers
+/// print bogus values while a GPU initializes (e.g. 4294967295, a -1 read
+/// as unsigned); those are treated as unavailable rather than charted
+const MAX_PLAUSIBLE_TEMP_C: u32 = 150;
+const MAX_PLAUSIBLE_POWER_W: u32 = 2000;
+
+/// A temperature reading, or None when it's out of range
+fn plausible_temp(temp: Option<u32>) -> Option<u32> {
+    temp.filter(|&t| t <= MAX_PLAUSIBLE_TEMP_C)
+}
+
+/// A power reading in watts, or None when it's negative or out of range
+fn plausible_power(watts: Option<f32>) -> Option<f32> {
+    watts.filter(|&w| (0.0..=MAX_PLAUSIBLE_POWER_W as f32).contains(&w))
+}
+
+/// Values nvidia-smi prints instead of a reading when the GPU is in trouble
+fn is_error_sentinel(field: &str) -> bool {
+    field.contains("ERR!") || field.contains("Unknown Error") || field.contains("GPU is lost")
+}
+
+/// Slowdown temperature assumed when the driver doesn't report one
+const HEADROOM_TEMP_LIMIT_C: u32 = 90;
+/// Degrees below the limit that count as full thermal headroom
+const HEADROOM_TEMP_SPAN_C: f64 = 30.0;
+/// Fan speed at or below which a hot GPU's fan counts as stalled
+const FAN_STALL_MAX_PCT: u32 = 5;
+/// Temperature from which a stalled fan is reported; idle cards with
+/// zero-RPM fan modes sit well below this
+const FAN_STALL_TEMP_C: u32 = 70;
+/// Clock event reasons that don't mean the GPU is being held back
+const BENIGN_THROTTLE_REASONS: [&str; 2] = ["GpuIdle", "ApplicationsClocksSetting"];
+
+/// Names for the bits of `clocks_throttle_reasons.active`
+const THROTTLE_REASON_BITS: [(u64, &str); 9] = [
+    (0x1, "GpuIdle"),
+    (0x2, "ApplicationsClocksSetting"),
+    (0x4, "SwPowerCap"),
+    (0x8, "HwSlowdown"),
+    (0x20, "SyncBoost"),
+    (0x40, "SwThermalSlowdown"),
+    (0x80, "HwThermalSlowdown"),
+    (0x100, "HwPowerBrakeSlowdown"),
+    (0x200, "DisplayClockSetting"),
+];
+
+/// Decode a hex bitmask like "0x0000000000000004" into reason names
+fn parse_throttle_reasons(s: &str) -> Vec<String> {
+    let mask = match u64::from_str_radix(s.trim().trim_start_matches("0x"), 16) {
+        Ok(m) => m,
+        Err(_) => return Vec::new(),
+    };
+    THROTTLE_REASON_BITS
+        .iter()
+        .filter(|(bit, _)| mask & bit != 0)
+        .map(|(_, name)| name.to_string())
+        .collect()
+}
+
+/// CUDA version from `nvidia-smi --version` ("CUDA Version        : 12.4") or
+/// the plain `nvidia-smi` banner ("CUDA Version: 12.4     |")
+pub fn parse_cuda_version(output: &str) -> Option<String> {
+    let line = output.lines().find(|l| l.contains("CUDA Version"))?;
+    let (_, rest) = line.split_once("CUDA Version")?;
+    let version = rest.trim_start().trim_start_matches(':').split_whitespace().next()?;
+    (!version.is_empty() && version != "N/A").then(|| version.to_string())
+}
+
+/// Throttle reason elements in `nvidia-smi -q -x` output, after the
+/// `clocks_event_reason_` (or older `clocks_throttle_reason_`) prefix, and
+/// the names `--query-gpu` reasons decode to
+const XML_THROTTLE_REASONS: [(&str, &str); 9] = [
+    ("gpu_idle", "GpuIdle"),
+    ("applications_clocks_setting", "ApplicationsClocksSetting"),
+    ("sw_power_cap", "SwPowerCap"),
+    ("hw_slowdown", "HwSlowdown"),
+    ("sync_boost", "SyncBoost"),
+    ("sw_thermal_slowdown", "SwThermalSlowdown"),
+    ("hw_thermal_slowdown", "HwThermalSlowdown"),
+    ("hw_power_brake_slowdown", "HwPowerBrakeSlowdown"),
+    ("display_clocks_setting", "DisplayClockSetting"),
+];
+
+impl GpuInfo {
+    /// Parse `nvidia-smi -q -x`. Elements are looked up by name, so fields
+    /// that moved or were renamed between drivers (power readings, throttle
+    /// reasons) are tried under each name. Malformed XML yields no GPUs
+    pub fn parse_xml(xml: &str) -> Vec<Self> {
+        // nvidia-smi starts with a <!DOCTYPE>, which roxmltree refuses by default
+        let options = roxmltree::ParsingOptions {
+            allow_dtd: true,
+            ..Default::default()
+        };
+        let Ok(doc) = roxmltree::Document::parse_with_options(xml, options) else {
+            return Vec::new();
+        };
+        let driver_version = doc
+            .root_element()
+            .children()
+            .find(|n| n.has_tag_name("driver_version"))
+            .and_then(|n| n.text())
+            .unwrap_or_default()
+            .to_string();
+
+        doc.root_element()
+            .children()
+            .filter(|n| n.has_tag_name("gpu"))
+            .enumerate()
+            .map(|(index, gpu)| Self::from_xml_gpu(gpu, index as u32, &driver_version))
+            .collect()
+    }
+
+    fn from_xml_gpu(gpu: roxmltree::Node, index: u32, driver_version: &str) -> Self {
+        // Text at a path of child element names, e.g. ["fb_memory_usage", "total"]
+        let text = |path: &[&str]| -> Option<&str> {
+            let mut node = gpu;
+            for name in path {
+                node = node.children().find(|n| n.has_tag_name(*name))?;
+            }
+            node.text().map(str::trim)
+        };
+        // Leading number of values like "81920 MiB", "62.31 W" or "16x"
+        let number = |path: &[&str]| -> Option<f64> {
+            let value = text(path)?.split_whitespace().next()?;
+            value.trim_end_matches('x').parse().ok()
+        };
+        let int = |path: &[&str]| number(path).map(|v| v as u32);
+        let mib = |path: &[&str]| number(path).map(|v| v as u64);
+        let first = |paths: &[&[&str]]| paths.iter().find_map(|p| number(p)).map(|v| v as f32);
+
+        let throttle_reasons = ["clocks_event_reasons", "clocks_throttle_reasons"]
+            .iter()
+            .find_map(|section| gpu.children().find(|n| n.has_tag_name(*section)))
+            .map(|section| {
+                section
+                    .children()
+                    .filter(|n| n.text().map(str::trim) == Some("Active"))
+                    .filter_map(|n| {
+                        let tag = n.tag_name().name();
+                        let reason = tag
+                            .strip_prefix("clocks_event_reason_")
+                            .or_else(|| tag.strip_prefix("clocks_throttle_reason_"))?;
+                        XML_THROTTLE_REASONS
+                            .iter()
+                            .find(|(xml, _)| *xml == reason)
+                            .map(|(_, name)| name.to_string())
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let insufficient_permissions = gpu
+            .descendants()
+            .filter_map(|n| n.text())
+            .any(|t| t.contains("Insufficient Permissions"));
+
+        let error = gpu
+            .descendants()
+            .filter_map(|n| n.text())
+            .map(str::trim)
+            .find(|t| is_error_sentinel(t))
+            .map(str::to_string);
+
+        Self {
+            index,
+            name: text(&["product_name"]).unwrap_or_default().to_string(),
+            uuid: text(&["uuid"]).unwrap_or_default().to_string(),
+            driver_version: driver_version.to_string(),
+            memory_total_mib: mib(&["fb_memory_usage", "total"]).unwrap_or(0),
+            memory_used_mib: mib(&["fb_memory_usage", "used"]).unwrap_or(0),
+            memory_free_mib: mib(&["fb_memory_usage", "free"]).unwrap_or(0),
+            // Renamed to gpu_power_readings (with separate instant/average
+            // draw) in newer drivers
+            power_limit_w: first(&[
+                &["gpu_power_readings", "current_power_limit"],
+                &["power_readings", "power_limit"],
+            ]),
+            power_min_limit_w: first(&[
+                &["gpu_power_readings", "min_power_limit"],
+                &["power_readings", "min_power_limit"],
+            ]),
+            power_max_limit_w: first(&[
+                &["gpu_power_readings", "max_power_limit"],
+                &["power_readings", "max_power_limit"],
+            ]),
+            power_draw_w: plausible_power(first(&[
+                &["gpu_power_readings", "instant_power_draw"],
+                &["gpu_power_readings", "power_draw"],
+                &["power_readings", "power_draw"],
+            ])),
+            temperature_c: plausible_temp(int(&["temperature", "gpu_temp"])),
+            temperature_limit_c: int(&["temperature", "gpu_temp_slow_threshold"]),
+            pcie_gen_current: int(&["pci", "pci_gpu_link_info", "pcie_gen", "current_link_gen"]),
+            pcie_gen_max: int(&["pci", "pci_gpu_link_info", "pcie_gen", "max_link_gen"]),
+            pcie_width_current: int(&["pci", "pci_gpu_link_info", "link_widths", "current_link_width"]),
+            pcie_width_max: int(&["pci", "pci_gpu_link_info", "link_widths", "max_link_width"]),
+            fan_speed_pct: int(&["fan_speed"]),
+            pstate: text(&["performance_state"]).unwrap_or_default().to_string(),
+            throttle_reasons,
+            clock_sm_mhz: int(&["clocks", "sm_clock"]),
+            clock_mem_mhz: int(&["clocks", "mem_clock"]),
+            clock_max_sm_mhz: int(&["max_clocks", "sm_clock"]),
+            clock_max_mem_mhz: int(&["max_clocks", "mem_clock"]),
+            bar1_total_mib: mib(&["bar1_memory_usage", "total"]),
+            bar1_used_mib: mib(&["bar1_memory_usage", "used"]),
+            pci_bus_id: text(&["pci", "pci_bus_id"])
+                .or_else(|| gpu.attribute("id"))
+                .unwrap_or_default()
+                .to_string(),
+            retired_pages_sbe: int(&["retired_pages", "multiple_single_bit_retirement", "retired_count"]),
+            retired_pages_dbe: int(&["retired_pages", "double_bit_retirement", "retired_count"]),
+            // pending_blacklist in older drivers
+            retired_pages_pending: ["pending_retirement", "pending_blacklist"]
+                .iter()
+                .any(|tag| text(&["retired_pages", tag]) == Some("Yes")),
+            error,
+            insufficient_permissions,
+        }
+    }
+}
+
+/// BAR1 (total, used) in MiB per GPU from `nvidia-smi -q -d MEMORY`, keyed
+/// by the PCI bus id heading each GPU's section (as `pci.bus_id` reports it).
+/// Readings a GPU doesn't report are None
+pub fn parse_bar1_usage(output: &str) -> HashMap<String, (Option<u64>, Option<u64>)> {
+    let mut gpus = HashMap::new();
+    let mut current = None;
+    let mut in_bar1 = false;
+    for line in output.lines() {
+        let trimmed = line.trim();
+        if let Some(bus_id) = line.strip_prefix("GPU ") {
+            let bus_id = bus_id.trim().to_string();
+            gpus.insert(bus_id.clone(), (None, None));
+            current = Some(bus_id);
+            in_bar1 = false;
+        } else if !trimmed.contains(':') {
+            // Section headers ("FB Memory Usage") have no value
+            in_bar1 = trimmed == "BAR1 Memory Usage";
+        } else if in_bar1 {
+            let Some((key, value)) = trimmed.split_once(':') else {
+                continue;
+            };
+            let mib = value.trim().trim_end_matches("MiB").trim().parse().ok();
+            if let Some(gpu) = current.as_ref().and_then(|id| gpus.get_mut(id)) {
+                match key.trim() {
+                    "Total" => gpu.0 = mib,
+                    "Used" => gpu.1 = mib,
+                    _ => {}
+                }
+            }
+        }
+    }
+    gpus
+}
+
+#[cfg(test)]
+mod query_tests {
+    use super::*;
+
+    const QUERY_LINE: &str = "NVIDIA A100-SXM4-80GB, GPU-3f1c2a4e-0000-0000-0000-000000000000, 550.54.15, 81920, 1024, 80896, 400.00, 62.31, 34, 87, 4, 4, 16, 16, [N/A], P0, 1410, 1593, 1410, 1593";
+
+    #[test]
+    fn test_parse_query_line_with_clocks() {
+        let info = GpuInfo::parse_csv_line(QUERY_LINE, 0).unwrap();
+        assert_eq!(info.name, "NVIDIA A100-SXM4-80GB");
+        assert_eq!(info.memory_total_mib, 81920);
+        assert_eq!(info.fan_speed_pct, None);
+        assert_eq!(info.clock_sm_mhz, Some(1410));
+        assert_eq!(info.clock_max_mem_mhz, Some(1593));
+        assert_eq!(info.sm_clock_pct(), Some(100.0));
+    }
+
+    #[test]
+    fn test_parse_etime_formats() {
+        assert_eq!(parse_etime("00:07"), Some(Duration::from_secs(7)));
+        assert_eq!(parse_etime("12:34"), Some(Duration::from_secs(754)));
+        assert_eq!(parse_etime("01:02:03"), Some(Duration::from_secs(3723)));
+        assert_eq!(parse_etime("2-03:00:01"), Some(Duration::from_secs(2 * 86400 + 3 * 3600 + 1)));
+        assert_eq!(parse_etime("-"), None);
+        assert_eq!(parse_etime("1:2:3:4"), None);
+    }
+
+    #[test]
+    fn test_parse_ps_line_with_args() {
+        let info = ProcessSystemInfo::parse_ps_line("  4242 alice 98.5 1048576    01:02:03 python train.py --lr 3e-4").unwrap();
+        assert_eq!((info.pid, info.user.as_str()), (4242, "alice"));
+        assert_eq!(info.rss_kb, 1048576);
+        assert_eq!(info.elapsed, "01:02:03");
+        assert_eq!(info.runtime, Some(Duration::from_secs(3723)));
+        assert_eq!(info.args, "python train.py --lr 3e-4");
+
+        let info = ProcessSystemInfo::parse_ps_line("4242 alice 0.0 512 05:00").unwrap();
+        assert_eq!(info.args, "");
+    }
+
+    #[test]
+    fn test_parse_container_id() {
+        let id = "4f6c2d1e9a8b7c6d5e4f3a2b1c0d9e8f7a6b5c4d3e2f1a0b9c8d7e6f5a4b3c2d";
+        let docker_v1 = format!("12:memory:/docker/{}\n11:cpu:/docker/{}", id, id);
+        assert_eq!(parse_container_id(&docker_v1).as_deref(), Some("4f6c2d1e9a8b"));
+        let systemd = format!("0::/system.slice/docker-{}.scope", id);
+        assert_eq!(parse_container_id(&systemd).as_deref(), Some("4f6c2d1e9a8b"));
+        let kubernetes = format!(
+            "0::/kubepods.slice/kubepods-burstable.slice/kubepods-burstable-pod1234.slice/cri-containerd-{}.scope",
+            id
+        );
+        assert_eq!(parse_container_id(&kubernetes).as_deref(), Some("4f6c2d1e9a8b"));
+
+        assert_eq!(parse_container_id("0::/user.slice/user-1000.slice/session-2.scope"), None);
+        assert_eq!(parse_container_id(""), None);
+
+        let listing = format!("/proc/42/cgroup:{}\n/proc/43/cgroup:0::/init.scope", systemd);
+        let containers = parse_cgroup_listing(&listing);
+        assert_eq!(containers.get(&42).map(String::as_str), Some("4f6c2d1e9a8b"));
+        assert!(!containers.contains_key(&43));
+    }
+
+    #[test]
+    fn test_control_characters_in_commands() {
+        let app = ComputeApp::parse_csv_line("4242, /tmp/evil\tname\x1b[2J, GPU-aaaa, 500 MiB").unwrap();
+        assert_eq!(app.name, "/tmp/evil?name?[2J");
+
+        // ps output is whitespace-split, so only non-whitespace controls survive
+        let info = ProcessSystemInfo::parse_ps_line("4242 alice 1.0 512 05:00 python \x07beep\x7f").unwrap();
+        assert_eq!(info.args, "python ?beep?");
+
+        assert_eq!(sanitize_command("a\tb\nc\rd"), "a?b?c?d");
+    }
+
+    #[test]
+    fn test_parse_query_line_implausible() {
+        let line = QUERY_LINE.replace("62.31, 34,", "65535.00, 4294967295,");
+        let info = GpuInfo::parse_csv_line(&line, 0).unwrap();
+        assert_eq!((info.power_draw_w, info.temperature_c), (None, None));
+        assert_eq!(info.power_limit_w, Some(400.0));
+    }
+
+    #[test]
+    fn test_parse_query_line_with_error() {
+        assert_eq!(GpuInfo::parse_csv_line(QUERY_LINE, 0).unwrap().error, None);
+
+        let line = "NVIDIA A100-SXM4-80GB, GPU-3f1c2a4e, 550.54.15, [Unknown Error], [Unknown Error], [Unknown Error], ERR!, ERR!, ERR!, ERR!, [Unknown Error], [Unknown Error], [Unknown Error], [Unknown Error], ERR!, [Unknown Error]";
+        let info = GpuInfo::parse_csv_line(line, 3).unwrap();
+        assert_eq!(info.error.as_deref(), Some("[Unknown Error]"));
+        assert_eq!(info.memory_total_mib, 0);
+    }
+
+    const XML: &str = r#"<?xml version="1.0" ?>
+<!DOCTYPE nvidia_smi_log SYSTEM "nvsmi_device_v12.dtd">
+<nvidia_smi_log>
+	<driver_version>550.54.15</driver_version>
+	<attached_gpus>2</attached_gpus>
+	<gpu id="00000000:01:00.0">
+		<product_name>NVIDIA A100-SXM4-80GB</product_name>
+		<uuid>GPU-3f1c2a4e</uuid>
+		<pci>
+			<pci_bus_id>00000000:01:00.0</pci_bus_id>
+			<pci_gpu_link_info>
+				<pcie_gen>
+					<max_link_gen>4</max_link_gen>
+					<current_link_gen>4</current_link_gen>
+				</pcie_gen>
+				<link_widths>
+					<max_link_width>16x</max_link_width>
+					<current_link_width>16x</current_link_width>
+				</link_widths>
+			</pci_gpu_link_info>
+		</pci>
+		<fan_speed>N/A</fan_speed>
+		<performance_state>P0</performance_state>
+		<clocks_event_reasons>
+			<clocks_event_reason_gpu_idle>Not Active</clocks_event_reason_gpu_idle>
+			<clocks_event_reason_sw_power_cap>Active</clocks_event_reason_sw_power_cap>
+		</clocks_event_reasons>
+		<fb_memory_usage>
+			<total>81920 MiB</total>
+			<reserved>512 MiB</reserved>
+			<used>1024 MiB</used>
+			<free>80384 MiB</free>
+		</fb_memory_usage>
+		<bar1_memory_usage>
+			<total>131072 MiB</total>
+			<used>5 MiB</used>
+			<free>131067 MiB</free>
+		</bar1_memory_usage>
+		<temperature>
+			<gpu_temp>34 C</gpu_temp>
+			<gpu_temp_slow_threshold>87 C</gpu_temp_slow_threshold>
+		</temperature>
+		<gpu_power_readings>
+			<power_state>P0</power_state>
+			<average_power_draw>60.02 W</average_power_draw>
+			<instant_power_draw>62.31 W</instant_power_draw>
+			<current_power_limit>400.00 W</current_power_limit>
+			<min_power_limit>100.00 W</min_power_limit>
+			<max_power_limit>400.00 W</max_power_limit>
+		</gpu_power_readings>
+		<clocks>
+			<graphics_clock>1410 MHz</graphics_clock>
+			<sm_clock>1410 MHz</sm_clock>
+			<mem_clock>1593 MHz</mem_clock>
+		</clocks>
+		<max_clocks>
+			<sm_clock>1410 MHz</sm_clock>
+			<mem_clock>1593 MHz</mem_clock>
+		</max_clocks>
+	</gpu>
+	<gpu id="00000000:41:00.0">
+		<product_name>Tesla V100</product_name>
+		<uuid>GPU-bbbb</uuid>
+		<fan_speed>[Unknown Error]</fan_speed>
+		<retired_pages>
+			<multiple_single_bit_retirement>
+				<retired_count>2</retired_count>
+			</multiple_single_bit_retirement>
+			<double_bit_retirement>
+				<retired_count>1</retired_count>
+			</double_bit_retirement>
+			<pending_blacklist>No</pending_blacklist>
+		</retired_pages>
+		<clocks_throttle_reasons>
+			<clocks_throttle_reason_hw_slowdown>Active</clocks_throttle_reason_hw_slowdown>
+		</clocks_throttle_reasons>
+		<power_readings>
+			<power_draw>250.5 W</power_draw>
+			<power_limit>300.00 W</power_limit>
+		</power_readings>
+	</gpu>
+</nvidia_smi_log>
+"#;
+
+    #[test]
+    fn test_parse_xml_matches_csv_fields() {
+        let gpus = GpuInfo::parse_xml(XML);
+        assert_eq!(gpus.len(), 2);
+
+        let a100 = &gpus[0];
+        assert_eq!(a100.index, 0);
+        assert_eq!(a100.name, "NVIDIA A100-SXM4-80GB");
+        assert_eq!(a100.driver_version, "550.54.15");
+        assert_eq!((a100.memory_total_mib, a100.memory_used_mib), (81920, 1024));
+        assert_eq!(a100.power_draw_w, Some(62.31));
+        assert_eq!(a100.power_limit_w, Some(400.0));
+        assert_eq!((a100.power_min_limit_w, a100.power_max_limit_w), (Some(100.0), Some(400.0)));
+        assert_eq!((a100.temperature_c, a100.temperature_limit_c), (Some(34), Some(87)));
+        assert_eq!((a100.pcie_gen_current, a100.pcie_width_max), (Some(4), Some(16)));
+        assert_eq!(a100.fan_speed_pct, None);
+        assert_eq!(a100.throttle_reasons, vec!["SwPowerCap"]);
+        assert_eq!((a100.clock_sm_mhz, a100.clock_max_mem_mhz), (Some(1410), Some(1593)));
+        assert_eq!((a100.bar1_total_mib, a100.bar1_used_mib), (Some(131072), Some(5)));
+        assert_eq!(a100.error, None);
+        assert_eq!(a100.pci_bus_id, "00000000:01:00.0");
+        assert_eq!((a100.retired_pages_sbe, a100.retired_pages_dbe), (None, None));
+        assert!(!a100.memory_failing());
+
+        // Older driver layout, and a GPU reporting errors
+        let v100 = &gpus[1];
+        assert_eq!(v100.index, 1);
+        assert_eq!((v100.power_draw_w, v100.power_limit_w), (Some(250.5), Some(300.0)));
+        assert_eq!(v100.throttle_reasons, vec!["HwSlowdown"]);
+        assert_eq!(v100.error.as_deref(), Some("[Unknown Error]"));
+        assert_eq!(v100.pci_bus_id, "00000000:41:00.0");
+        assert_eq!((v100.retired_pages_sbe, v100.retired_pages_dbe), (Some(2), Some(1)));
+        assert!(v100.memory_failing());
+
+        assert!(GpuInfo::parse_xml("<nvidia_smi_log>").is_empty());
+    }
+
+    #[test]
+    fn test_parse_bar1_usage() {
+        let output = "
+==============NVSMI LOG==============
+
+Attached GPUs                             : 2
+GPU 00000000:01:00.0
+    FB Memory Usage
+        Total                             : 81920 MiB
+        Used                              : 1024 MiB
+        Free                              : 80896 MiB
+    BAR1 Memory Usage
+        Total                             : 131072 MiB
+        Used                              : 5 MiB
+        Free                              : 131067 MiB
+
+GPU 00000000:41:00.0
+    FB Memory Usage
+        Total                             : 8192 MiB
+        Used                              : 100 MiB
+    BAR1 Memory Usage
+        Total                             : N/A
+        Used                              : N/A
+";
+        let bar1 = parse_bar1_usage(output);
+        assert_eq!(bar1.len(), 2);
+        assert_eq!(bar1["00000000:01:00.0"], (Some(131072), Some(5)));
+        assert_eq!(bar1["00000000:41:00.0"], (None, None));
+        assert!(parse_bar1_usage("No devices were found").is_empty());
+    }
+
+    #[test]
+    fn test_throttle_reasons_lower_headroom() {
+        let idle = GpuInfo::parse_csv_line(&format!("{}, 0x0000000000000001", QUERY_LINE), 0).unwrap();
+        assert_eq!(idle.throttle_reasons, vec!["GpuIdle"]);
+        let capped = GpuInfo::parse_csv_line(&format!("{}, 0x0000000000000044", QUERY_LINE), 0).unwrap();
+        assert_eq!(capped.throttle_reasons, vec!["SwPowerCap", "SwThermalSlowdown"]);
+
+        // 34°C of an 87°C limit and 62W of 400W: plenty of room unless throttled
+        assert_eq!(idle.headroom_score(), Some(94));
+        assert_eq!(capped.headroom_score(), Some(74));
+        assert_eq!(GpuInfo::default().headroom_score(), None);
+    }
+
+    #[test]
+    fn test_fan_stalled() {
+        let gpu = |fan, temp| GpuInfo {
+            fan_speed_pct: fan,
+            temperature_c: Some(temp),
+            ..Default::default()
+        };
+        assert!(gpu(Some(0), 82).fan_stalled());
+        // Zero-RPM idle mode on a cool card
+        assert!(!gpu(Some(0), 45).fan_stalled());
+        assert!(!gpu(Some(60), 82).fan_stalled());
+        // Passively cooled
+        assert!(!gpu(None, 82).fan_stalled());
+    }
+
+    #[test]
+    fn test_parse_cuda_version() {
+        let version = "NVIDIA-SMI version  : 550.54.15\nNVML version        : 550.54\nDRIVER version      : 550.54.15\nCUDA Version        : 12.4\n";
+        assert_eq!(parse_cuda_version(version).as_deref(), Some("12.4"));
+
+        let banner = "| NVIDIA-SMI 550.54.15              Driver Version: 550.54.15      CUDA Version: 12.4     |";
+        assert_eq!(parse_cuda_version(banner).as_deref(), Some("12.4"));
+
+        assert_eq!(parse_cuda_version("NVIDIA-SMI 470.57.02"), None);
+    }
+
+    #[test]
+    fn test_check_power_limit_against_range() {
+        let line = format!("{}, 0x0000000000000000, 100.00, 400.00", QUERY_LINE);
+        let info = GpuInfo::parse_csv_line(&line, 0).unwrap();
+        assert_eq!((info.power_min_limit_w, info.power_max_limit_w), (Some(100.0), Some(400.0)));
+        assert!(info.check_power_limit(250.0).is_ok());
+        assert!(info.check_power_limit(400.0).is_ok());
+        assert_eq!(info.check_power_limit(450.0).unwrap_err(), "450 W is outside GPU 0's range of 100-400 W");
+        assert!(info.check_power_limit(50.0).is_err());
+
+        // Without reported bounds nvidia-smi has the final say
+        let unbounded = GpuInfo::parse_csv_line(QUERY_LINE, 0).unwrap();
+        assert!(unbounded.check_power_limit(9999.0).is_ok());
+    }
+
+    #[test]
+    fn test_insufficient_permissions() {
+        let line = QUERY_LINE.replace("62.31", "[Insufficient Permissions]");
+        let info = GpuInfo::parse_csv_line(&line, 0).unwrap();
+        assert_eq!(info.power_draw_w, None);
+        assert!(info.insufficient_permissions);
+        assert_eq!(info.error, None);
+
+        assert!(!GpuInfo::parse_csv_line(QUERY_LINE, 0).unwrap().insufficient_permissions);
+    }
+
+    #[test]
+    fn test_retired_pages() {
+        let line = format!("{}, 0x0000000000000000, 100.00, 400.00, 3, 0, No, 00000000:3B:00.0", QUERY_LINE);
+        let info = GpuInfo::parse_csv_line(&line, 0).unwrap();
+        assert_eq!((info.retired_pages_sbe, info.retired_pages_dbe), (Some(3), Some(0)));
+        assert_eq!(info.pci_bus_id, "00000000:3B:00.0");
+        // Single-bit retirements are corrected and routine
+        assert!(!info.memory_failing());
+
+        let line = format!("{}, 0x0000000000000000, 100.00, 400.00, 3, 0, Yes, 00000000:3B:00.0", QUERY_LINE);
+        assert!(GpuInfo::parse_csv_line(&line, 0).unwrap().memory_failing());
+        let line = format!("{}, 0x0000000000000000, 100.00, 400.00, 3, 2, No, 00000000:3B:00.0", QUERY_LINE);
+        assert!(GpuInfo::parse_csv_line(&line, 0).unwrap().memory_failing());
+
+        // GPUs without page retirement
+        let line = format!("{}, 0x0000000000000000, 100.00, 400.00, [N/A], [N/A], [N/A], 00000000:3B:00.0", QUERY_LINE);
+        let info = GpuInfo::parse_csv_line(&line, 0).unwrap();
+        assert_eq!(info.retired_pages_dbe, None);
+        assert!(!info.memory_failing());
+    }
+
+    #[test]
+    fn test_parse_query_line_without_clocks() {
+        let line = QUERY_LINE.rsplitn(5, ',').last().unwrap();
+        let info = GpuInfo::parse_csv_line(line, 1).unwrap();
+        assert_eq!(info.index, 1);
+        assert_eq!(info.pstate, "P0");
+        assert_eq!(info.clock_max_sm_mhz, None);
+        assert_eq!(info.sm_clock_pct(), None);
+    }
 }
 
 // ============================================================================
@@ -308,6 +1459,7 @@ impl GpuInfo {
 // ============================================================================
 /// GPU interconnect types
 #[derive(Debug, Clone, PartialEq)]
+#[allow(clippy::upper_case_acronyms)]
 pub enum GpuLink {
     Self_,        // X - same GPU
     PIX,          // Single PCIe bridge
@@ -328,30 +1480,46 @@ impl GpuLink {
             "PHB" => Some(Self::PHB),
             "NODE" => Some(Self::NODE),
             "SYS" => Some(Self::SYS),
-            s if s.starts_with("NV") => {
-                s[2..].parse().ok().map(Self::NVLink)
-            }
-            _ => None,
+            // NV# with any number of links, e.g. NV4, NV12 or NV18
+            s => s
+                .strip_prefix("NV")
+                .filter(|n| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()))
+                .and_then(|n| n.parse().ok())
+                .filter(|&n| n > 0)
+                .map(Self::NVLink),
+        }
+    }
+
+    pub fn description(&self) -> String {
+        match self {
+            Self::Self_ => "Self".into(),
+            Self::PIX => "Single PCIe bridge (fast)".into(),
+            Self::PXB => "Multiple PCIe bridges".into(),
+            Self::PHB => "PCIe Host Bridge".into(),
+            Self::NODE => "Same NUMA node".into(),
+            Self::SYS => "Cross NUMA (slow)".into(),
+            Self::NVLink(n) => format!("NVLink x{}", n),
         }
     }
 
-    pub fn description(&self) -> &'static str {
+    /// Bandwidth tier of an NVLink connection by its link count: 1 for one
+    /// or two links (bridged consumer/workstation cards), 2 for up to 7
+    /// (e.g. V100's NV4/NV6), 3 for 8 or more (NVSwitch systems: A100 NV12,
+    /// H100 NV18). None for other link types
+    pub fn nvlink_tier(&self) -> Option<u8> {
         match self {
-            Self::Self_ => "Self",
-            Self::PIX => "Single PCIe bridge (fast)",
-            Self::PXB => "Multiple PCIe bridges",
-            Self::PHB => "PCIe Host Bridge",
-            Self::NODE => "Same NUMA node",
-            Self::SYS => "Cross NUMA (slow)",
-            Self::NVLink(n) => match n {
-                1 => "NVLink x1",
-                2 => "NVLink x2",
-                _ => "NVLink",
-            },
+            Self::NVLink(n) if *n >= NVLINK_HIGH_TIER_LINKS => Some(3),
+            Self::NVLink(n) if *n >= NVLINK_MID_TIER_LINKS => Some(2),
+            Self::NVLink(_) => Some(1),
+            _ => None,
         }
     }
 }
 
+/// Link counts from which an NVLink connection is in the mid and high tier
+const NVLINK_MID_TIER_LINKS: u32 = 3;
+const NVLINK_HIGH_TIER_LINKS: u32 = 8;
+
 #[derive(Debug, Clone, Default)]
 pub struct GpuTopology {
     pub gpu_count: usize,
@@ -368,15 +1536,23 @@ impl GpuTopology {
 
         // Find the header line and data lines
         for (i, line) in lines.iter().enumerate() {
-            // Skip until we find a line starting with GPU0
+            // Skip until we find the column header, which starts with GPU0
             if line.trim().starts_with("GPU0") {
+                let gpu_columns = line
+                    .split_whitespace()
+                    .filter(|col| {
+                        col.strip_prefix("GPU")
+                            .is_some_and(|n| n.parse::<u32>().is_ok())
+                    })
+                    .count();
+
                 // Parse data lines
-                for data_line in &lines[i..] {
+                for data_line in &lines[i + 1..] {
                     if data_line.trim().is_empty() || data_line.starts_with("Legend") {
                         break;
                     }
                     if data_line.trim().starts_with("GPU") {
-                        topo.parse_topo_line(data_line);
+                        topo.parse_topo_line(data_line, gpu_columns);
                     }
                 }
                 break;
@@ -386,31 +1562,137 @@ impl GpuTopology {
         topo
     }
 
-    fn parse_topo_line(&mut self, line: &str) {
+    fn parse_topo_line(&mut self, line: &str, gpu_columns: usize) {
         let parts: Vec<&str> = line.split_whitespace().collect();
         if parts.is_empty() || !parts[0].starts_with("GPU") {
             return;
         }
 
-        let mut row = Vec::new();
-        for (i, part) in parts.iter().enumerate().skip(1) {
-            if let Some(link) = GpuLink::from_str(part) {
-                row.push(Some(link));
-            } else if i <= self.gpu_count + 1 {
-                row.push(None);
-            } else {
-                // CPU/NUMA affinity columns
-                if self.cpu_affinity.len() < self.matrix.len() + 1 {
-                    self.cpu_affinity.push(part.to_string());
-                } else if self.numa_affinity.len() < self.matrix.len() + 1 {
-                    self.numa_affinity.push(part.to_string());
-                }
-            }
-        }
+        let mut tokens = parts.iter().skip(1);
+        let row: Vec<Option<GpuLink>> = tokens
+            .by_ref()
+            .take(gpu_columns)
+            .map(|part| GpuLink::from_str(part))
+            .collect();
+
+        // Any NIC link columns come next, then CPU and NUMA affinity.
+        // Push placeholders when missing so indices stay aligned with GPUs.
+        let mut affinity = tokens.copied().filter(|part| GpuLink::from_str(part).is_none());
+        self.cpu_affinity.push(affinity.next().unwrap_or("N/A").to_string());
+        self.numa_affinity.push(affinity.next().unwrap_or("N/A").to_string());
 
         if !row.is_empty() {
             self.gpu_count = self.gpu_count.max(row.len());
             self.matrix.push(row);
         }
     }
+
+    /// Render the GPU interconnect as an undirected Graphviz DOT graph.
+    /// NVLink edges are weighted and thickened by their link count.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("graph gpu_topology {\n    node [shape=box];\n");
+
+        for i in 0..self.matrix.len() {
+            match self.cpu_affinity_for(i as u32) {
+                Some(cpus) => dot.push_str(&format!("    gpu{i} [label=\"GPU{i}\\nCPUs {cpus}\"];\n")),
+                None => dot.push_str(&format!("    gpu{i} [label=\"GPU{i}\"];\n")),
+            }
+        }
+
+        for (i, row) in self.matrix.iter().enumerate() {
+            // The matrix is symmetric, so only emit each pair once
+            for (j, link) in row.iter().enumerate().skip(i + 1) {
+                let link = match link {
+                    Some(GpuLink::Self_) | None => continue,
+                    Some(link) => link,
+                };
+                let attrs = match link {
+                    GpuLink::NVLink(n) => format!("color=magenta, penwidth={}, weight={}", (*n).clamp(1, 8), n),
+                    GpuLink::PIX => "color=green".to_string(),
+                    GpuLink::PXB | GpuLink::PHB => "color=orange".to_string(),
+                    GpuLink::NODE => "color=cyan3".to_string(),
+                    GpuLink::SYS => "color=red, style=dashed".to_string(),
+                    GpuLink::Self_ => unreachable!(),
+                };
+                dot.push_str(&format!(
+                    "    gpu{i} -- gpu{j} [label=\"{}\", {}];\n",
+                    link.description(),
+                    attrs
+                ));
+            }
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// CPU list local to a GPU, or None when topo didn't report one
+    pub fn cpu_affinity_for(&self, gpu_idx: u32) -> Option<&str> {
+        self.cpu_affinity
+            .get(gpu_idx as usize)
+            .map(|s| s.as_str())
+            .filter(|s| !s.is_empty() && *s != "N/A" && *s != "-")
+    }
+}
+
+#[cfg(test)]
+mod topology_tests {
+    use super::*;
+
+    const TOPO_OUTPUT: &str = "\
+\tGPU0\tGPU1\tCPU Affinity\tNUMA Affinity\tGPU NUMA ID
+GPU0\t X \tNV4\t0-15,32-47\t0\t\tN/A
+GPU1\tNV4\t X \t16-31,48-63\t1\t\tN/A
+
+Legend:
+
+  X    = Self
+";
+
+    #[test]
+    fn test_parse_topology_skips_header() {
+        let topo = GpuTopology::parse(TOPO_OUTPUT);
+        assert_eq!(topo.matrix.len(), 2);
+        assert_eq!(topo.matrix[0][1], Some(GpuLink::NVLink(4)));
+        assert_eq!(topo.cpu_affinity, vec!["0-15,32-47", "16-31,48-63"]);
+        assert_eq!(topo.numa_affinity, vec!["0", "1"]);
+    }
+
+    #[test]
+    fn test_to_dot() {
+        let dot = GpuTopology::parse(TOPO_OUTPUT).to_dot();
+        assert!(dot.starts_with("graph gpu_topology {"));
+        assert!(dot.contains("gpu0 [label=\"GPU0\\nCPUs 0-15,32-47\"];"));
+        assert!(dot.contains("gpu0 -- gpu1 [label=\"NVLink x4\", color=magenta, penwidth=4, weight=4];"));
+        // Each pair appears once and self-links are skipped
+        assert!(!dot.contains("gpu1 -- gpu0"));
+        assert!(!dot.contains("gpu0 -- gpu0"));
+    }
+
+    #[test]
+    fn test_parse_nvlink_counts() {
+        for (token, n) in [("NV1", 1), ("NV6", 6), ("NV12", 12), ("NV18", 18)] {
+            assert_eq!(GpuLink::from_str(token), Some(GpuLink::NVLink(n)));
+        }
+        for token in ["NV", "NV0", "NVx", "NV+4", "NV1A"] {
+            assert_eq!(GpuLink::from_str(token), None, "{}", token);
+        }
+        assert_eq!(GpuLink::NVLink(12).description(), "NVLink x12");
+
+        let tiers: Vec<_> = [1, 2, 4, 6, 12, 18].map(|n| GpuLink::NVLink(n).nvlink_tier().unwrap()).to_vec();
+        assert_eq!(tiers, vec![1, 1, 2, 2, 3, 3]);
+        assert_eq!(GpuLink::PIX.nvlink_tier(), None);
+
+        let topo = GpuTopology::parse(&TOPO_OUTPUT.replace("NV4", "NV12"));
+        assert_eq!(topo.matrix[1][0], Some(GpuLink::NVLink(12)));
+    }
+
+    #[test]
+    fn test_cpu_affinity_missing() {
+        let mut topo = GpuTopology::parse(TOPO_OUTPUT);
+        assert_eq!(topo.cpu_affinity_for(1), Some("16-31,48-63"));
+        assert_eq!(topo.cpu_affinity_for(5), None);
+        topo.cpu_affinity[0] = "N/A".into();
+        assert_eq!(topo.cpu_affinity_for(0), None);
+    }
 }
@@ -1,37 +1,204 @@
 use anyhow::{Context, Result};
 use tokio::io::{AsyncBufReadExt, BufReader};
 use tokio::process::{Child, Command};
-use tokio::sync::mpsc;
-use std::process::Stdio;
-use std::collections::HashSet;
+use tokio::sync::{mpsc, Semaphore};
+use std::path::PathBuf;
+use std::process::{Output, Stdio};
+use std::sync::Arc;
+use std::time::Duration;
+use std::collections::{HashMap, HashSet};
 
-use crate::parser::{GpuSample, ProcessSample, GpuInfo, GpuTopology, ComputeApp, ProcessSystemInfo};
+use crate::config::{Config, QueryFormat};
+use crate::parser::{GpuSample, ProcessSample, PmonColumns, GpuInfo, GpuTopology, ComputeApp, EncoderSession, ProcessSystemInfo, XidEvent, parse_bar1_usage, parse_cgroup_listing, parse_cuda_version};
 
 /// Message types from nvidia-smi processes
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum NvidiaMessage {
     GpuSample(GpuSample),
     ProcessSample(ProcessSample),
     GpuInfo(Vec<GpuInfo>),
     ComputeApps(Vec<ComputeApp>),
     ProcessSystemInfo(Vec<ProcessSystemInfo>),
+    EncoderSessions(Vec<EncoderSession>),
+    /// All Xid reports currently in the kernel log, with `--xid-dmesg`
+    XidEvents(Vec<XidEvent>),
     Error(String),
     Exited(String),
 }
 
+/// Where nvidia-smi runs: locally, or on a remote host over ssh
+#[derive(Debug, Clone)]
+pub struct Target {
+    host: Option<String>,
+    // Shared by clones, so all one-shot nvidia-smi queries against this
+    // host take turns
+    query_lock: Arc<Semaphore>,
+    /// With `--verbose`, the command line of everything run on this target
+    trace: Option<mpsc::UnboundedSender<String>>,
+}
+
+impl Target {
+    pub fn local() -> Self {
+        Self { host: None, query_lock: Arc::new(Semaphore::new(1)), trace: None }
+    }
+
+    pub fn remote(host: &str) -> Self {
+        Self { host: Some(host.to_string()), query_lock: Arc::new(Semaphore::new(1)), trace: None }
+    }
+
+    /// Send the command line of every command run through this target (and
+    /// its clones) to `tx`, as it would be typed in a shell
+    pub fn traced(mut self, tx: mpsc::UnboundedSender<String>) -> Self {
+        self.trace = Some(tx);
+        self
+    }
+
+    /// Display name for section titles
+    pub fn name(&self) -> &str {
+        self.host.as_deref().unwrap_or("local")
+    }
+
+    /// Build a command for `program` and `args`, wrapped in ssh for remote
+    /// hosts. Stdin is closed so children (ssh especially) can't read the
+    /// TUI's keystrokes
+    pub fn command(&self, program: &str, args: &[&str]) -> Command {
+        let mut cmd = match &self.host {
+            None => {
+                let mut cmd = Command::new(program);
+                cmd.args(args);
+                cmd
+            }
+            Some(host) => {
+                // ssh joins its arguments into one line for the remote shell,
+                // so quote each word to keep it a single argument there
+                let remote: Vec<String> = std::iter::once(program).chain(args.iter().copied()).map(shell_quote).collect();
+                let mut cmd = Command::new("ssh");
+                // BatchMode fails fast instead of prompting over the TUI
+                cmd.args(["-o", "BatchMode=yes", host, &remote.join(" ")]);
+                cmd
+            }
+        };
+        cmd.stdin(Stdio::null());
+        cmd
+    }
+
+    /// Run a one-shot nvidia-smi query to completion, one at a time per
+    /// host: some drivers fail intermittently when queries overlap. The
+    /// long-running dmon/pmon children don't go through this
+    pub async fn query(&self, cmd: &mut Command) -> std::io::Result<Output> {
+        // Never closed, so acquiring can't fail
+        let _permit = self.query_lock.acquire().await;
+        self.run(cmd).await
+    }
+
+    /// Run a command to completion without waiting for other queries
+    pub async fn run(&self, cmd: &mut Command) -> std::io::Result<Output> {
+        self.trace(cmd);
+        cmd.output().await
+    }
+
+    /// Start a long-running command such as dmon or pmon
+    pub fn spawn(&self, cmd: &mut Command) -> std::io::Result<Child> {
+        self.trace(cmd);
+        cmd.spawn()
+    }
+
+    fn trace(&self, cmd: &Command) {
+        if let Some(tx) = &self.trace {
+            let _ = tx.send(command_line(cmd));
+        }
+    }
+}
+
+/// A command as a shell line, e.g. `ssh -o BatchMode=yes gpu1 'nvidia-smi topo -m'`
+pub fn command_line(cmd: &Command) -> String {
+    let cmd = cmd.as_std();
+    std::iter::once(cmd.get_program())
+        .chain(cmd.get_args())
+        .map(|word| shell_quote(&word.to_string_lossy()))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// `word` as the shell would read it back, single-quoted if it would
+/// otherwise be split or expanded
+fn shell_quote(word: &str) -> String {
+    let plain = !word.is_empty()
+        && word.chars().all(|c| c.is_ascii_alphanumeric() || "-_./=,:@%+".contains(c));
+    if plain {
+        word.to_string()
+    } else {
+        format!("'{}'", word.replace('\'', "'\\''"))
+    }
+}
+
+/// How the nvidia-smi children are started
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MonitorOptions {
+    /// Skip dmon/pmon and poll `--query-gpu` instead
+    pub poll_only: bool,
+    /// Run dmon with `-o T` so samples carry the device time
+    pub dmon_timestamps: bool,
+    /// Format of the periodic GPU details query
+    pub query_format: QueryFormat,
+    /// Read Xid errors from the kernel log with dmesg
+    pub xid_dmesg: bool,
+    /// Skip the periodic GPU details, compute-apps, ps and encoder queries
+    pub no_query: bool,
+}
+
+impl MonitorOptions {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            poll_only: config.poll_only,
+            dmon_timestamps: config.dmon_timestamps,
+            query_format: config.query_format,
+            xid_dmesg: config.xid_dmesg,
+            no_query: config.no_query,
+        }
+    }
+}
+
+/// Where monitor messages come from
+#[derive(Debug, Clone)]
+pub enum Source {
+    /// nvidia-smi and ps running on a target
+    Live(Target),
+    /// nvidia-smi output recorded to a file, for `--replay`
+    Replay(PathBuf),
+}
+
 /// Manages all nvidia-smi processes
 pub struct NvidiaMonitor {
+    /// None when polling `--query-gpu` instead of streaming dmon
     #[allow(dead_code)]
-    dmon_child: Child,
+    dmon_child: Option<Child>,
     #[allow(dead_code)]
-    pmon_child: Child,
+    pmon_child: Option<Child>,
 }
 
+/// How often poll-only mode queries utilization, matching dmon's default rate
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Messages buffered between the reader tasks and the UI. Steady state is
+/// about one dmon line per GPU plus one pmon line per process each second
+/// (~100/s on a busy 8-GPU box), drained every frame. The headroom absorbs
+/// dmon's buffered startup burst and a few seconds of a stalled UI, so the
+/// readers don't block on `send` and let pipe output back up.
+const CHANNEL_CAPACITY: usize = 4096;
+
+/// How often BAR1 usage is read. It takes a second, heavier `nvidia-smi -q`
+/// and changes little, so the details query in between reuses the last one
+const BAR1_INTERVAL: Duration = Duration::from_secs(30);
+
+/// How often `--xid-dmesg` reads the kernel log, starting once the first
+/// GPU details (with the PCI addresses Xid reports are matched by) are in
+const XID_INTERVAL: Duration = Duration::from_secs(10);
+const XID_FIRST_DELAY: Duration = Duration::from_secs(3);
+
 impl NvidiaMonitor {
-    pub async fn query_topology() -> Result<GpuTopology> {
-        let output = Command::new("nvidia-smi")
-            .args(["topo", "-m"])
-            .output()
+    pub async fn query_topology(target: &Target) -> Result<GpuTopology> {
+        let output = target.query(&mut target.command("nvidia-smi", &["topo", "-m"]))
             .await
             .context("Failed to run nvidia-smi topo")?;
 
@@ -39,13 +206,31 @@ impl NvidiaMonitor {
         Ok(GpuTopology::parse(&stdout))
     }
 
-    pub async fn query_gpu_info() -> Result<Vec<GpuInfo>> {
-        let output = Command::new("nvidia-smi")
-            .args([
-                "--query-gpu=name,uuid,driver_version,memory.total,memory.used,memory.free,power.limit,power.draw,temperature.gpu,temperature.gpu.tlimit,pcie.link.gen.current,pcie.link.gen.max,pcie.link.width.current,pcie.link.width.max,fan.speed,pstate",
+    /// CUDA version supported by the driver. Older nvidia-smi builds don't
+    /// print it with `--version`, so fall back to the default banner
+    pub async fn query_cuda_version(target: &Target) -> Result<Option<String>> {
+        for args in [&["--version"][..], &[]] {
+            let output = target.query(&mut target.command("nvidia-smi", args))
+                .await
+                .context("Failed to run nvidia-smi")?;
+            if let Some(version) = parse_cuda_version(&String::from_utf8_lossy(&output.stdout)) {
+                return Ok(Some(version));
+            }
+        }
+        Ok(None)
+    }
+
+    pub async fn query_gpu_info(target: &Target, format: QueryFormat) -> Result<Vec<GpuInfo>> {
+        if format == QueryFormat::Xml {
+            return Self::query_gpu_info_xml(target).await;
+        }
+
+        let output = target.query(&mut target.command(
+            "nvidia-smi",
+            &[
+                "--query-gpu=name,uuid,driver_version,memory.total,memory.used,memory.free,power.limit,power.draw,temperature.gpu,temperature.gpu.tlimit,pcie.link.gen.current,pcie.link.gen.max,pcie.link.width.current,pcie.link.width.max,fan.speed,pstate,clocks.sm,clocks.mem,clocks.max.sm,clocks.max.mem,clocks_throttle_reasons.active,power.min_limit,power.max_limit,retired_pages.single_bit_ecc.count,retired_pages.double_bit.count,retired_pages.pending,pci.bus_id",
                 "--format=csv,noheader,nounits"
-            ])
-            .output()
+            ]))
             .await
             .context("Failed to run nvidia-smi query-gpu")?;
 
@@ -61,28 +246,118 @@ impl NvidiaMonitor {
         Ok(gpus)
     }
 
+    /// BAR1 (total, used) per PCI bus id. BAR1 isn't a --query-gpu field, so
+    /// this is a separate `nvidia-smi -q -d MEMORY`
+    async fn query_bar1_usage(target: &Target) -> Result<HashMap<String, (Option<u64>, Option<u64>)>> {
+        let output = target.query(&mut target.command("nvidia-smi", &["-q", "-d", "MEMORY"]))
+            .await
+            .context("Failed to run nvidia-smi -q -d MEMORY")?;
+
+        Ok(parse_bar1_usage(&String::from_utf8_lossy(&output.stdout)))
+    }
+
+    /// GPU details from `nvidia-smi -q -x`, BAR1 included
+    async fn query_gpu_info_xml(target: &Target) -> Result<Vec<GpuInfo>> {
+        let output = target.query(&mut target.command("nvidia-smi", &["-q", "-x"]))
+            .await
+            .context("Failed to run nvidia-smi -q -x")?;
+
+        Ok(GpuInfo::parse_xml(&String::from_utf8_lossy(&output.stdout)))
+    }
+
+    /// Set a GPU's power limit with `nvidia-smi -i <idx> -pl <watts>`;
+    /// needs root (or a driver set up to allow it)
+    pub async fn set_power_limit(target: &Target, gpu_index: u32, watts: f32) -> Result<()> {
+        let output = target.query(&mut target.command("nvidia-smi", &["-i", &gpu_index.to_string(), "-pl", &watts.to_string()]))
+            .await
+            .context("Failed to run nvidia-smi -pl")?;
+        if output.status.success() {
+            return Ok(());
+        }
+
+        let text = format!("{}{}", String::from_utf8_lossy(&output.stdout), String::from_utf8_lossy(&output.stderr));
+        // Exit code 4 is nvidia-smi's "no permission"
+        if output.status.code() == Some(4) || text.contains("Insufficient Permissions") {
+            anyhow::bail!("permission denied; setting the power limit needs root");
+        }
+        let reason = text.lines().map(str::trim).find(|l| !l.is_empty()).unwrap_or("nvidia-smi -pl failed");
+        anyhow::bail!("{}", reason)
+    }
+
     /// Query per-process VRAM usage
-    pub async fn query_compute_apps() -> Result<Vec<ComputeApp>> {
-        let output = Command::new("nvidia-smi")
-            .args([
+    pub async fn query_compute_apps(target: &Target) -> Result<Vec<ComputeApp>> {
+        let output = target.query(&mut target.command(
+            "nvidia-smi",
+            &[
                 "--query-compute-apps=pid,process_name,gpu_uuid,used_memory",
                 "--format=csv"
-            ])
-            .output()
+            ]))
             .await
             .context("Failed to run nvidia-smi query-compute-apps")?;
 
         let stdout = String::from_utf8_lossy(&output.stdout);
         let apps: Vec<ComputeApp> = stdout
             .lines()
-            .filter_map(|line| ComputeApp::parse_csv_line(line))
+            .filter_map(ComputeApp::parse_csv_line)
             .collect();
 
         Ok(apps)
     }
 
+    /// Query NVENC sessions. Fails on GPUs and drivers without encoder
+    /// session accounting, which the caller treats as unavailable
+    pub async fn query_encoder_sessions(target: &Target) -> Result<Vec<EncoderSession>> {
+        let output = target.query(&mut target.command("nvidia-smi", &["encodersessions"]))
+            .await
+            .context("Failed to run nvidia-smi encodersessions")?;
+        if !output.status.success() {
+            anyhow::bail!("nvidia-smi encodersessions is not supported");
+        }
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        Ok(stdout.lines().filter_map(EncoderSession::parse_line).collect())
+    }
+
+    /// Xid errors in the kernel log. Fails where reading it needs more
+    /// permissions (`kernel.dmesg_restrict`)
+    pub async fn query_xid_events(target: &Target) -> Result<Vec<XidEvent>> {
+        let output = target.run(&mut target.command("dmesg", &[]))
+            .await
+            .context("Failed to run dmesg")?;
+        if !output.status.success() {
+            anyhow::bail!(
+                "Can't read Xid errors from the kernel log: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        Ok(stdout.lines().filter_map(XidEvent::parse_line).collect())
+    }
+
+    /// Feed Xid reports from the kernel log until the receiver closes. Stops
+    /// after reporting the first failure, which won't go away by retrying
+    async fn poll_xid_events(target: Target, tx: mpsc::Sender<NvidiaMessage>) {
+        let start = tokio::time::Instant::now() + XID_FIRST_DELAY;
+        let mut interval = tokio::time::interval_at(start, XID_INTERVAL);
+        loop {
+            interval.tick().await;
+
+            let msg = match Self::query_xid_events(&target).await {
+                Ok(events) => NvidiaMessage::XidEvents(events),
+                Err(e) => {
+                    let _ = tx.send(NvidiaMessage::Error(e.to_string())).await;
+                    return;
+                }
+            };
+            if tx.send(msg).await.is_err() {
+                return;
+            }
+        }
+    }
+
     /// Query system info for given PIDs via ps
-    pub async fn query_process_info(pids: &[u32]) -> Result<Vec<ProcessSystemInfo>> {
+    pub async fn query_process_info(target: &Target, pids: &[u32]) -> Result<Vec<ProcessSystemInfo>> {
         if pids.is_empty() {
             return Ok(Vec::new());
         }
@@ -92,60 +367,244 @@ impl NvidiaMonitor {
             .collect::<Vec<_>>()
             .join(",");
 
-        let output = Command::new("ps")
-            .args(["-p", &pid_str, "-o", "pid,pcpu,rss,etime", "--no-headers"])
-            .output()
+        let output = target.run(&mut target.command("ps", &["-p", &pid_str, "-o", "pid,user:32,pcpu,rss,etime,args", "--no-headers"]))
             .await
             .context("Failed to run ps")?;
 
         let stdout = String::from_utf8_lossy(&output.stdout);
-        let infos: Vec<ProcessSystemInfo> = stdout
+        let mut infos: Vec<ProcessSystemInfo> = stdout
             .lines()
-            .filter_map(|line| ProcessSystemInfo::parse_ps_line(line))
+            .filter_map(ProcessSystemInfo::parse_ps_line)
             .collect();
 
+        let containers = Self::query_containers(target, pids).await;
+        for info in &mut infos {
+            info.container = containers.get(&info.pid).cloned();
+        }
+
         Ok(infos)
     }
 
-    pub async fn spawn() -> Result<(Self, mpsc::Receiver<NvidiaMessage>)> {
+    /// Container ids for PIDs running in containers, from their cgroups.
+    /// Best effort: an unreadable /proc (non-Linux, hidepid) just means no ids
+    async fn query_containers(target: &Target, pids: &[u32]) -> HashMap<u32, String> {
+        match target.run(&mut Self::cgroup_command(target, pids)).await {
+            Ok(output) => parse_cgroup_listing(&String::from_utf8_lossy(&output.stdout)),
+            Err(_) => HashMap::new(),
+        }
+    }
+
+    /// grep -H prefixes each line with its file, so one call covers all PIDs.
+    /// `^` rather than an empty pattern, which ssh would drop
+    fn cgroup_command(target: &Target, pids: &[u32]) -> Command {
+        let files: Vec<String> = pids.iter().map(|p| format!("/proc/{}/cgroup", p)).collect();
+        let args: Vec<&str> = ["-H", "-e", "^"].into_iter().chain(files.iter().map(String::as_str)).collect();
+        target.command("grep", &args)
+    }
+
+    /// Query per-GPU utilization as dmon-style samples, for poll-only mode
+    pub async fn query_gpu_samples(target: &Target) -> Result<Vec<GpuSample>> {
+        let output = target.query(&mut target.command(
+            "nvidia-smi",
+            &[
+                "--query-gpu=index,power.draw,temperature.gpu,utilization.gpu,utilization.memory,clocks.mem,clocks.sm,temperature.memory",
+                "--format=csv,noheader,nounits"
+            ]))
+            .await
+            .context("Failed to run nvidia-smi query-gpu")?;
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let samples: Vec<GpuSample> = stdout
+            .lines()
+            .filter_map(GpuSample::from_query)
+            .collect();
+
+        Ok(samples)
+    }
+
+    /// Feed samples from periodic `--query-gpu` calls until the receiver closes
+    async fn poll_gpu_samples(target: Target, tx: mpsc::Sender<NvidiaMessage>) {
+        let mut interval = tokio::time::interval(POLL_INTERVAL);
+        loop {
+            interval.tick().await;
+
+            match Self::query_gpu_samples(&target).await {
+                Ok(samples) => {
+                    for sample in samples {
+                        if tx.send(NvidiaMessage::GpuSample(sample)).await.is_err() {
+                            return;
+                        }
+                    }
+                }
+                Err(e) => {
+                    if tx.send(NvidiaMessage::Error(e.to_string())).await.is_err() {
+                        return;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Start monitoring `source`. A replay feeds the recording through the
+    /// same channel, so everything downstream behaves as with a live GPU.
+    pub async fn spawn(source: Source, options: MonitorOptions) -> Result<(Self, mpsc::Receiver<NvidiaMessage>)> {
+        match source {
+            Source::Live(target) => Self::spawn_live(target, options).await,
+            Source::Replay(path) => {
+                let (tx, rx) = mpsc::channel(CHANNEL_CAPACITY);
+                crate::replay::spawn(&path, tx)?;
+                Ok((Self { dmon_child: None, pmon_child: None }, rx))
+            }
+        }
+    }
+
+    /// Start monitoring `target`. With `poll_only`, dmon and pmon are never
+    /// started and GPU samples come from `--query-gpu` instead; the same
+    /// fallback kicks in when dmon can't be spawned or exits before its first sample.
+    async fn spawn_live(target: Target, options: MonitorOptions) -> Result<(Self, mpsc::Receiver<NvidiaMessage>)> {
         // Check if nvidia-smi is available
-        let check = Command::new("nvidia-smi")
-            .arg("--version")
-            .output()
-            .await;
+        let check = target.run(&mut target.command("nvidia-smi", &["--version"])).await;
+
+        // Older drivers may not support --version, so locally only a failed
+        // spawn counts; over ssh a non-zero exit means the host is unreachable
+        let available = match check {
+            Ok(out) => target.host.is_none() || out.status.success(),
+            Err(_) => false,
+        };
 
-        if check.is_err() {
+        if !available {
             anyhow::bail!(
-                "nvidia-smi not found. Please ensure NVIDIA drivers are installed and nvidia-smi is in your PATH."
+                "nvidia-smi not found on {}. Please ensure NVIDIA drivers are installed and nvidia-smi is in your PATH.",
+                target.name()
             );
         }
 
-        let (tx, rx) = mpsc::channel(200);
+        let (tx, rx) = mpsc::channel(CHANNEL_CAPACITY);
+
+        let dmon_child = if options.poll_only {
+            tokio::spawn(Self::poll_gpu_samples(target.clone(), tx.clone()));
+            None
+        } else {
+            Self::spawn_dmon(&target, &tx, options.dmon_timestamps)
+        };
+
+        let pmon_child = if options.poll_only {
+            None
+        } else {
+            Some(Self::spawn_pmon(&target, &tx)?)
+        };
+
+        if options.xid_dmesg {
+            tokio::spawn(Self::poll_xid_events(target.clone(), tx.clone()));
+        }
+
+        // Only dmon and pmon with --no-query
+        if options.no_query {
+            return Ok((Self { dmon_child, pmon_child }, rx));
+        }
 
-        // Spawn dmon
-        let mut dmon_child = Command::new("nvidia-smi")
-            .arg("dmon")
-            .stdout(Stdio::piped())
-            .stderr(Stdio::null())
-            .kill_on_drop(true)
-            .spawn()
-            .context("Failed to spawn nvidia-smi dmon")?;
+        // Spawn periodic query-gpu task
+        let tx_query = tx.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(Duration::from_secs(2));
+            let mut bar1 = HashMap::new();
+            let mut bar1_read: Option<tokio::time::Instant> = None;
+            loop {
+                interval.tick().await;
+
+                // -q -x already has BAR1; for csv it's read every BAR1_INTERVAL,
+                // best effort, so a failure just leaves it unreported
+                if options.query_format == QueryFormat::Csv && bar1_read.is_none_or(|at| at.elapsed() >= BAR1_INTERVAL) {
+                    bar1 = Self::query_bar1_usage(&target).await.unwrap_or_default();
+                    bar1_read = Some(tokio::time::Instant::now());
+                }
+
+                // Query GPU info
+                if let Ok(mut info) = Self::query_gpu_info(&target, options.query_format).await {
+                    for gpu in info.iter_mut() {
+                        if let Some(&(total, used)) = bar1.get(&gpu.pci_bus_id) {
+                            gpu.bar1_total_mib = total;
+                            gpu.bar1_used_mib = used;
+                        }
+                    }
+                    if tx_query.send(NvidiaMessage::GpuInfo(info)).await.is_err() {
+                        break;
+                    }
+                }
+
+                // Query compute apps (VRAM per process)
+                if let Ok(apps) = Self::query_compute_apps(&target).await {
+                    // Collect unique PIDs
+                    let pids: Vec<u32> = apps.iter()
+                        .map(|a| a.pid)
+                        .collect::<HashSet<_>>()
+                        .into_iter()
+                        .collect();
+
+                    // Send compute apps
+                    if tx_query.send(NvidiaMessage::ComputeApps(apps)).await.is_err() {
+                        break;
+                    }
+
+                    // Query system info for these PIDs
+                    if let Ok(sys_info) = Self::query_process_info(&target, &pids).await {
+                        if tx_query.send(NvidiaMessage::ProcessSystemInfo(sys_info)).await.is_err() {
+                            break;
+                        }
+                    }
+                }
 
-        let dmon_stdout = dmon_child.stdout.take().context("Failed to get dmon stdout")?;
+                // NVENC sessions per process, where the GPU reports them
+                if let Ok(sessions) = Self::query_encoder_sessions(&target).await {
+                    if tx_query.send(NvidiaMessage::EncoderSessions(sessions)).await.is_err() {
+                        break;
+                    }
+                }
+            }
+        });
+
+        Ok((Self { dmon_child, pmon_child }, rx))
+    }
+
+    /// Spawn dmon, falling back to polling if it can't start or produces nothing
+    fn spawn_dmon(target: &Target, tx: &mpsc::Sender<NvidiaMessage>, timestamps: bool) -> Option<Child> {
+        let fallback = "dmon unavailable, polling --query-gpu instead";
+
+        let args: &[&str] = if timestamps { &["dmon", "-o", "T"] } else { &["dmon"] };
+        let mut cmd = target.command("nvidia-smi", args);
+        let mut dmon_child = match target.spawn(cmd.stdout(Stdio::piped()).stderr(Stdio::null()).kill_on_drop(true)) {
+            Ok(child) => child,
+            Err(_) => {
+                let _ = tx.try_send(NvidiaMessage::Error(fallback.into()));
+                tokio::spawn(Self::poll_gpu_samples(target.clone(), tx.clone()));
+                return None;
+            }
+        };
+
+        let dmon_stdout = dmon_child.stdout.take()?;
         let tx_dmon = tx.clone();
+        let target = target.clone();
         tokio::spawn(async move {
             let reader = BufReader::new(dmon_stdout);
             let mut lines = reader.lines();
+            let mut got_sample = false;
 
             loop {
                 match lines.next_line().await {
                     Ok(Some(line)) => {
                         if let Some(sample) = GpuSample::parse_line(&line) {
+                            got_sample = true;
                             if tx_dmon.send(NvidiaMessage::GpuSample(sample)).await.is_err() {
                                 break;
                             }
                         }
                     }
+                    // Refused outright (e.g. a locked-down environment): switch to polling
+                    Ok(None) if !got_sample => {
+                        let _ = tx_dmon.send(NvidiaMessage::Error(fallback.into())).await;
+                        Self::poll_gpu_samples(target, tx_dmon).await;
+                        break;
+                    }
                     Ok(None) => {
                         let _ = tx_dmon.send(NvidiaMessage::Exited("dmon".into())).await;
                         break;
@@ -158,13 +617,12 @@ impl NvidiaMonitor {
             }
         });
 
-        // Spawn pmon
-        let mut pmon_child = Command::new("nvidia-smi")
-            .arg("pmon")
-            .stdout(Stdio::piped())
-            .stderr(Stdio::null())
-            .kill_on_drop(true)
-            .spawn()
+        Some(dmon_child)
+    }
+
+    fn spawn_pmon(target: &Target, tx: &mpsc::Sender<NvidiaMessage>) -> Result<Child> {
+        let mut pmon_child = target
+            .spawn(target.command("nvidia-smi", &["pmon"]).stdout(Stdio::piped()).stderr(Stdio::null()).kill_on_drop(true))
             .context("Failed to spawn nvidia-smi pmon")?;
 
         let pmon_stdout = pmon_child.stdout.take().context("Failed to get pmon stdout")?;
@@ -172,11 +630,15 @@ impl NvidiaMonitor {
         tokio::spawn(async move {
             let reader = BufReader::new(pmon_stdout);
             let mut lines = reader.lines();
+            let mut columns = PmonColumns::default();
 
             loop {
                 match lines.next_line().await {
                     Ok(Some(line)) => {
-                        if let Some(sample) = ProcessSample::parse_line(&line) {
+                        // pmon repeats its header periodically; follow whatever layout it reports
+                        if let Some(header) = PmonColumns::from_header(&line) {
+                            columns = header;
+                        } else if let Some(sample) = ProcessSample::parse_line_with(&line, &columns) {
                             if tx_pmon.send(NvidiaMessage::ProcessSample(sample)).await.is_err() {
                                 break;
                             }
@@ -194,44 +656,40 @@ impl NvidiaMonitor {
             }
         });
 
-        // Spawn periodic query-gpu task
-        let tx_query = tx.clone();
-        tokio::spawn(async move {
-            let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(2));
-            loop {
-                interval.tick().await;
-
-                // Query GPU info
-                if let Ok(info) = Self::query_gpu_info().await {
-                    if tx_query.send(NvidiaMessage::GpuInfo(info)).await.is_err() {
-                        break;
-                    }
-                }
+        Ok(pmon_child)
+    }
+}
 
-                // Query compute apps (VRAM per process)
-                if let Ok(apps) = Self::query_compute_apps().await {
-                    // Collect unique PIDs
-                    let pids: Vec<u32> = apps.iter()
-                        .map(|a| a.pid)
-                        .collect::<HashSet<_>>()
-                        .into_iter()
-                        .collect();
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-                    // Send compute apps
-                    if tx_query.send(NvidiaMessage::ComputeApps(apps)).await.is_err() {
-                        break;
-                    }
+    #[test]
+    fn test_command_line_quotes_for_the_shell() {
+        let cmd = Target::local().command("grep", &["-H", "", "it's"]);
+        assert_eq!(command_line(&cmd), "grep -H '' 'it'\\''s'");
+    }
 
-                    // Query system info for these PIDs
-                    if let Ok(sys_info) = Self::query_process_info(&pids).await {
-                        if tx_query.send(NvidiaMessage::ProcessSystemInfo(sys_info)).await.is_err() {
-                            break;
-                        }
-                    }
-                }
-            }
-        });
+    #[test]
+    fn test_cgroup_command_over_ssh() {
+        let cmd = NvidiaMonitor::cgroup_command(&Target::remote("gpu1"), &[4242, 77]);
+        assert_eq!(
+            command_line(&cmd),
+            "ssh -o BatchMode=yes gpu1 'grep -H -e '\\''^'\\'' /proc/4242/cgroup /proc/77/cgroup'"
+        );
+    }
 
-        Ok((Self { dmon_child, pmon_child }, rx))
+    #[test]
+    fn test_remote_command_is_one_quoted_word() {
+        let cmd = Target::remote("gpu1").command("grep", &["-H", "", "it's"]);
+        let args: Vec<_> = cmd.as_std().get_args().map(|a| a.to_string_lossy().into_owned()).collect();
+        assert_eq!(args, ["-o", "BatchMode=yes", "gpu1", "grep -H '' 'it'\\''s'"]);
+
+        let cmd = Target::remote("gpu1")
+            .command("nvidia-smi", &["--query-gpu=index,power.draw", "--format=csv,noheader,nounits"]);
+        assert_eq!(
+            command_line(&cmd),
+            "ssh -o BatchMode=yes gpu1 'nvidia-smi --query-gpu=index,power.draw --format=csv,noheader,nounits'"
+        );
     }
 }
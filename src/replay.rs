@@ -0,0 +1,189 @@
+//! Recorded nvidia-smi output for `--replay`, so the TUI can run without a GPU
+//!
+//! Each line is a source tag followed by that source's raw output line:
+//!
+//! ```text
+//! dmon    0     62     40      -     12     30      0      0      -      -   3615   1531
+//! pmon # gpu         pid   type     sm    mem    enc    dec    jpg    ofa    command
+//! pmon    0       4242     C     50     14      -      -      -      -    python
+//! gpu NVIDIA A100, GPU-aaaa, 550.54.15, 81920, 1024, 80896, 400.00, 62.31, 34, 87, 4, 4, 16, 16, [N/A], P0
+//! app 4242, /usr/bin/python, GPU-aaaa, 500 MiB
+//! ps 4242 alice 98.5 1048576 01:02:03 python train.py
+//! tick
+//! ```
+//!
+//! `tick` ends one second of output. `gpu`, `app` and `ps` lines since the
+//! previous tick are sent as one batch, like a single query. Lines starting
+//! with `#` are comments. Older recordings have no user column in `ps`
+//! lines; those processes are shown with an unknown owner.
+
+use std::path::Path;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use tokio::sync::mpsc;
+
+use crate::data::UNKNOWN_USER;
+use crate::parser::{ComputeApp, GpuInfo, GpuSample, PmonColumns, ProcessSample, ProcessSystemInfo};
+use crate::process::NvidiaMessage;
+
+/// Time between two `tick`s, matching dmon's default rate
+const TICK: Duration = Duration::from_secs(1);
+
+/// A `ps` line, with or without the user column. Etime (always `mm:ss` or
+/// longer) is the fourth field only in lines recorded before it was added
+fn parse_ps(line: &str) -> Option<ProcessSystemInfo> {
+    let legacy = line.split_whitespace().nth(3).is_some_and(|f| f.contains(':'));
+    if legacy {
+        let (pid, rest) = line.trim().split_once(char::is_whitespace)?;
+        ProcessSystemInfo::parse_ps_line(&format!("{} {} {}", pid, UNKNOWN_USER, rest))
+    } else {
+        ProcessSystemInfo::parse_ps_line(line)
+    }
+}
+
+/// Parse a recording into one batch of messages per tick
+pub fn parse_recording(text: &str) -> Vec<Vec<NvidiaMessage>> {
+    let mut ticks = Vec::new();
+    let mut messages = Vec::new();
+    let mut gpus = Vec::new();
+    let mut apps = Vec::new();
+    let mut procs = Vec::new();
+    let mut columns = PmonColumns::default();
+
+    for line in text.lines() {
+        if line.starts_with('#') || line.trim().is_empty() {
+            continue;
+        }
+        let (tag, rest) = line.split_once(' ').unwrap_or((line, ""));
+        match tag {
+            "dmon" => messages.extend(GpuSample::parse_line(rest).map(NvidiaMessage::GpuSample)),
+            "pmon" => {
+                if let Some(header) = PmonColumns::from_header(rest) {
+                    columns = header;
+                } else if let Some(sample) = ProcessSample::parse_line_with(rest, &columns) {
+                    messages.push(NvidiaMessage::ProcessSample(sample));
+                }
+            }
+            "gpu" => gpus.extend(GpuInfo::parse_csv_line(rest, gpus.len() as u32)),
+            "app" => apps.extend(ComputeApp::parse_csv_line(rest)),
+            "ps" => procs.extend(parse_ps(rest)),
+            "tick" => {
+                flush_batches(&mut messages, &mut gpus, &mut apps, &mut procs);
+                ticks.push(std::mem::take(&mut messages));
+            }
+            _ => {}
+        }
+    }
+
+    flush_batches(&mut messages, &mut gpus, &mut apps, &mut procs);
+    if !messages.is_empty() {
+        ticks.push(messages);
+    }
+    ticks
+}
+
+fn flush_batches(
+    messages: &mut Vec<NvidiaMessage>,
+    gpus: &mut Vec<GpuInfo>,
+    apps: &mut Vec<ComputeApp>,
+    procs: &mut Vec<ProcessSystemInfo>,
+) {
+    if !gpus.is_empty() {
+        messages.push(NvidiaMessage::GpuInfo(std::mem::take(gpus)));
+    }
+    if !apps.is_empty() {
+        messages.push(NvidiaMessage::ComputeApps(std::mem::take(apps)));
+    }
+    if !procs.is_empty() {
+        messages.push(NvidiaMessage::ProcessSystemInfo(std::mem::take(procs)));
+    }
+}
+
+/// Read `path` and feed it to `tx` one tick per second, starting over at
+/// the end so a short recording can run as a demo
+pub fn spawn(path: &Path, tx: mpsc::Sender<NvidiaMessage>) -> Result<()> {
+    let text = std::fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let ticks = parse_recording(&text);
+    if ticks.is_empty() {
+        anyhow::bail!("{} has no recorded nvidia-smi output", path.display());
+    }
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(TICK);
+        for tick in ticks.iter().cycle() {
+            interval.tick().await;
+            for msg in tick {
+                if tx.send(msg.clone()).await.is_err() {
+                    return;
+                }
+            }
+        }
+    });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::DataStore;
+
+    const RECORDING: &str = "\
+# two seconds on a single GPU
+dmon # gpu    pwr  gtemp  mtemp     sm    mem    enc    dec    jpg    ofa   mclk   pclk
+dmon     0     62     40      -     12     30      0      0      -      -   3615   1531
+pmon # gpu         pid   type     sm    mem    enc    dec    jpg    ofa    command
+pmon     0       4242     C     50     14      -      -      -      -    python
+gpu NVIDIA A100, GPU-aaaa, 550.54.15, 81920, 1024, 80896, 400.00, 62.31, 34, 87, 4, 4, 16, 16, [N/A], P0
+app 4242, /usr/bin/python, GPU-aaaa, 500 MiB
+ps 4242 alice 98.5 1048576 01:02:03 python train.py
+tick
+dmon     0     70     41      -     99     30      0      0      -      -   3615   1531
+tick
+";
+
+    #[test]
+    fn test_replay_drives_data_store() {
+        let ticks = parse_recording(RECORDING);
+        assert_eq!(ticks.len(), 2);
+        assert_eq!(ticks[1].len(), 1);
+
+        let mut data = DataStore::new(60, Duration::from_secs(5));
+        for msg in ticks.into_iter().flatten() {
+            match msg {
+                NvidiaMessage::GpuSample(s) => data.add_sample(s),
+                NvidiaMessage::ProcessSample(s) => data.add_process_sample(s),
+                NvidiaMessage::GpuInfo(info) => data.update_gpu_info(info),
+                NvidiaMessage::ComputeApps(apps) => {
+                    data.update_compute_apps(apps);
+                }
+                NvidiaMessage::ProcessSystemInfo(infos) => data.update_process_sys_info(infos),
+                other => panic!("unexpected message {:?}", other),
+            }
+        }
+
+        assert_eq!(data.total_samples(), 2);
+        assert_eq!(data.get_gpu(0).unwrap().latest().unwrap().power_w, Some(70));
+        assert_eq!(data.get_gpu_info(0).unwrap().memory_used_mib, 1024);
+
+        let procs = data.get_enriched_processes(false);
+        assert_eq!(procs.len(), 1);
+        assert_eq!(procs[0].pid, 4242);
+        assert_eq!(procs[0].vram_mib, Some(500));
+        assert_eq!(procs[0].sm_util, Some(50));
+        assert_eq!(procs[0].args, "python train.py");
+    }
+
+    #[test]
+    fn test_ps_lines_without_user_column() {
+        let old = parse_ps("4242 98.5 1048576 01:02:03 python train.py").unwrap();
+        assert_eq!(old.user, UNKNOWN_USER);
+        assert_eq!(old.rss_kb, 1048576);
+        assert_eq!(old.args, "python train.py");
+
+        // a numeric user (uid without a passwd entry) is still the new layout
+        let new = parse_ps("4242 1001 98.5 1048576 01:02:03 python").unwrap();
+        assert_eq!(new.user, "1001");
+        assert_eq!(new.elapsed, "01:02:03");
+    }
+}
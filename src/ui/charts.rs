@@ -1,31 +1,205 @@
+use std::time::Instant;
+
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     symbols,
     text::Span,
-    widgets::{Axis, Block, Borders, Chart, Dataset, GraphType},
+    widgets::{Axis, Block, Chart, Dataset, GraphType},
     Frame,
 };
 
-use crate::data::DataStore;
+use crate::data::{DataStore, GpuHistory};
+use crate::parser::GpuSample;
+use crate::ui::panel;
+
+/// Number of chart slots in the charts view
+pub const CHART_SLOTS: usize = 3;
+
+/// One color per GPU when all GPUs share a chart; more GPUs than colors
+/// aren't drawn, since the lines would be unreadable anyway
+const OVERLAY_COLORS: [Color; 8] = [
+    Color::Green,
+    Color::Yellow,
+    Color::Cyan,
+    Color::Magenta,
+    Color::Red,
+    Color::LightBlue,
+    Color::White,
+    Color::LightGreen,
+];
+
+/// Below this many rows the slots are too cramped to read, so only the
+/// focused slot is drawn
+const COMPACT_HEIGHT: u16 = 20;
+
+/// Metric plotted in a chart slot
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChartMetric {
+    Power,
+    Temperature,
+    /// Memory (HBM) temperature from dmon's mtemp
+    MemTemperature,
+    Utilization,
+    Sm,
+    Mem,
+    Encoder,
+    Decoder,
+    /// Encoder and decoder together, for transcoding hosts
+    EncDec,
+    Clocks,
+}
+
+impl ChartMetric {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Power => "Power (W)",
+            Self::Temperature => "Temperature (°C)",
+            Self::MemTemperature => "Memory Temp (°C)",
+            Self::Utilization => "Utilization (%)",
+            Self::Sm => "SM Utilization (%)",
+            Self::Mem => "Memory Bandwidth (%)",
+            Self::Encoder => "Encoder (%)",
+            Self::Decoder => "Decoder (%)",
+            Self::EncDec => "Encoder/Decoder (%)",
+            Self::Clocks => "Clocks (MHz)",
+        }
+    }
 
-pub fn render_chart_view(frame: &mut Frame, area: Rect, data: &DataStore, selected_gpu: usize) {
+    pub fn all() -> &'static [ChartMetric] {
+        &[
+            ChartMetric::Power,
+            ChartMetric::Temperature,
+            ChartMetric::MemTemperature,
+            ChartMetric::Utilization,
+            ChartMetric::Sm,
+            ChartMetric::Mem,
+            ChartMetric::Encoder,
+            ChartMetric::Decoder,
+            ChartMetric::EncDec,
+            ChartMetric::Clocks,
+        ]
+    }
+
+    /// Series plotted per GPU when all GPUs share a chart (the first of a
+    /// pair, e.g. SM for Utilization) and its y-axis top; None scales to
+    /// the data
+    fn primary(&self) -> (Extractor, Option<f64>) {
+        match self {
+            Self::Power => (|s| s.power_w, Some(400.0)),
+            Self::Temperature => (|s| s.gpu_temp_c, Some(100.0)),
+            Self::MemTemperature => (|s| s.mem_temp_c, Some(110.0)),
+            Self::Utilization | Self::Sm => (|s| s.sm_util, Some(100.0)),
+            Self::Mem => (|s| s.mem_util, Some(100.0)),
+            Self::Encoder | Self::EncDec => (|s| s.enc_util, Some(100.0)),
+            Self::Decoder => (|s| s.dec_util, Some(100.0)),
+            Self::Clocks => (|s| s.gpu_clock_mhz, None),
+        }
+    }
+
+    /// Default metrics for each slot, matching the original fixed layout
+    pub fn defaults() -> [ChartMetric; CHART_SLOTS] {
+        [ChartMetric::Power, ChartMetric::Temperature, ChartMetric::Utilization]
+    }
+
+    pub fn next(&self) -> Self {
+        let all = Self::all();
+        let pos = all.iter().position(|m| m == self).unwrap_or(0);
+        all[(pos + 1) % all.len()]
+    }
+
+    pub fn prev(&self) -> Self {
+        let all = Self::all();
+        let pos = all.iter().position(|m| m == self).unwrap_or(0);
+        all[(pos + all.len() - 1) % all.len()]
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn render_chart_view(
+    frame: &mut Frame,
+    area: Rect,
+    data: &DataStore,
+    selected_gpu: usize,
+    // A second GPU (J/K) whose charts are tiled beside the selected one's
+    compare_gpu: Option<usize>,
+    metrics: &[ChartMetric; CHART_SLOTS],
+    focused_slot: usize,
+    accent: Color,
+    // Seconds ago the ←/→ cursor points at, if shown
+    cursor: Option<f64>,
+    // Plot the focused slot's metric for every GPU on one chart
+    all_gpus: bool,
+    // Right edge of the time axis: the last sample with --sync-charts, else now
+    now: Instant,
+) {
     let gpu_indices = data.gpu_indices();
 
     if gpu_indices.is_empty() {
-        let block = Block::default()
-            .borders(Borders::ALL)
+        let block = panel()
             .title(" Charts - No Data ")
             .title_style(Style::default().fg(Color::Yellow));
         frame.render_widget(block, area);
         return;
     }
 
+    if all_gpus {
+        render_overlay_chart(frame, area, data, metrics[focused_slot], accent, cursor, now);
+        return;
+    }
+
     let gpu_idx = gpu_indices.get(selected_gpu).copied().unwrap_or(0);
     let history = match data.get_gpu(gpu_idx) {
         Some(h) => h,
         None => return,
     };
+    let compare = compare_gpu
+        .and_then(|pos| gpu_indices.get(pos).copied())
+        .filter(|&idx| idx != gpu_idx)
+        .and_then(|idx| Some((idx, data.get_gpu(idx)?)));
+
+    // Calculate x-axis bounds; compared GPUs share them so the curves line up
+    let x_min = std::iter::once(history)
+        .chain(compare.map(|(_, h)| h))
+        .map(|h| -h.time_span(now))
+        .fold(-60.0, f64::min);
+
+    let Some((compare_idx, compare_history)) = compare else {
+        render_gpu_charts(frame, area, data, gpu_idx, history, metrics, focused_slot, accent, cursor, now, x_min);
+        return;
+    };
+    let halves = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(area);
+    render_gpu_charts(frame, halves[0], data, gpu_idx, history, metrics, focused_slot, accent, cursor, now, x_min);
+    render_gpu_charts(frame, halves[1], data, compare_idx, compare_history, metrics, focused_slot, accent, cursor, now, x_min);
+}
+
+/// The chart slots for one GPU, stacked
+#[allow(clippy::too_many_arguments)]
+fn render_gpu_charts(
+    frame: &mut Frame,
+    area: Rect,
+    data: &DataStore,
+    gpu_idx: u32,
+    history: &GpuHistory,
+    metrics: &[ChartMetric; CHART_SLOTS],
+    focused_slot: usize,
+    accent: Color,
+    cursor: Option<f64>,
+    now: Instant,
+    x_min: f64,
+) {
+    let peaks = data.peaks(gpu_idx);
+    let x_max = 0.0;
+
+    // Short terminal: one full-height chart for the focused slot; c and m/M
+    // still switch slot and metric
+    if area.height < COMPACT_HEIGHT {
+        render_metric_chart(frame, area, history, peaks, gpu_idx, metrics[focused_slot], Some(accent), cursor, now, x_min, x_max);
+        return;
+    }
 
     // Split into 3 chart areas
     let chunks = Layout::default()
@@ -37,86 +211,306 @@ pub fn render_chart_view(frame: &mut Frame, area: Rect, data: &DataStore, select
         ])
         .split(area);
 
-    // Get chart data
-    let power_data: Vec<(f64, f64)> = history.chart_data(|s| s.power_w);
-    let temp_data: Vec<(f64, f64)> = history.chart_data(|s| s.gpu_temp_c);
-    let sm_data: Vec<(f64, f64)> = history.chart_data(|s| s.sm_util);
-    let mem_data: Vec<(f64, f64)> = history.chart_data(|s| s.mem_util);
-
-    // Calculate x-axis bounds
-    let x_min = power_data
-        .first()
-        .map(|(x, _)| *x)
-        .unwrap_or(-60.0)
-        .min(-60.0);
-    let x_max = 0.0;
+    for (slot, metric) in metrics.iter().enumerate() {
+        render_metric_chart(
+            frame,
+            chunks[slot],
+            history,
+            peaks,
+            gpu_idx,
+            *metric,
+            (slot == focused_slot).then_some(accent),
+            cursor,
+            now,
+            x_min,
+            x_max,
+        );
+    }
+}
 
-    // Power chart
-    render_single_chart(
-        frame,
-        chunks[0],
-        &format!(" GPU {} - Power (W) ", gpu_idx),
-        &power_data,
-        x_min,
-        x_max,
-        0.0,
-        400.0, // Max TDP for high-end GPUs
-        Color::Yellow,
-    );
+/// Pulls one series out of a dmon sample
+type Extractor = fn(&GpuSample) -> Option<u32>;
+
+/// One metric for every GPU on a single chart, a color per GPU, to compare
+/// load balance across the node. The legend shows values at the cursor
+fn render_overlay_chart(
+    frame: &mut Frame,
+    area: Rect,
+    data: &DataStore,
+    metric: ChartMetric,
+    accent: Color,
+    cursor: Option<f64>,
+    now: Instant,
+) {
+    let gpu_indices = data.gpu_indices();
+    let shown: Vec<(u32, &GpuHistory)> = gpu_indices
+        .iter()
+        .filter_map(|&idx| data.get_gpu(idx).map(|h| (idx, h)))
+        .take(OVERLAY_COLORS.len())
+        .collect();
+
+    let (extractor, y_max) = metric.primary();
+    let max_points = chart_points(area);
+    let series: Vec<Vec<(f64, f64)>> = shown.iter().map(|(_, h)| h.chart_data(max_points, now, extractor)).collect();
+    let y_max = y_max.unwrap_or_else(|| {
+        // Round the peak up to the next 500 MHz so the lines aren't pinned to the top
+        let peak = series.iter().flatten().map(|(_, y)| *y).fold(0.0, f64::max);
+        ((peak / 500.0).floor() + 1.0) * 500.0
+    });
+    let x_min = shown.iter().map(|(_, h)| -h.time_span(now)).fold(-60.0, f64::min);
+
+    let cursor_line = cursor_points(cursor, y_max);
+    let mut datasets = vec![cursor_dataset(&cursor_line)];
+    for (((idx, history), points), color) in shown.iter().zip(&series).zip(OVERLAY_COLORS) {
+        let name = match cursor.and_then(|c| history.value_at(now, c, extractor)) {
+            Some((_, value)) => format!("GPU {}: {}", idx, value),
+            None => format!("GPU {}", idx),
+        };
+        datasets.push(
+            Dataset::default()
+                .name(name)
+                .marker(symbols::Marker::Braille)
+                .graph_type(GraphType::Line)
+                .style(Style::default().fg(color))
+                .data(points),
+        );
+    }
+
+    let mut title = format!(" All GPUs - {}", metric.name());
+    if gpu_indices.len() > shown.len() {
+        title.push_str(&format!(" (first {} of {})", shown.len(), gpu_indices.len()));
+    }
+    if let Some(c) = cursor {
+        title.push_str(&format!(" at -{:.0}s", c));
+    }
+    title.push(' ');
+
+    let chart = Chart::new(datasets)
+        .block(chart_block(&title, Color::White, Some(accent)))
+        // Always show the legend; it's the only way to tell the GPUs apart
+        .hidden_legend_constraints((Constraint::Ratio(1, 1), Constraint::Ratio(1, 1)))
+        .x_axis(
+            Axis::default()
+                .style(Style::default().fg(Color::Gray))
+                .bounds([x_min, 0.0])
+                .labels(vec![
+                    Span::from(format!("{:.0}s", x_min)),
+                    Span::from("now"),
+                ]),
+        )
+        .y_axis(
+            Axis::default()
+                .style(Style::default().fg(Color::Gray))
+                .bounds([0.0, y_max])
+                .labels(vec![
+                    Span::from("0"),
+                    Span::from(format!("{:.0}", y_max)),
+                ]),
+        );
+
+    frame.render_widget(chart, area);
+}
+
+#[allow(clippy::too_many_arguments)]
+fn render_metric_chart(
+    frame: &mut Frame,
+    area: Rect,
+    history: &GpuHistory,
+    // High-water marks since the last reset, drawn as a line and in the title
+    peaks: Option<&GpuSample>,
+    gpu_idx: u32,
+    metric: ChartMetric,
+    // Border color when this is the slot selected for metric changes
+    focus: Option<Color>,
+    cursor: Option<f64>,
+    now: Instant,
+    x_min: f64,
+    x_max: f64,
+) {
+    // Get chart data, sized to the current terminal width
+    let max_points = chart_points(area);
+    let base_title = format!(" GPU {} - {}", gpu_idx, metric.name());
+    let peak_of = |extractor: Extractor| peaks.and_then(extractor);
+    // Exact reading nearest the cursor, and the time it was taken
+    let at_cursor = |extractor: Extractor| cursor.and_then(|c| history.value_at(now, c, extractor));
+    // With the cursor shown, titles read out the values under it instead of
+    // the peaks; dual charts only mark their peaks in the title
+    let dual_title = |e1: Extractor, e2: Extractor, label1: &str, label2: &str| {
+        if cursor.is_some() {
+            let value = |e: Extractor| at_cursor(e).map(|(_, v)| v.to_string()).unwrap_or("-".into());
+            let ago = at_cursor(e1).or_else(|| at_cursor(e2)).map(|(ago, _)| ago).unwrap_or_default();
+            return format!("{} - {} {} {} {} at -{:.0}s ", base_title, label1, value(e1), label2, value(e2), ago);
+        }
+        let peak = |e: Extractor| peak_of(e).map(|v| v.to_string()).unwrap_or("-".into());
+        format!("{} - peak {} {} {} {} ", base_title, label1, peak(e1), label2, peak(e2))
+    };
+
+    let single = |extractor: Extractor, y_max: f64, color: Color| {
+        let data = history.chart_data(max_points, now, extractor);
+        (data, y_max, color, peak_of(extractor), at_cursor(extractor))
+    };
+
+    let (data, y_max, color, peak, reading) = match metric {
+        ChartMetric::Power => single(|s| s.power_w, 400.0, Color::Yellow), // Max TDP for high-end GPUs
+        ChartMetric::Temperature => single(|s| s.gpu_temp_c, 100.0, Color::Red),
+        ChartMetric::MemTemperature => single(|s| s.mem_temp_c, 110.0, Color::LightRed),
+        ChartMetric::Sm => single(|s| s.sm_util, 100.0, Color::Green),
+        ChartMetric::Mem => single(|s| s.mem_util, 100.0, Color::Cyan),
+        ChartMetric::Encoder => single(|s| s.enc_util, 100.0, Color::Magenta),
+        ChartMetric::Decoder => single(|s| s.dec_util, 100.0, Color::LightBlue),
+        ChartMetric::Utilization => {
+            let sm_data = history.chart_data(max_points, now, |s| s.sm_util);
+            let mem_data = history.chart_data(max_points, now, |s| s.mem_util);
+            let title = dual_title(|s| s.sm_util, |s| s.mem_util, "SM", "Mem");
+            render_dual_chart(
+                frame,
+                area,
+                chart_block(&title, Color::White, focus),
+                &sm_data,
+                &mem_data,
+                cursor,
+                x_min,
+                x_max,
+                100.0,
+                "SM",
+                "Mem",
+                Color::Green,
+                Color::Cyan,
+            );
+            return;
+        }
+        ChartMetric::EncDec => {
+            let enc_data = history.chart_data(max_points, now, |s| s.enc_util);
+            let dec_data = history.chart_data(max_points, now, |s| s.dec_util);
+            let title = dual_title(|s| s.enc_util, |s| s.dec_util, "Enc", "Dec");
+            render_dual_chart(
+                frame,
+                area,
+                chart_block(&title, Color::White, focus),
+                &enc_data,
+                &dec_data,
+                cursor,
+                x_min,
+                x_max,
+                100.0,
+                "Enc",
+                "Dec",
+                Color::Magenta,
+                Color::LightBlue,
+            );
+            return;
+        }
+        ChartMetric::Clocks => {
+            let gpu_data = history.chart_data(max_points, now, |s| s.gpu_clock_mhz);
+            let mem_data = history.chart_data(max_points, now, |s| s.mem_clock_mhz);
+            // Round the peak up to the next 500 MHz so the line isn't pinned to the top
+            let peak = gpu_data.iter().chain(&mem_data).map(|(_, y)| *y).fold(0.0, f64::max);
+            let y_max = ((peak / 500.0).floor() + 1.0) * 500.0;
+            let title = dual_title(|s| s.gpu_clock_mhz, |s| s.mem_clock_mhz, "PCLK", "MCLK");
+            render_dual_chart(
+                frame,
+                area,
+                chart_block(&title, Color::White, focus),
+                &gpu_data,
+                &mem_data,
+                cursor,
+                x_min,
+                x_max,
+                y_max,
+                "PCLK",
+                "MCLK",
+                Color::Yellow,
+                Color::Cyan,
+            );
+            return;
+        }
+    };
 
-    // Temperature chart
+    let title = match (cursor, reading, peak) {
+        (Some(_), Some((ago, value)), _) => format!("{} - {} at -{:.0}s ", base_title, value, ago),
+        (Some(_), None, _) => format!("{} - no reading ", base_title),
+        (None, _, Some(peak)) => format!("{} - peak {} ", base_title, peak),
+        (None, _, None) => format!("{} ", base_title),
+    };
+    // Flat line across the chart at the peak
+    let peak_line: Vec<(f64, f64)> = peak.map(|p| vec![(x_min, p as f64), (x_max, p as f64)]).unwrap_or_default();
     render_single_chart(
         frame,
-        chunks[1],
-        &format!(" GPU {} - Temperature (°C) ", gpu_idx),
-        &temp_data,
+        area,
+        chart_block(&title, color, focus),
+        &data,
+        &peak_line,
+        cursor,
         x_min,
         x_max,
         0.0,
-        100.0,
-        Color::Red,
+        y_max,
+        color,
     );
+}
 
-    // Utilization chart (SM and Memory)
-    render_dual_chart(
-        frame,
-        chunks[2],
-        &format!(" GPU {} - Utilization (%) ", gpu_idx),
-        &sm_data,
-        &mem_data,
-        x_min,
-        x_max,
-        "SM",
-        "Mem",
-        Color::Green,
-        Color::Cyan,
-    );
+/// Ends of the ←/→ cursor's vertical line; none when it's hidden
+fn cursor_points(cursor: Option<f64>, y_max: f64) -> Vec<(f64, f64)> {
+    cursor.map(|c| vec![(-c, 0.0), (-c, y_max)]).unwrap_or_default()
 }
 
+/// Vertical line for the ←/→ cursor
+fn cursor_dataset(line: &[(f64, f64)]) -> Dataset<'_> {
+    Dataset::default()
+        .marker(symbols::Marker::Braille)
+        .graph_type(GraphType::Line)
+        .style(Style::default().fg(Color::White))
+        .data(line)
+}
+
+/// Number of points a braille chart drawn in `area` can resolve horizontally
+fn chart_points(area: Rect) -> usize {
+    // Borders take a column on each side; braille packs two dots per cell
+    area.width.saturating_sub(2) as usize * 2
+}
+
+/// Chart border; the slot selected for metric changes gets a highlighted border
+fn chart_block(title: &str, color: Color, focus: Option<Color>) -> Block<'static> {
+    let border_style = match focus {
+        Some(accent) => Style::default().fg(accent),
+        None => Style::default(),
+    };
+
+    panel()
+        .border_style(border_style)
+        .title(title.to_string())
+        .title_style(Style::default().fg(color).add_modifier(Modifier::BOLD))
+}
+
+#[allow(clippy::too_many_arguments)]
 fn render_single_chart(
     frame: &mut Frame,
     area: Rect,
-    title: &str,
+    block: Block,
     data: &[(f64, f64)],
+    peak_line: &[(f64, f64)],
+    cursor: Option<f64>,
     x_min: f64,
     x_max: f64,
     y_min: f64,
     y_max: f64,
     color: Color,
 ) {
+    // Drawn first so the live series stays on top where they meet
+    let peak = Dataset::default()
+        .marker(symbols::Marker::Braille)
+        .graph_type(GraphType::Line)
+        .style(Style::default().fg(Color::DarkGray))
+        .data(peak_line);
     let dataset = Dataset::default()
         .marker(symbols::Marker::Braille)
         .graph_type(GraphType::Line)
         .style(Style::default().fg(color))
         .data(data);
 
-    let chart = Chart::new(vec![dataset])
-        .block(
-            Block::default()
-                .borders(Borders::ALL)
-                .title(title)
-                .title_style(Style::default().fg(color).add_modifier(Modifier::BOLD)),
-        )
+    let cursor_line = cursor_points(cursor, y_max);
+    let chart = Chart::new(vec![peak, cursor_dataset(&cursor_line), dataset])
+        .block(block)
         .x_axis(
             Axis::default()
                 .style(Style::default().fg(Color::Gray))
@@ -139,20 +533,25 @@ fn render_single_chart(
     frame.render_widget(chart, area);
 }
 
+#[allow(clippy::too_many_arguments)]
 fn render_dual_chart(
     frame: &mut Frame,
     area: Rect,
-    title: &str,
+    block: Block,
     data1: &[(f64, f64)],
     data2: &[(f64, f64)],
+    cursor: Option<f64>,
     x_min: f64,
     x_max: f64,
+    y_max: f64,
     label1: &str,
     label2: &str,
     color1: Color,
     color2: Color,
 ) {
+    let cursor_line = cursor_points(cursor, y_max);
     let datasets = vec![
+        cursor_dataset(&cursor_line),
         Dataset::default()
             .name(label1)
             .marker(symbols::Marker::Braille)
@@ -168,12 +567,7 @@ fn render_dual_chart(
     ];
 
     let chart = Chart::new(datasets)
-        .block(
-            Block::default()
-                .borders(Borders::ALL)
-                .title(title)
-                .title_style(Style::default().fg(Color::White).add_modifier(Modifier::BOLD)),
-        )
+        .block(block)
         .x_axis(
             Axis::default()
                 .style(Style::default().fg(Color::Gray))
@@ -186,10 +580,10 @@ fn render_dual_chart(
         .y_axis(
             Axis::default()
                 .style(Style::default().fg(Color::Gray))
-                .bounds([0.0, 100.0])
+                .bounds([0.0, y_max])
                 .labels(vec![
                     Span::from("0"),
-                    Span::from("100"),
+                    Span::from(format!("{:.0}", y_max)),
                 ]),
         );
 
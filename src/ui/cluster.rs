@@ -0,0 +1,76 @@
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::Paragraph,
+    Frame,
+};
+
+use crate::config::Config;
+use crate::data::DataStore;
+use crate::ui::dashboard::render_gpu_table;
+use crate::ui::format_power;
+
+/// Stack one GPU table per host under an aggregate header.
+/// The first host is the primary one that the other views and selection follow.
+pub fn render_cluster_view(
+    frame: &mut Frame,
+    area: Rect,
+    config: &Config,
+    hosts: &[(&str, &DataStore)],
+    selected_gpu: usize,
+) {
+    let constraints: Vec<Constraint> = std::iter::once(Constraint::Length(1))
+        .chain(hosts.iter().map(|(_, data)| {
+            // Borders + header + one row per GPU
+            Constraint::Min(data.gpu_indices().len().max(1) as u16 + 3)
+        }))
+        .collect();
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(constraints)
+        .split(area);
+
+    render_cluster_header(frame, chunks[0], hosts, config.power_precision);
+
+    for (i, (host, data)) in hosts.iter().enumerate() {
+        let selected = (i == 0).then_some(selected_gpu);
+        let title = format!(" {} ({} GPUs) ", host, data.gpu_indices().len());
+        render_gpu_table(frame, chunks[i + 1], config, data, selected, &title, None);
+    }
+}
+
+fn render_cluster_header(frame: &mut Frame, area: Rect, hosts: &[(&str, &DataStore)], precision: usize) {
+    let mut gpu_count = 0;
+    let mut total_power = 0u32;
+    let mut max_temp: Option<u32> = None;
+
+    for (_, data) in hosts {
+        for idx in data.gpu_indices() {
+            gpu_count += 1;
+            if let Some(sample) = data.get_gpu(idx).and_then(|h| h.latest()) {
+                total_power += sample.power_w.unwrap_or(0);
+                max_temp = max_temp.max(sample.gpu_temp_c);
+            }
+        }
+    }
+
+    let header = Paragraph::new(Line::from(vec![
+        Span::styled("Hosts: ", Style::default().fg(Color::Gray)),
+        Span::styled(format!("{}", hosts.len()), Style::default().fg(Color::White).add_modifier(Modifier::BOLD)),
+        Span::raw(" | "),
+        Span::styled("GPUs: ", Style::default().fg(Color::Gray)),
+        Span::styled(format!("{}", gpu_count), Style::default().fg(Color::White)),
+        Span::raw(" | "),
+        Span::styled("Power: ", Style::default().fg(Color::Gray)),
+        Span::styled(format_power(total_power as f64, precision), Style::default().fg(Color::White)),
+        Span::raw(" | "),
+        Span::styled("Max temp: ", Style::default().fg(Color::Gray)),
+        Span::styled(
+            max_temp.map(|t| format!("{}°C", t)).unwrap_or("-".into()),
+            Style::default().fg(Color::White),
+        ),
+    ]));
+    frame.render_widget(header, area);
+}
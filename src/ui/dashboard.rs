@@ -1,24 +1,78 @@
+use std::collections::HashMap;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, Cell, Gauge, Paragraph, Row, Table},
+    widgets::{Cell, Gauge, Paragraph, Row, Table, TableState},
     Frame,
 };
 
-use crate::data::DataStore;
+use crate::alert::CRIT_TEMP_C;
+use crate::config::{Config, GaugeStyle, GpuSort, TimeFormat, VramUnit};
+use crate::data::{trend, DataStore, Trend};
+use crate::parser::GpuSample;
+use crate::ui::diff::DiffHighlight;
+use crate::ui::{charset, format_clock, format_power, is_ascii, is_monochrome, panel, truncate_to_width};
 
-// Simple color scheme: green and cyan
-const COLOR_ACCENT: Color = Color::Cyan;
-const COLOR_HEADER: Color = Color::Cyan;
+// Simple color scheme: green bars and cyan values; titles use the accent
 const COLOR_BAR: Color = Color::Green;
 const COLOR_HIGHLIGHT: Color = Color::Cyan;
+/// Half a blink cycle for `--flash-crit` rows
+const FLASH_PERIOD_MS: u128 = 500;
+
+/// Utilization sparkline with each character colored by its own value
+/// (green, yellow from 50%, red from 80%) so spikes stand out
+fn sparkline_spans(values: &[f64], width: usize) -> Vec<Span<'static>> {
+    let values = &values[values.len().saturating_sub(width)..];
+    let chars = &charset().sparkline;
+    let mut spans = vec![Span::raw(" ".repeat(width - values.len()))];
+    spans.extend(values.iter().map(|&v| {
+        Span::styled(chars[sparkline_level(v, 100.0)].to_string(), Style::default().fg(util_color(v)))
+    }));
+    spans
+}
+
+/// Utilization color: green, yellow from 50%, red from 80%
+pub fn util_color(pct: f64) -> Color {
+    if pct >= 80.0 {
+        Color::Red
+    } else if pct >= 50.0 {
+        Color::Yellow
+    } else {
+        Color::Green
+    }
+}
+
+/// VRAM used beyond the listed processes' that's worth pointing out; the
+/// driver alone normally holds a few hundred MiB
+pub const UNACCOUNTED_VRAM_MIB: u64 = 1024;
+
+/// Degrees below the critical temperature where temperatures turn yellow
+const TEMP_WARN_MARGIN_C: u32 = 10;
+
+/// GPU temperature color: red from the critical temperature, yellow
+/// within TEMP_WARN_MARGIN_C of it
+pub fn temp_color(temp_c: u32) -> Color {
+    if temp_c >= CRIT_TEMP_C {
+        Color::Red
+    } else if temp_c + TEMP_WARN_MARGIN_C >= CRIT_TEMP_C {
+        Color::Yellow
+    } else {
+        Color::White
+    }
+}
 
-/// Sparkline characters (8 levels)
-const SPARKLINE_CHARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+/// Index into the sparkline charset for a value in 0..=max
+pub fn sparkline_level(value: f64, max: f64) -> usize {
+    let idx = ((value.clamp(0.0, max) / max) * 7.0).round() as usize;
+    idx.min(7)
+}
 
-fn sparkline(values: &[f64], width: usize) -> String {
-    if values.is_empty() {
+/// Sparkline for values in 0..=max (e.g. watts against the power limit)
+fn sparkline_scaled(values: &[f64], width: usize, max: f64) -> String {
+    if values.is_empty() || max <= 0.0 {
         return " ".repeat(width);
     }
     let values: Vec<f64> = if values.len() > width {
@@ -26,11 +80,10 @@ fn sparkline(values: &[f64], width: usize) -> String {
     } else {
         values.to_vec()
     };
+    let chars = &charset().sparkline;
     let mut result = String::new();
     for &v in &values {
-        let clamped = v.clamp(0.0, 100.0);
-        let idx = ((clamped / 100.0) * 7.0).round() as usize;
-        result.push(SPARKLINE_CHARS[idx.min(7)]);
+        result.push(chars[sparkline_level(v, max)]);
     }
     while result.chars().count() < width {
         result.insert(0, ' ');
@@ -38,6 +91,153 @@ fn sparkline(values: &[f64], width: usize) -> String {
     result
 }
 
+/// Text gauge like `[####····] 62%`, or just `62%` in the percent style
+fn mini_gauge(pct: f64, width: usize, style: GaugeStyle) -> String {
+    match style {
+        GaugeStyle::Percent => format!("{:.0}%", pct),
+        style => format!("[{}] {:.0}%", gauge_bar(pct, width, style), pct),
+    }
+}
+
+/// Bar of `width` cells filled to `pct`. Braille fills half a cell at a
+/// time (`⣿⣿⡇⣀`); without braille glyphs (`--ascii`) it uses the charset's
+fn gauge_bar(pct: f64, width: usize, style: GaugeStyle) -> String {
+    let fraction = pct.clamp(0.0, 100.0) / 100.0;
+    if style == GaugeStyle::Braille && !is_ascii() {
+        let halves = (fraction * width as f64 * 2.0).round() as usize;
+        let (full, half) = (halves / 2, halves % 2);
+        return format!(
+            "{}{}{}",
+            BRAILLE_FULL.to_string().repeat(full),
+            BRAILLE_HALF.to_string().repeat(half),
+            BRAILLE_EMPTY.to_string().repeat(width - full - half)
+        );
+    }
+    let filled = (fraction * width as f64).round() as usize;
+    let chars = charset();
+    format!(
+        "{}{}",
+        chars.gauge_fill.to_string().repeat(filled),
+        chars.gauge_empty.to_string().repeat(width - filled)
+    )
+}
+
+/// Braille cells for a full, left-half and empty gauge position
+const BRAILLE_FULL: char = '⣿';
+const BRAILLE_HALF: char = '⡇';
+const BRAILLE_EMPTY: char = '⣀';
+
+/// Memory gauge for the Memory & Power rows in the configured style. The
+/// block style uses ratatui's Gauge, which fills with block elements and
+/// tells filled from empty by color, so `--ascii` and monochrome get the
+/// text gauge instead
+fn render_memory_gauge(frame: &mut Frame, area: Rect, pct: f64, label: String, style: GaugeStyle) {
+    if style == GaugeStyle::Blocks && !is_ascii() && !is_monochrome() {
+        let gauge = Gauge::default()
+            .gauge_style(Style::default().fg(COLOR_BAR).bg(Color::DarkGray))
+            .percent(pct.clamp(0.0, 100.0) as u16)
+            .label(label);
+        frame.render_widget(gauge, area);
+        return;
+    }
+
+    let text = match style {
+        GaugeStyle::Percent => format!("{:>3.0}%  {}", pct, label),
+        style => {
+            let width = (area.width as usize).saturating_sub(label.chars().count() + 4).max(1);
+            format!("[{}] {}", gauge_bar(pct, width, style), label)
+        }
+    };
+    frame.render_widget(Paragraph::new(text).style(Style::default().fg(COLOR_BAR)), area);
+}
+
+/// "active" for a busy GPU, "idle 3m" once its SM utilization has been at
+/// zero, with a "+" when it hasn't been busy since monitoring started
+fn idle_cell(data: &DataStore, gpu_idx: u32, latest: Option<&GpuSample>) -> Cell<'static> {
+    let Some(idle) = data.idle_for(gpu_idx) else {
+        return match latest.and_then(|s| s.sm_util) {
+            Some(_) => Cell::from("active").style(Style::default().fg(Color::Green)),
+            None => Cell::from("-"),
+        };
+    };
+    let secs = idle.as_secs();
+    let mut text = match secs {
+        0..=59 => format!("idle {}s", secs),
+        60..=3599 => format!("idle {}m", secs / 60),
+        3600..=86399 => format!("idle {}h", secs / 3600),
+        _ => format!("idle {}d", secs / 86400),
+    };
+    if data.idle_since_start(gpu_idx) {
+        text.push('+');
+    }
+    // Long idle GPUs are the ones worth reclaiming
+    let color = if idle >= IDLE_RECLAIM { Color::Yellow } else { Color::DarkGray };
+    Cell::from(text).style(Style::default().fg(color))
+}
+
+/// Idle time from which a GPU's idle cell is highlighted
+const IDLE_RECLAIM: Duration = Duration::from_secs(10 * 60);
+
+/// Dim ▲/▼/– after a reading for rising, falling or steady
+fn trend_span(trend: Trend) -> Span<'static> {
+    let (up, down, steady) = if is_ascii() { ("^", "v", "-") } else { ("▲", "▼", "–") };
+    let arrow = match trend {
+        Trend::Rising => up,
+        Trend::Falling => down,
+        Trend::Steady => steady,
+    };
+    Span::styled(arrow, Style::default().fg(Color::DarkGray))
+}
+
+/// Samples the trend arrows are fitted over
+const TREND_SAMPLES: usize = 10;
+/// Change per sample, as a percentage of the average, below which power
+/// and temperature count as steady. Power is noisier than temperature
+const POWER_TREND_STEADY_PCT: f64 = 1.0;
+const TEMP_TREND_STEADY_PCT: f64 = 0.3;
+
+/// Headroom score badge: green when a GPU can clearly take more work, red when it can't
+fn headroom_cell(score: Option<u32>) -> Cell<'static> {
+    match score {
+        Some(score) => {
+            let color = if score >= 60 {
+                Color::Green
+            } else if score >= 30 {
+                Color::Yellow
+            } else {
+                Color::Red
+            };
+            Cell::from(format!("{:>3}", score))
+                .style(Style::default().fg(Color::Black).bg(color).add_modifier(Modifier::BOLD))
+        }
+        None => Cell::from("-"),
+    }
+}
+
+/// Clock for the GPU table: bare MHz as before, or with units under `--ghz`
+/// since the column then mixes MHz and GHz
+fn clock_cell_text(mhz: Option<u32>, ghz: bool) -> String {
+    match mhz {
+        Some(mhz) if ghz => format_clock(mhz, true),
+        Some(mhz) => mhz.to_string(),
+        None => "-".into(),
+    }
+}
+
+/// Power for the GPU table. dmon only reports whole watts, so fractional
+/// precision uses query-gpu's power draw when it has arrived.
+fn power_cell_text(data: &DataStore, sample: &GpuSample, precision: usize) -> String {
+    let fractional = data
+        .get_gpu_info(sample.gpu_idx)
+        .and_then(|g| g.power_draw_w)
+        .filter(|_| precision > 0);
+    match (fractional, sample.power_w) {
+        (Some(w), _) => format_power(w as f64, precision),
+        (None, Some(w)) => format_power(w as f64, precision),
+        (None, None) => "-".to_string(),
+    }
+}
+
 fn fmt_val(val: Option<u32>, unit: &str) -> String {
     match val {
         Some(v) => format!("{}{}", v, unit),
@@ -45,7 +245,15 @@ fn fmt_val(val: Option<u32>, unit: &str) -> String {
     }
 }
 
-pub fn render_dashboard(frame: &mut Frame, area: Rect, data: &DataStore, selected_gpu: usize) {
+pub fn render_dashboard(
+    frame: &mut Frame,
+    area: Rect,
+    config: &Config,
+    data: &DataStore,
+    selected_gpu: usize,
+    selected_process: Option<usize>,
+    diff: Option<&mut DiffHighlight>,
+) {
     let gpu_indices = data.gpu_indices();
     let gpu_count = gpu_indices.len().max(1);
 
@@ -63,40 +271,83 @@ pub fn render_dashboard(frame: &mut Frame, area: Rect, data: &DataStore, selecte
         .split(area);
 
     // === GPU Metrics Table ===
-    render_gpu_table(frame, chunks[0], data, selected_gpu);
+    render_gpu_table(frame, chunks[0], config, data, Some(selected_gpu), " GPU Metrics ", diff);
 
     // === Memory & Power Section ===
-    render_memory_section(frame, chunks[1], data);
+    render_memory_section(frame, chunks[1], config, data, selected_gpu);
 
     // === Processes Section ===
-    render_processes_section(frame, chunks[2], data);
+    render_processes_section(frame, chunks[2], data, selected_process, config);
 }
 
-fn render_gpu_table(frame: &mut Frame, area: Rect, data: &DataStore, selected_gpu: usize) {
-    let gpu_indices = data.gpu_indices();
+pub fn render_gpu_table(
+    frame: &mut Frame,
+    area: Rect,
+    config: &Config,
+    data: &DataStore,
+    selected_gpu: Option<usize>,
+    title: &str,
+    mut diff: Option<&mut DiffHighlight>,
+) {
+    // `selected_gpu` is a position in index order, so the selection follows
+    // the GPU itself when a re-sort moves its row
+    let selected_idx = selected_gpu.and_then(|pos| data.gpu_indices().get(pos).copied());
+    let gpu_indices = data.sorted_gpu_indices(config.gpu_sort);
+    let selected_row = selected_idx.and_then(|idx| gpu_indices.iter().position(|&i| i == idx));
+
+    let mut headers = vec!["GPU", "Power", "Temp"];
+    if config.power_temp_sparklines {
+        headers.extend(["Pwr~", "Temp~"]);
+    }
+    headers.extend(["SM%", "Mem%", "VRAM", "Proc", "Idle", "Enc", "Dec", "MCLK", "PCLK", "Head"]);
 
-    let header_cells = ["GPU", "Power", "Temp", "SM%", "Mem%", "Enc", "Dec", "MCLK", "PCLK"]
-        .iter()
-        .map(|h| Cell::from(*h).style(Style::default().fg(COLOR_HEADER).add_modifier(Modifier::BOLD)));
+    // Processes per GPU, to spot an oversubscribed card
+    let mut process_counts: HashMap<u32, usize> = HashMap::new();
+    for p in data.get_enriched_processes(false) {
+        *process_counts.entry(p.gpu_idx).or_default() += 1;
+    }
+
+    // Mark the sorted column, except for the default index order
+    let sort_marker = if is_ascii() { "v" } else { "▼" };
+    let header_cells = headers.iter().map(|&h| {
+        let text = if config.gpu_sort != GpuSort::Index && h == config.gpu_sort.column() {
+            format!("{}{}", h, sort_marker)
+        } else {
+            h.to_string()
+        };
+        Cell::from(text).style(Style::default().fg(config.accent.color()).add_modifier(Modifier::BOLD))
+    });
     let header = Row::new(header_cells).height(1).bottom_margin(0);
 
     let rows: Vec<Row> = gpu_indices
         .iter()
-        .enumerate()
-        .map(|(i, &gpu_idx)| {
+        .map(|&gpu_idx| {
             let history = data.get_gpu(gpu_idx);
             let latest = history.and_then(|h| h.latest());
 
+            // Value cells flash when they change if diff highlighting is on;
+            // a trend arrow after the value doesn't count as a change
+            let mut value_cell = |column: &'static str, text: String, style: Style, trend: Option<Span<'static>>| {
+                let style = match diff.as_deref_mut() {
+                    Some(d) => d.style(gpu_idx, column, &text, style),
+                    None => style,
+                };
+                match trend {
+                    Some(arrow) => Cell::from(Line::from(vec![Span::styled(text, style), arrow])),
+                    None => Cell::from(text).style(style),
+                }
+            };
+
             let (power, temp, _sm, _mem, enc, dec, mclk, pclk) = match latest {
                 Some(s) => (
-                    fmt_val(s.power_w, "W"),
+                    power_cell_text(data, s, config.power_precision),
                     fmt_val(s.gpu_temp_c, "°"),
                     fmt_val(s.sm_util, "%"),
                     fmt_val(s.mem_util, "%"),
                     fmt_val(s.enc_util, "%"),
                     fmt_val(s.dec_util, "%"),
-                    fmt_val(s.mem_clock_mhz, ""),
-                    fmt_val(s.gpu_clock_mhz, ""),
+                    clock_cell_text(s.mem_clock_mhz, config.clock_ghz),
+                    clock_cell_text(s.gpu_clock_mhz, config.clock_ghz),
                 ),
                 None => (
                     "-".into(), "-".into(), "-".into(), "-".into(),
@@ -104,79 +355,212 @@ fn render_gpu_table(frame: &mut Frame, area: Rect, data: &DataStore, selected_gp
                 ),
             };
 
+            // Direction over the last few samples, next to the reading
+            let (power_trend, temp_trend) = match (history, latest) {
+                (Some(h), Some(_)) => (
+                    Some(trend_span(trend(&h.recent_values(TREND_SAMPLES, |s| s.power_w), POWER_TREND_STEADY_PCT))),
+                    Some(trend_span(trend(&h.recent_values(TREND_SAMPLES, |s| s.gpu_temp_c), TEMP_TREND_STEADY_PCT))),
+                ),
+                _ => (None, None),
+            };
+
             let sm_spark = history
-                .map(|h| sparkline(&h.recent_values(8, |s| s.sm_util), 8))
-                .unwrap_or_else(|| " ".repeat(8));
+                .map(|h| sparkline_spans(&h.recent_values(8, |s| s.sm_util), 8))
+                .unwrap_or_default();
             let mem_spark = history
-                .map(|h| sparkline(&h.recent_values(8, |s| s.mem_util), 8))
-                .unwrap_or_else(|| " ".repeat(8));
+                .map(|h| sparkline_spans(&h.recent_values(8, |s| s.mem_util), 8))
+                .unwrap_or_default();
 
-            let row_style = if i == selected_gpu {
+            // A GPU reporting errors (e.g. fallen off the bus) is drawn in red
+            let gpu_error = data.get_gpu_info(gpu_idx).and_then(|g| g.error.as_deref());
+            let mut row_style = if selected_idx == Some(gpu_idx) {
                 Style::default().bg(Color::DarkGray)
             } else {
                 Style::default()
             };
+            if gpu_error.is_some() {
+                row_style = row_style.fg(Color::Red);
+            }
+            // Draw against the limit, by the --power-warn/--power-crit thresholds
+            let power_style = match latest {
+                Some(s) => {
+                    let limit = data.get_gpu_info(gpu_idx).and_then(|g| g.power_limit_w);
+                    config.power_color(s.power_w.map(|w| w as f32), limit).map_or(Style::default(), |c| Style::default().fg(c))
+                }
+                None => Style::default(),
+            };
+            let critical = latest.and_then(|s| s.gpu_temp_c).is_some_and(|t| t >= CRIT_TEMP_C);
+            if config.flash_crit && critical && flash_on() {
+                row_style = row_style.bg(Color::Red).fg(Color::White);
+            }
 
-            Row::new(vec![
+            let mut cells = vec![
                 Cell::from(format!("{}", gpu_idx)),
-                Cell::from(power),
-                Cell::from(temp),
-                Cell::from(sm_spark).style(Style::default().fg(Color::Green)),
-                Cell::from(mem_spark).style(Style::default().fg(Color::Cyan)),
-                Cell::from(enc),
-                Cell::from(dec),
-                Cell::from(mclk),
-                Cell::from(pclk),
-            ])
-            .style(row_style)
-            .height(1)
+                value_cell("power", power, power_style, power_trend),
+                value_cell("temp", temp, Style::default(), temp_trend),
+            ];
+
+            if config.power_temp_sparklines {
+                // Power scales to the board limit when known; temperature to 100°C
+                let power_max = data
+                    .get_gpu_info(gpu_idx)
+                    .and_then(|g| g.power_limit_w)
+                    .map(|w| w as f64)
+                    .unwrap_or(400.0);
+                let power_spark = history
+                    .map(|h| sparkline_scaled(&h.recent_values(8, |s| s.power_w), 8, power_max))
+                    .unwrap_or_else(|| " ".repeat(8));
+                let temp_spark = history
+                    .map(|h| sparkline_scaled(&h.recent_values(8, |s| s.gpu_temp_c), 8, 100.0))
+                    .unwrap_or_else(|| " ".repeat(8));
+                cells.push(Cell::from(power_spark).style(Style::default().fg(Color::Yellow)));
+                cells.push(Cell::from(temp_spark).style(Style::default().fg(Color::Red)));
+            }
+
+            // VRAM usage from query-gpu, which may not have arrived yet; an
+            // errored GPU shows the error text here instead
+            let vram_cell = if let Some(err) = gpu_error {
+                Cell::from(err.to_string()).style(Style::default().fg(Color::Red).add_modifier(Modifier::BOLD))
+            } else {
+                match data.get_gpu_info(gpu_idx).filter(|g| g.memory_total_mib > 0) {
+                    Some(g) => {
+                        let pct = g.memory_used_mib as f64 / g.memory_total_mib as f64 * 100.0;
+                        let color = if pct >= 90.0 {
+                            Color::Red
+                        } else if pct >= 70.0 {
+                            Color::Yellow
+                        } else {
+                            Color::Green
+                        };
+                        value_cell("vram", mini_gauge(pct, 8, config.gauge_style), Style::default().fg(color), None)
+                    }
+                    None => Cell::from("-"),
+                }
+            };
+
+            cells.extend([
+                Cell::from(Line::from(sm_spark)),
+                Cell::from(Line::from(mem_spark)),
+                vram_cell,
+                Cell::from(process_counts.get(&gpu_idx).copied().unwrap_or(0).to_string()),
+                idle_cell(data, gpu_idx, latest),
+                value_cell("enc", enc, Style::default(), None),
+                value_cell("dec", dec, Style::default(), None),
+                value_cell("mclk", mclk, Style::default(), None),
+                value_cell("pclk", pclk, Style::default(), None),
+                headroom_cell(data.get_gpu_info(gpu_idx).and_then(|g| g.headroom_score())),
+            ]);
+
+            Row::new(cells).style(row_style).height(1)
         })
         .collect();
 
-    let widths = [
+    let table = Table::new(rows, gpu_table_widths(config))
+        .header(header)
+        .block(
+            panel()
+                .title(title.to_string())
+                .title_style(Style::default().fg(config.accent.color()).add_modifier(Modifier::BOLD)),
+        );
+
+    // Selecting a row scrolls the table so GPUs past the bottom stay reachable
+    let mut state = TableState::default().with_selected(selected_row);
+    frame.render_stateful_widget(table, area, &mut state);
+}
+
+/// Blink phase for `--flash-crit`, from the wall clock rather than a frame
+/// counter so the blink rate doesn't depend on how often the screen redraws
+fn flash_on() -> bool {
+    let millis = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_millis()).unwrap_or(0);
+    (millis / FLASH_PERIOD_MS).is_multiple_of(2)
+}
+
+/// Column widths of the GPU table for the enabled columns
+fn gpu_table_widths(config: &Config) -> Vec<Constraint> {
+    // Room for the decimal point and digits when power precision is raised
+    let power_width = match config.power_precision {
+        0 => 5,
+        p => 6 + p as u16,
+    };
+    let clock_width = if config.clock_ghz { 8 } else { 5 };
+    // One extra column for the sort marker on "Power" and "Temp"
+    let mut widths = vec![
         Constraint::Length(4),
+        Constraint::Length(power_width + 1),
         Constraint::Length(5),
-        Constraint::Length(4),
+    ];
+    if config.power_temp_sparklines {
+        widths.extend([Constraint::Length(9), Constraint::Length(9)]);
+    }
+    widths.extend([
         Constraint::Length(9),
         Constraint::Length(9),
+        Constraint::Length(15),
         Constraint::Length(4),
+        Constraint::Length(9),
         Constraint::Length(4),
-        Constraint::Length(5),
-        Constraint::Length(5),
-    ];
-
-    let table = Table::new(rows, widths)
-        .header(header)
-        .block(
-            Block::default()
-                .borders(Borders::ALL)
-                .title(" GPU Metrics ")
-                .title_style(Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)),
-        );
+        Constraint::Length(4),
+        Constraint::Length(clock_width),
+        Constraint::Length(clock_width),
+        Constraint::Length(4),
+    ]);
+    widths
+}
 
-    frame.render_widget(table, area);
+/// Columns the GPU table needs to show every column, borders included
+pub fn gpu_table_width(config: &Config) -> u16 {
+    let columns: u16 = gpu_table_widths(config)
+        .iter()
+        .map(|c| match c {
+            Constraint::Length(n) => n + 1,
+            _ => 0,
+        })
+        .sum();
+    columns + 2
 }
 
-fn render_memory_section(frame: &mut Frame, area: Rect, data: &DataStore) {
+fn render_memory_section(frame: &mut Frame, area: Rect, config: &Config, data: &DataStore, selected_gpu: usize) {
     let gpu_infos = data.all_gpu_info();
+    let precision = config.power_precision;
 
-    let block = Block::default()
-        .borders(Borders::ALL)
-        .title(" Memory & Power ")
-        .title_style(Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD));
+    let title = if config.no_query { " Memory & Power (disabled by --no-query) " } else { " Memory & Power " };
+    let mut block = panel()
+        .title(title)
+        .title_style(Style::default().fg(config.accent.color()).add_modifier(Modifier::BOLD));
 
     if gpu_infos.is_empty() {
         frame.render_widget(block, area);
         return;
     }
 
+    // Two rows per GPU for the block gauge, one for the thin styles; when
+    // they don't all fit, scroll to keep the GPU selected with j/k in view
+    let row_height = if config.gauge_style == GaugeStyle::Blocks { 2 } else { 1 };
+    let visible = (block.inner(area).height / row_height).max(1) as usize;
+    let selected_idx = data.gpu_indices().get(selected_gpu).copied();
+    let selected_pos = gpu_infos.iter().position(|g| Some(g.index) == selected_idx).unwrap_or(0);
+    let (offset, shown) = memory_window(gpu_infos.len(), visible, selected_pos);
+
+    let above = offset;
+    let below = gpu_infos.len() - offset - shown;
+    if above > 0 || below > 0 {
+        let mut hint = Vec::new();
+        if above > 0 {
+            hint.push(format!("↑ {} more above", above));
+        }
+        if below > 0 {
+            hint.push(format!("↓ {} more below", below));
+        }
+        block = block.title_bottom(
+            Line::from(format!(" {} ", hint.join(", "))).style(Style::default().fg(Color::Yellow)),
+        );
+    }
+
     let inner = block.inner(area);
     frame.render_widget(block, area);
 
-    // Two rows per GPU: memory bar + power info
-    let constraints: Vec<Constraint> = gpu_infos
-        .iter()
-        .map(|_| Constraint::Length(2))
+    let constraints: Vec<Constraint> = (0..shown)
+        .map(|_| Constraint::Length(row_height))
         .chain(std::iter::once(Constraint::Min(0)))
         .collect();
 
@@ -185,42 +569,39 @@ fn render_memory_section(frame: &mut Frame, area: Rect, data: &DataStore) {
         .constraints(constraints)
         .split(inner);
 
-    for (i, gpu) in gpu_infos.iter().enumerate() {
-        if i >= chunks.len() - 1 {
-            break;
-        }
-
+    for (i, gpu) in gpu_infos.iter().skip(offset).take(shown).enumerate() {
         let row_chunks = Layout::default()
             .direction(Direction::Horizontal)
             .constraints([Constraint::Length(6), Constraint::Min(20), Constraint::Length(25)])
             .split(chunks[i]);
 
         // GPU label
+        let label_color = if gpu.error.is_some() { Color::Red } else { Color::White };
         let label = Paragraph::new(format!("GPU{}", gpu.index))
-            .style(Style::default().fg(Color::White).add_modifier(Modifier::BOLD));
+            .style(Style::default().fg(label_color).add_modifier(Modifier::BOLD));
         frame.render_widget(label, row_chunks[0]);
 
         // Memory gauge
         let used = gpu.memory_used_mib;
         let total = gpu.memory_total_mib;
-        let pct = if total > 0 { (used as f64 / total as f64 * 100.0) as u16 } else { 0 };
-
-        let gauge = Gauge::default()
-            .gauge_style(Style::default().fg(COLOR_BAR).bg(Color::DarkGray))
-            .percent(pct)
-            .label(format!("{}/{} MiB", used, total));
-        frame.render_widget(gauge, row_chunks[1]);
+        let pct = if total > 0 { used as f64 / total as f64 * 100.0 } else { 0.0 };
+        let mut label = format!("{}/{} MiB", used, total);
+        if let Some(unaccounted) = data.unaccounted_vram(gpu.index).filter(|&m| m >= UNACCOUNTED_VRAM_MIB) {
+            label.push_str(&format!(", {} in no listed process", format_vram(unaccounted)));
+        }
+        render_memory_gauge(frame, row_chunks[1], pct, label, config.gauge_style);
 
         // Power/temp info
+        let power_color = config.power_color(gpu.power_draw_w, gpu.power_limit_w).unwrap_or(Color::White);
         let power_str = gpu.power_draw_w
-            .map(|p| format!("{:.0}W", p))
+            .map(|p| format_power(p as f64, precision))
             .unwrap_or("-".into());
         let temp_str = gpu.temperature_c
             .map(|t| format!("{}°C", t))
             .unwrap_or("-".into());
 
         let info = Paragraph::new(Line::from(vec![
-            Span::styled(power_str, Style::default().fg(Color::White)),
+            Span::styled(power_str, Style::default().fg(power_color)),
             Span::raw(" "),
             Span::styled(temp_str, Style::default().fg(Color::White)),
         ]));
@@ -228,9 +609,27 @@ fn render_memory_section(frame: &mut Frame, area: Rect, data: &DataStore) {
     }
 }
 
-fn format_vram(mib: u64) -> String {
-    if mib >= 1024 {
-        format!("{:.1} GiB", mib as f64 / 1024.0)
+/// First row and row count of a `visible`-row window over `total` GPUs that
+/// keeps `selected` in view
+fn memory_window(total: usize, visible: usize, selected: usize) -> (usize, usize) {
+    let shown = total.min(visible);
+    let offset = (selected + 1).saturating_sub(shown).min(total - shown);
+    (offset, shown)
+}
+
+pub fn format_vram(mib: u64) -> String {
+    format_vram_as(mib, VramUnit::Auto, 1)
+}
+
+/// Format VRAM in a fixed or automatic unit, with `precision` decimals for GiB
+pub fn format_vram_as(mib: u64, unit: VramUnit, precision: usize) -> String {
+    let gib = match unit {
+        VramUnit::Auto => mib >= 1024,
+        VramUnit::Mib => false,
+        VramUnit::Gib => true,
+    };
+    if gib {
+        format!("{:.*} GiB", precision, mib as f64 / 1024.0)
     } else {
         format!("{} MiB", mib)
     }
@@ -244,74 +643,199 @@ fn format_ram(mb: u64) -> String {
     }
 }
 
-fn render_processes_section(frame: &mut Frame, area: Rect, data: &DataStore) {
-    let processes = data.get_enriched_processes();
-
-    let header_cells = ["GPU", "PID", "VRAM", "SM%", "CPU%", "RAM", "Time", "Command"]
-        .iter()
-        .map(|h| Cell::from(*h).style(Style::default().fg(COLOR_HEADER).add_modifier(Modifier::BOLD)));
+fn render_processes_section(
+    frame: &mut Frame,
+    area: Rect,
+    data: &DataStore,
+    selected_process: Option<usize>,
+    config: &Config,
+) {
+    let merge_pids = config.merge_pids;
+    let processes = data.get_process_rows(merge_pids, config.hide_idle);
+
+    // Only hosts running containers get the column; host processes show "-"
+    let show_container = processes.iter().any(|p| p.container.is_some());
+    // Likewise for encoder sessions, which only some GPUs and drivers report
+    let show_nvenc = processes.iter().any(|p| p.enc_sessions.unwrap_or(0) > 0);
+
+    let gpu_header = if merge_pids { "GPUs" } else { "GPU" };
+    let mut headers = vec![gpu_header, "PID", "VRAM", "SM%", "MemBW%"];
+    if show_nvenc {
+        headers.push("NVENC");
+    }
+    let time_header = match config.time_format {
+        TimeFormat::Etime => "Time",
+        TimeFormat::Relative => "Runtime",
+        TimeFormat::Started => "Started",
+    };
+    headers.extend(["CPU%", "RAM", time_header]);
+    if show_container {
+        headers.push("Container");
+    }
+    headers.push("Command");
+    let header_cells = headers
+        .into_iter()
+        .map(|h| Cell::from(h).style(Style::default().fg(config.accent.color()).add_modifier(Modifier::BOLD)));
     let header = Row::new(header_cells).height(1).bottom_margin(0);
 
-    let rows: Vec<Row> = processes
+    // "?" for processes only pmon reports
+    let vram_text = |mib: Option<u64>| mib.map_or("?".into(), |m| format_vram_as(m, config.vram_unit, config.vram_precision));
+    // Wide enough for the longest value, since fixed units and extra
+    // decimals can outgrow "12.3 GiB"
+    let vram_width = processes
         .iter()
-        .map(|p| {
-            // VRAM - always show actual allocation
-            let vram_str = format_vram(p.vram_mib);
-
-            // SM utilization from pmon (instantaneous - may be "-" when idle)
-            let sm_str = p.sm_util.map(|v| format!("{}%", v)).unwrap_or("-".into());
-
-            // CPU and RAM from /proc
-            let cpu_str = if p.cpu_percent > 0.0 {
-                format!("{:.1}%", p.cpu_percent)
-            } else {
-                "-".into()
-            };
-            let ram_str = if p.rss_mb > 0 {
-                format_ram(p.rss_mb)
-            } else {
-                "-".into()
-            };
-
-            Row::new(vec![
-                Cell::from(format!("{}", p.gpu_idx)),
-                Cell::from(format!("{}", p.pid)),
-                Cell::from(vram_str).style(Style::default().fg(COLOR_HIGHLIGHT)),
-                Cell::from(sm_str).style(Style::default().fg(Color::Green)),
-                Cell::from(cpu_str),
-                Cell::from(ram_str),
-                Cell::from(p.elapsed.clone()).style(Style::default().fg(Color::Gray)),
-                Cell::from(p.command.clone()),
-            ])
-            .height(1)
-        })
-        .collect();
+        .map(|p| vram_text(p.vram_mib).len() as u16)
+        .max()
+        .unwrap_or(0)
+        .max(9);
 
-    let widths = [
-        Constraint::Length(4),   // GPU
+    let mut widths = vec![
+        Constraint::Length(if merge_pids { 8 } else { 4 }),   // GPU(s)
         Constraint::Length(7),   // PID
-        Constraint::Length(9),   // VRAM
+        Constraint::Length(vram_width),   // VRAM
         Constraint::Length(5),   // SM%
+        Constraint::Length(7),   // MemBW%
+    ];
+    if show_nvenc {
+        widths.push(Constraint::Length(5)); // NVENC sessions
+    }
+    widths.extend([
         Constraint::Length(6),   // CPU%
         Constraint::Length(6),   // RAM
-        Constraint::Length(8),   // Time
-        Constraint::Min(12),     // Command
-    ];
+        Constraint::Length(if config.time_format == TimeFormat::Started { 12 } else { 8 }), // Time
 
-    let title = if processes.is_empty() {
-        " Processes (none) "
-    } else {
-        " Processes "
+    ]);
+    if show_container {
+        widths.push(Constraint::Length(12)); // Container (short id)
+    }
+    widths.push(Constraint::Min(12)); // Command
+    // Commands get whatever the fixed columns and their one-cell gaps leave,
+    // cut with an ellipsis rather than silently at the border
+    let fixed: u16 = widths
+        .iter()
+        .map(|w| match w {
+            Constraint::Length(n) => n + 1,
+            _ => 0,
+        })
+        .sum();
+    let command_width = panel().inner(area).width.saturating_sub(fixed).max(12) as usize;
+
+    // Merged rows can span GPUs, so grouping only applies to per-GPU rows
+    let grouped = config.group_by_gpu && !merge_pids;
+    let mut rows: Vec<Row> = Vec::new();
+    let mut current_gpu = None;
+
+    for (i, p) in processes.iter().enumerate() {
+        if grouped && current_gpu != Some(p.gpu_idx) {
+            current_gpu = Some(p.gpu_idx);
+            // Rows are sorted by GPU, so the group is the run that starts here
+            let total: u64 = processes[i..]
+                .iter()
+                .take_while(|q| q.gpu_idx == p.gpu_idx)
+                .filter_map(|q| q.vram_mib)
+                .sum();
+            let subheader = Style::default().fg(config.accent.color()).add_modifier(Modifier::BOLD);
+            rows.push(Row::new(vec![
+                Cell::from(if is_ascii() { "--" } else { "──" }).style(subheader),
+                Cell::from(format!("GPU {}", p.gpu_idx)).style(subheader),
+                Cell::from(vram_text(Some(total))).style(Style::default().fg(COLOR_HIGHLIGHT)),
+            ]));
+        }
+
+        // VRAM - always show actual allocation
+        let vram_str = vram_text(p.vram_mib);
+
+        // SM utilization from pmon (instantaneous - may be "-" when idle)
+        let sm_str = p.sm_util.map(|v| format!("{}%", v)).unwrap_or("-".into());
+        let mem_bw_str = p.mem_util.map(|v| format!("{}%", v)).unwrap_or("-".into());
+
+        // CPU and RAM from /proc
+        let cpu_str = if p.cpu_percent > 0.0 {
+            format!("{:.1}%", p.cpu_percent)
+        } else {
+            "-".into()
+        };
+        let ram_str = if p.rss_mb > 0 {
+            format_ram(p.rss_mb)
+        } else {
+            "-".into()
+        };
+
+        // Over --proc-vram-warn: the whole row turns red to stand out on a
+        // shared card
+        let vram_warn = config.proc_vram_warn_mib.is_some_and(|warn| p.vram_mib.is_some_and(|m| m >= warn));
+        let mut row_style = if selected_process == Some(i) {
+            Style::default().bg(Color::DarkGray)
+        } else {
+            Style::default()
+        };
+        let mut vram_style = Style::default().fg(COLOR_HIGHLIGHT);
+        if vram_warn {
+            row_style = row_style.fg(Color::Red);
+            vram_style = Style::default().fg(Color::Red).add_modifier(Modifier::BOLD);
+        }
+
+        let mut cells = vec![
+            Cell::from(p.gpu_label()),
+            Cell::from(format!("{}", p.pid)),
+            Cell::from(vram_str).style(vram_style),
+            Cell::from(sm_str).style(Style::default().fg(Color::Green)),
+            Cell::from(mem_bw_str).style(Style::default().fg(Color::Cyan)),
+        ];
+        if show_nvenc {
+            let sessions = p.enc_sessions.filter(|&n| n > 0).map(|n| n.to_string()).unwrap_or("-".into());
+            cells.push(Cell::from(sessions).style(Style::default().fg(Color::Yellow)));
+        }
+        cells.extend([
+            Cell::from(cpu_str),
+            Cell::from(ram_str),
+            Cell::from(p.runtime_text(config.time_format)).style(Style::default().fg(Color::Gray)),
+        ]);
+        if show_container {
+            cells.push(Cell::from(p.container.clone().unwrap_or("-".into())).style(Style::default().fg(Color::Magenta)));
+        }
+        cells.push(Cell::from(truncate_to_width(p.command_text(config.command_display), command_width)));
+        rows.push(Row::new(cells).style(row_style).height(1));
+    }
+
+    let title = match (processes.is_empty(), merge_pids) {
+        // Without compute-apps only pmon's processes can be listed
+        (true, _) if config.no_query => " Processes (disabled by --no-query) ",
+        (true, _) if config.hide_idle => " Processes (none busy) ",
+        (true, _) => " Processes (none) ",
+        (false, true) => " Processes (merged by PID) ",
+        (false, false) if grouped => " Processes (by GPU) ",
+        (false, false) => " Processes ",
     };
 
     let table = Table::new(rows, widths)
         .header(header)
         .block(
-            Block::default()
-                .borders(Borders::ALL)
+            panel()
                 .title(title)
-                .title_style(Style::default().fg(COLOR_ACCENT).add_modifier(Modifier::BOLD)),
+                .title_style(Style::default().fg(config.accent.color()).add_modifier(Modifier::BOLD)),
         );
 
     frame.render_widget(table, area);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_vram_as() {
+        // Auto switches at exactly 1 GiB
+        assert_eq!(format_vram_as(1023, VramUnit::Auto, 1), "1023 MiB");
+        assert_eq!(format_vram_as(1024, VramUnit::Auto, 1), "1.0 GiB");
+        assert_eq!(format_vram_as(700, VramUnit::Auto, 2), "700 MiB");
+
+        assert_eq!(format_vram_as(700, VramUnit::Gib, 2), "0.68 GiB");
+        assert_eq!(format_vram_as(61440, VramUnit::Gib, 0), "60 GiB");
+        assert_eq!(format_vram_as(0, VramUnit::Gib, 1), "0.0 GiB");
+
+        assert_eq!(format_vram_as(61440, VramUnit::Mib, 1), "61440 MiB");
+        assert_eq!(format_vram_as(u64::from(u32::MAX), VramUnit::Mib, 1), "4294967295 MiB");
+        assert_eq!(format_vram_as(196608, VramUnit::Auto, 3), "192.000 GiB");
+    }
+}
@@ -0,0 +1,35 @@
+use ratatui::style::{Modifier, Style};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// How long a changed cell stays highlighted. dmon reports once a second,
+/// so this spans one update, like `watch -d`.
+const FLASH_DURATION: Duration = Duration::from_secs(1);
+
+/// Remembers the last rendered text per table cell and highlights cells
+/// whose value just changed
+#[derive(Debug, Default)]
+pub struct DiffHighlight {
+    // Text and when it last changed; None until it first does
+    cells: HashMap<(u32, &'static str), (String, Option<Instant>)>,
+}
+
+impl DiffHighlight {
+    /// Style for `column` of GPU `gpu_idx`, reversed if its text changed recently
+    pub fn style(&mut self, gpu_idx: u32, column: &'static str, text: &str, style: Style) -> Style {
+        let now = Instant::now();
+        let changed_at = match self.cells.get(&(gpu_idx, column)) {
+            Some((prev, at)) if prev == text => *at,
+            // First sight of a cell isn't a change
+            None => None,
+            Some(_) => Some(now),
+        };
+        self.cells.insert((gpu_idx, column), (text.to_string(), changed_at));
+
+        if changed_at.is_some_and(|at| now.duration_since(at) < FLASH_DURATION) {
+            style.add_modifier(Modifier::REVERSED)
+        } else {
+            style
+        }
+    }
+}
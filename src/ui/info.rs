@@ -2,20 +2,28 @@ use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, Paragraph},
+    widgets::{Bar, BarChart, BarGroup, Paragraph},
     Frame,
 };
 
+use crate::config::Config;
 use crate::data::DataStore;
+use crate::ui::dashboard::{format_vram, UNACCOUNTED_VRAM_MIB};
+use crate::ui::{format_clock, format_power, panel};
 
-pub fn render_info_view(frame: &mut Frame, area: Rect, data: &DataStore, selected_gpu: usize) {
+pub fn render_info_view(frame: &mut Frame, area: Rect, data: &DataStore, selected_gpu: usize, config: &Config) {
+    let (precision, ghz) = (config.power_precision, config.clock_ghz);
     let gpu_infos = data.all_gpu_info();
     let gpu_indices = data.gpu_indices();
 
     if gpu_infos.is_empty() {
-        let block = Block::default()
-            .borders(Borders::ALL)
-            .title(" GPU Info - Waiting for data... ")
+        let title = if config.no_query {
+            " GPU Info - disabled by --no-query "
+        } else {
+            " GPU Info - Waiting for data... "
+        };
+        let block = panel()
+            .title(title)
             .title_style(Style::default().fg(Color::Yellow));
         frame.render_widget(block, area);
         return;
@@ -25,8 +33,7 @@ pub fn render_info_view(frame: &mut Frame, area: Rect, data: &DataStore, selecte
     let gpu = match data.get_gpu_info(gpu_idx) {
         Some(g) => g,
         None => {
-            let block = Block::default()
-                .borders(Borders::ALL)
+            let block = panel()
                 .title(" GPU Info - No data for selected GPU ")
                 .title_style(Style::default().fg(Color::Yellow));
             frame.render_widget(block, area);
@@ -34,10 +41,9 @@ pub fn render_info_view(frame: &mut Frame, area: Rect, data: &DataStore, selecte
         }
     };
 
-    let block = Block::default()
-        .borders(Borders::ALL)
+    let block = panel()
         .title(format!(" GPU {} Info ", gpu_idx))
-        .title_style(Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD));
+        .title_style(Style::default().fg(config.accent.color()).add_modifier(Modifier::BOLD));
 
     let inner = block.inner(area);
     frame.render_widget(block, area);
@@ -47,15 +53,18 @@ pub fn render_info_view(frame: &mut Frame, area: Rect, data: &DataStore, selecte
         .direction(Direction::Vertical)
         .constraints([
             Constraint::Length(8),  // Basic info
-            Constraint::Length(6),  // Memory info
+            Constraint::Length(8),  // Memory info
             Constraint::Length(6),  // Power info
             Constraint::Length(4),  // PCIe info
+            Constraint::Length(4),  // Clocks info
+            Constraint::Length(2 + XID_LINES as u16), // Xid errors
+            Constraint::Length(6),  // SM utilization histogram
             Constraint::Min(0),     // Extra space
         ])
         .split(inner);
 
     // Basic info section
-    let basic_info = Paragraph::new(vec![
+    let mut device_lines = vec![
         Line::from(vec![
             Span::styled("Name: ", Style::default().fg(Color::DarkGray)),
             Span::styled(&gpu.name, Style::default().fg(Color::White).add_modifier(Modifier::BOLD)),
@@ -79,8 +88,14 @@ pub fn render_info_view(frame: &mut Frame, area: Rect, data: &DataStore, selecte
                 Style::default().fg(Color::White),
             ),
         ]),
-    ])
-    .block(Block::default().borders(Borders::ALL).title(" Device "));
+    ];
+    if gpu.fan_stalled() {
+        device_lines.push(Line::from(Span::styled(
+            "Fan stalled while hot - check the fan!",
+            Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+        )));
+    }
+    let basic_info = Paragraph::new(device_lines).block(panel().title(" Device "));
     frame.render_widget(basic_info, sections[0]);
 
     // Memory info section
@@ -97,6 +112,49 @@ pub fn render_info_view(frame: &mut Frame, area: Rect, data: &DataStore, selecte
         Color::Green
     };
 
+    // Used VRAM no compute-apps process claims: graphics clients, the driver,
+    // or processes in other containers that compute-apps can't see
+    let (unaccounted_text, unaccounted_color) = match data.unaccounted_vram(gpu_idx) {
+        Some(mib) if mib >= UNACCOUNTED_VRAM_MIB => (format!("{} (not in any listed process)", format_vram(mib)), Color::Yellow),
+        Some(mib) => (format_vram(mib), Color::Green),
+        None => ("N/A".to_string(), Color::DarkGray),
+    };
+
+    // BAR1 nearly full makes large pinned or peer-to-peer mappings fail or fall back
+    let (bar1_text, bar1_color) = match (gpu.bar1_used_mib, gpu.bar1_total_mib) {
+        (Some(used), Some(total)) if total > 0 => {
+            let pct = used as f64 / total as f64 * 100.0;
+            let color = if pct >= 90.0 {
+                Color::Red
+            } else if pct >= 70.0 {
+                Color::Yellow
+            } else {
+                Color::Green
+            };
+            (format!("{}/{} MiB ({:.1}%)", used, total, pct), color)
+        }
+        _ => ("N/A".to_string(), Color::DarkGray),
+    };
+
+    // Double-bit retirements mean uncorrectable errors: failing memory
+    let (retired_text, retired_color) = match (gpu.retired_pages_sbe, gpu.retired_pages_dbe) {
+        (None, None) if !gpu.retired_pages_pending => ("N/A".to_string(), Color::DarkGray),
+        (sbe, dbe) => {
+            let mut text = format!("{} single-bit, {} double-bit", sbe.unwrap_or(0), dbe.unwrap_or(0));
+            if gpu.retired_pages_pending {
+                text.push_str(" (pending)");
+            }
+            if gpu.memory_failing() {
+                text.push_str(" - memory failing!");
+                (text, Color::Red)
+            } else if sbe.is_some_and(|n| n > 0) {
+                (text, Color::Yellow)
+            } else {
+                (text, Color::Green)
+            }
+        }
+    };
+
     let mem_info = Paragraph::new(vec![
         Line::from(vec![
             Span::styled("Total: ", Style::default().fg(Color::DarkGray)),
@@ -106,41 +164,53 @@ pub fn render_info_view(frame: &mut Frame, area: Rect, data: &DataStore, selecte
             Span::styled("Used:  ", Style::default().fg(Color::DarkGray)),
             Span::styled(format!("{} MiB ({:.1}%)", gpu.memory_used_mib, mem_pct), Style::default().fg(mem_color)),
         ]),
+        Line::from(vec![
+            Span::styled("Unaccounted: ", Style::default().fg(Color::DarkGray)),
+            Span::styled(unaccounted_text, Style::default().fg(unaccounted_color)),
+        ]),
         Line::from(vec![
             Span::styled("Free:  ", Style::default().fg(Color::DarkGray)),
             Span::styled(format!("{} MiB", gpu.memory_free_mib), Style::default().fg(Color::Green)),
         ]),
+        Line::from(vec![
+            Span::styled("BAR1:  ", Style::default().fg(Color::DarkGray)),
+            Span::styled(bar1_text, Style::default().fg(bar1_color)),
+        ]),
+        Line::from(vec![
+            Span::styled("Retired pages: ", Style::default().fg(Color::DarkGray)),
+            Span::styled(retired_text, Style::default().fg(retired_color)),
+        ]),
     ])
-    .block(Block::default().borders(Borders::ALL).title(" Memory "));
+    .block(panel().title(" Memory "));
     frame.render_widget(mem_info, sections[1]);
 
     // Power info section
-    let power_pct = match (gpu.power_draw_w, gpu.power_limit_w) {
-        (Some(draw), Some(limit)) if limit > 0.0 => draw / limit * 100.0,
-        _ => 0.0,
-    };
-    let power_color = if power_pct >= 90.0 {
-        Color::Red
-    } else if power_pct >= 70.0 {
-        Color::Yellow
-    } else {
-        Color::Cyan
-    };
+    let power_color = config.power_color(gpu.power_draw_w, gpu.power_limit_w).unwrap_or(Color::Cyan);
+
+    // Memory (HBM) temperature only comes from dmon's mtemp column
+    let mem_temp = data.get_gpu(gpu_idx).and_then(|h| h.latest()).and_then(|s| s.mem_temp_c);
 
     let power_info = Paragraph::new(vec![
         Line::from(vec![
             Span::styled("Draw:  ", Style::default().fg(Color::DarkGray)),
             Span::styled(
-                gpu.power_draw_w.map(|p| format!("{:.1} W", p)).unwrap_or("N/A".into()),
+                gpu.power_draw_w.map(|p| format_power(p as f64, precision)).unwrap_or("N/A".into()),
                 Style::default().fg(power_color),
             ),
         ]),
         Line::from(vec![
             Span::styled("Limit: ", Style::default().fg(Color::DarkGray)),
             Span::styled(
-                gpu.power_limit_w.map(|p| format!("{:.1} W", p)).unwrap_or("N/A".into()),
+                gpu.power_limit_w.map(|p| format_power(p as f64, precision)).unwrap_or("N/A".into()),
                 Style::default().fg(Color::White),
             ),
+            Span::styled(
+                match (gpu.power_min_limit_w, gpu.power_max_limit_w) {
+                    (Some(min), Some(max)) => format!(" ({:.0}-{:.0}W)", min, max),
+                    _ => String::new(),
+                },
+                Style::default().fg(Color::DarkGray),
+            ),
         ]),
         Line::from(vec![
             Span::styled("Temp:  ", Style::default().fg(Color::DarkGray)),
@@ -154,8 +224,15 @@ pub fn render_info_view(frame: &mut Frame, area: Rect, data: &DataStore, selecte
                 Style::default().fg(Color::DarkGray),
             ),
         ]),
+        Line::from(vec![
+            Span::styled("HBM:   ", Style::default().fg(Color::DarkGray)),
+            Span::styled(
+                mem_temp.map(|t| format!("{}°C", t)).unwrap_or("-".into()),
+                Style::default().fg(mem_temp_color(mem_temp)),
+            ),
+        ]),
     ])
-    .block(Block::default().borders(Borders::ALL).title(" Power & Thermal "));
+    .block(panel().title(" Power & Thermal "));
     frame.render_widget(power_info, sections[2]);
 
     // PCIe info section
@@ -182,6 +259,114 @@ pub fn render_info_view(frame: &mut Frame, area: Rect, data: &DataStore, selecte
             Span::styled(")", Style::default().fg(Color::DarkGray)),
         ]),
     ])
-    .block(Block::default().borders(Borders::ALL).title(" PCIe "));
+    .block(panel().title(" PCIe "));
     frame.render_widget(pcie_info, sections[3]);
+    // Clocks section: current vs max boost, flagged when well below max
+    let clock_color = match gpu.sm_clock_pct() {
+        Some(pct) if pct < 50.0 => Color::Yellow,
+        Some(_) => Color::Green,
+        None => Color::White,
+    };
+    let fmt_clock = |cur: Option<u32>, max: Option<u32>| -> String {
+        if ghz {
+            let fmt = |v: Option<u32>| v.map(|v| format_clock(v, true)).unwrap_or("N/A".into());
+            return format!("{} / {}", fmt(cur), fmt(max));
+        }
+        let cur = cur.map(|c| c.to_string()).unwrap_or("N/A".into());
+        let max = max.map(|m| m.to_string()).unwrap_or("N/A".into());
+        format!("{} / {} MHz", cur, max)
+    };
+
+    let clocks_info = Paragraph::new(vec![
+        Line::from(vec![
+            Span::styled("SM: ", Style::default().fg(Color::DarkGray)),
+            Span::styled(
+                fmt_clock(gpu.clock_sm_mhz, gpu.clock_max_sm_mhz),
+                Style::default().fg(clock_color),
+            ),
+            Span::styled(
+                gpu.sm_clock_pct().map(|p| format!(" ({:.0}%)", p)).unwrap_or_default(),
+                Style::default().fg(clock_color),
+            ),
+            Span::styled("  Mem: ", Style::default().fg(Color::DarkGray)),
+            Span::styled(
+                fmt_clock(gpu.clock_mem_mhz, gpu.clock_max_mem_mhz),
+                Style::default().fg(Color::White),
+            ),
+        ]),
+    ])
+    .block(panel().title(" Clocks "));
+    frame.render_widget(clocks_info, sections[4]);
+
+    // Most recent Xid errors from the kernel log (--xid-dmesg)
+    let xid_lines: Vec<Line> = match data.xid_events(gpu_idx) {
+        None => vec![Line::from(Span::styled(
+            "Kernel log not read (start with --xid-dmesg)",
+            Style::default().fg(Color::DarkGray),
+        ))],
+        Some(events) if events.is_empty() => {
+            vec![Line::from(Span::styled("None in the kernel log", Style::default().fg(Color::Green)))]
+        }
+        Some(events) => events
+            .iter()
+            .rev()
+            .take(XID_LINES)
+            .map(|e| {
+                Line::from(vec![
+                    Span::styled(format!("Xid {:<3} ", e.code), Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)),
+                    Span::styled(e.detail.as_str(), Style::default().fg(Color::White)),
+                ])
+            })
+            .collect(),
+    };
+    let xid_title = match data.xid_events(gpu_idx) {
+        Some(events) if events.len() > XID_LINES => format!(" Xid Errors ({}, latest first) ", events.len()),
+        _ => " Xid Errors ".to_string(),
+    };
+    frame.render_widget(Paragraph::new(xid_lines).block(panel().title(xid_title)), sections[5]);
+
+    // How the retained samples spread over SM utilization: one bar per 10%
+    if let Some(history) = data.get_gpu(gpu_idx) {
+        let buckets = history.sm_histogram();
+        let total: u32 = buckets.iter().sum();
+        let bars: Vec<Bar> = buckets
+            .iter()
+            .enumerate()
+            .map(|(i, &count)| {
+                let color = match i {
+                    0..=4 => Color::Green,
+                    5..=7 => Color::Yellow,
+                    _ => Color::Red,
+                };
+                Bar::default()
+                    .value(count as u64)
+                    .text_value(String::new())
+                    .label(Line::from(format!("{}", i * 10)))
+                    .style(Style::default().fg(color))
+            })
+            .collect();
+        let histogram = BarChart::default()
+            .block(panel().title(format!(" SM% Distribution ({} samples) ", total)))
+            .data(BarGroup::default().bars(&bars))
+            .bar_width(3)
+            .bar_gap(1);
+        frame.render_widget(histogram, sections[6]);
+    }
+}
+
+/// Xid errors listed in the info overlay
+const XID_LINES: usize = 3;
+
+/// HBM temperatures at which the memory starts to throttle on current
+/// data-center parts (H100 HBM3 is rated to 95°C)
+const MEM_TEMP_WARN_C: u32 = 85;
+const MEM_TEMP_CRIT_C: u32 = 95;
+
+fn mem_temp_color(temp: Option<u32>) -> Color {
+    match temp {
+        Some(t) if t >= MEM_TEMP_CRIT_C => Color::Red,
+        Some(t) if t >= MEM_TEMP_WARN_C => Color::Yellow,
+        Some(_) => Color::White,
+        None => Color::DarkGray,
+    }
 }
@@ -0,0 +1,37 @@
+use ratatui::{
+    layout::Rect,
+    style::{Color, Style},
+    text::{Line, Span},
+    widgets::Paragraph,
+    Frame,
+};
+
+use crate::events::{EventLog, LogLevel};
+use crate::ui::panel;
+
+pub fn render_log_view(frame: &mut Frame, area: Rect, log: &EventLog) {
+    if log.is_empty() {
+        let block = panel()
+            .title(" Event Log - Nothing yet ")
+            .title_style(Style::default().fg(Color::Yellow));
+        frame.render_widget(block, area);
+        return;
+    }
+
+    let lines: Vec<Line> = log
+        .newest_first()
+        .map(|entry| {
+            let color = match entry.level {
+                LogLevel::Info => Color::White,
+                LogLevel::Warn => Color::Red,
+            };
+            Line::from(vec![
+                Span::styled(entry.time.format("%H:%M:%S ").to_string(), Style::default().fg(Color::DarkGray)),
+                Span::styled(entry.message.clone(), Style::default().fg(color)),
+            ])
+        })
+        .collect();
+
+    let log_view = Paragraph::new(lines).block(panel().title(format!(" Event Log ({}) ", log.len())));
+    frame.render_widget(log_view, area);
+}
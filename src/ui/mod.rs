@@ -1,5 +1,148 @@
 pub mod charts;
+pub mod cluster;
 pub mod dashboard;
+pub mod diff;
 pub mod info;
+pub mod log;
+pub mod process_detail;
+pub mod rack;
+pub mod recap;
 pub mod status;
 pub mod topology;
+pub mod users;
+
+use std::sync::OnceLock;
+
+use ratatui::buffer::Buffer;
+use ratatui::style::{Color, Modifier};
+use ratatui::widgets::{Block, BorderType, Borders};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+use crate::config::BorderPreset;
+
+/// Chosen once at startup; every panel reads it through `panel()`
+static BORDER_PRESET: OnceLock<BorderPreset> = OnceLock::new();
+
+pub fn set_border_preset(preset: BorderPreset) {
+    let _ = BORDER_PRESET.set(preset);
+}
+
+/// Glyphs for sparklines and text gauges
+pub struct Charset {
+    /// Eight levels, lowest first
+    pub sparkline: [char; 8],
+    pub gauge_fill: char,
+    pub gauge_empty: char,
+}
+
+const UNICODE_CHARSET: Charset = Charset {
+    sparkline: ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'],
+    gauge_fill: '#',
+    gauge_empty: '·',
+};
+
+/// For serial consoles and fonts without block elements (`--ascii`)
+const ASCII_CHARSET: Charset = Charset {
+    sparkline: ['.', ':', '-', '=', '+', '*', '#', '@'],
+    gauge_fill: '#',
+    gauge_empty: '.',
+};
+
+/// Chosen once at startup, like the border preset
+static ASCII: OnceLock<bool> = OnceLock::new();
+
+pub fn set_ascii(ascii: bool) {
+    let _ = ASCII.set(ascii);
+}
+
+pub fn is_ascii() -> bool {
+    ASCII.get().copied().unwrap_or(false)
+}
+
+/// No colors (`NO_COLOR`, `TERM=dumb` or `--no-color`); chosen once at startup
+static MONOCHROME: OnceLock<bool> = OnceLock::new();
+
+pub fn set_monochrome(monochrome: bool) {
+    let _ = MONOCHROME.set(monochrome);
+}
+
+pub fn is_monochrome() -> bool {
+    MONOCHROME.get().copied().unwrap_or(false)
+}
+
+/// Replace the colors of a drawn frame with modifiers, so every view stays
+/// legible without color: backgrounds (selection, tabs, badges) become
+/// reverse video, red (errors, critical readings) bold and grays dim
+pub fn strip_colors(buf: &mut Buffer) {
+    for cell in buf.content.iter_mut() {
+        let style = cell.style();
+        let mut modifier = style.add_modifier;
+        if style.bg.is_some_and(|c| c != Color::Reset) {
+            modifier |= Modifier::REVERSED;
+        }
+        match style.fg {
+            Some(Color::Red | Color::LightRed) => modifier |= Modifier::BOLD,
+            Some(Color::DarkGray | Color::Gray) => modifier |= Modifier::DIM,
+            _ => {}
+        }
+        cell.fg = Color::Reset;
+        cell.bg = Color::Reset;
+        cell.modifier = modifier;
+    }
+}
+
+pub fn charset() -> &'static Charset {
+    if is_ascii() {
+        &ASCII_CHARSET
+    } else {
+        &UNICODE_CHARSET
+    }
+}
+
+/// A bordered panel in the configured border style
+pub fn panel() -> Block<'static> {
+    let block = Block::default();
+    match BORDER_PRESET.get().copied().unwrap_or_default() {
+        BorderPreset::Plain => block.borders(Borders::ALL),
+        BorderPreset::Rounded => block.borders(Borders::ALL).border_type(BorderType::Rounded),
+        BorderPreset::Double => block.borders(Borders::ALL).border_type(BorderType::Double),
+        BorderPreset::None => block,
+    }
+}
+
+/// Format a power reading with a fixed number of decimals, e.g. "62W" or "62.3W"
+pub fn format_power(watts: f64, precision: usize) -> String {
+    format!("{:.*}W", precision, watts)
+}
+
+/// Cut `s` to at most `width` terminal columns, marking the cut with an
+/// ellipsis ("..." in `--ascii` mode)
+pub fn truncate_to_width(s: &str, width: usize) -> String {
+    if s.width() <= width {
+        return s.to_string();
+    }
+    let ellipsis = if is_ascii() { "..." } else { "…" };
+    let budget = width.saturating_sub(ellipsis.width());
+    let mut out = String::new();
+    let mut used = 0;
+    for c in s.chars() {
+        let w = c.width().unwrap_or(0);
+        if used + w > budget {
+            break;
+        }
+        used += w;
+        out.push(c);
+    }
+    out.push_str(ellipsis);
+    out
+}
+
+/// Format a clock, switching to GHz from 1000 MHz up when `ghz` is set,
+/// e.g. "810 MHz" or "1.53 GHz"
+pub fn format_clock(mhz: u32, ghz: bool) -> String {
+    if ghz && mhz >= 1000 {
+        format!("{:.2} GHz", mhz as f64 / 1000.0)
+    } else {
+        format!("{} MHz", mhz)
+    }
+}
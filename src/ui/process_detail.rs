@@ -0,0 +1,180 @@
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::Paragraph,
+    Frame,
+};
+
+use crate::data::{DataStore, UNKNOWN_USER};
+use crate::config::{CommandDisplay, Config, TimeFormat};
+use crate::ui::panel;
+
+pub fn render_process_detail(frame: &mut Frame, area: Rect, data: &DataStore, config: &Config, selected_process: usize) {
+    let processes = data.get_process_rows(config.merge_pids, config.hide_idle);
+
+    let process = match processes.get(selected_process) {
+        Some(p) => p,
+        None => {
+            let block = panel()
+                .title(" Process - No longer running ")
+                .title_style(Style::default().fg(Color::Yellow));
+            frame.render_widget(block, area);
+            return;
+        }
+    };
+
+    let block = panel()
+        .title(format!(" PID {} ", process.pid))
+        .title_style(Style::default().fg(config.accent.color()).add_modifier(Modifier::BOLD));
+
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let sections = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(6),  // Process info
+            Constraint::Length(6),  // Resource usage
+            Constraint::Length(4),  // Affinity hint
+            Constraint::Min(0),     // Extra space
+        ])
+        .split(inner);
+
+    let mut gpu_line = vec![
+        Span::styled("GPU:     ", Style::default().fg(Color::DarkGray)),
+        Span::styled(process.gpu_label(), Style::default().fg(Color::Cyan)),
+    ];
+    gpu_line.extend([
+        Span::styled("  User: ", Style::default().fg(Color::DarkGray)),
+        Span::styled(process.user.as_deref().unwrap_or(UNKNOWN_USER), Style::default().fg(Color::White)),
+    ]);
+    if let Some(container) = &process.container {
+        gpu_line.extend([
+            Span::styled("  Container: ", Style::default().fg(Color::DarkGray)),
+            Span::styled(container.as_str(), Style::default().fg(Color::Magenta)),
+        ]);
+    }
+    if let Some(sessions) = process.enc_sessions.filter(|&n| n > 0) {
+        gpu_line.extend([
+            Span::styled("  NVENC sessions: ", Style::default().fg(Color::DarkGray)),
+            Span::styled(sessions.to_string(), Style::default().fg(Color::Yellow)),
+        ]);
+    }
+
+    // Process info section
+    let process_info = Paragraph::new(vec![
+        Line::from(vec![
+            Span::styled("Command: ", Style::default().fg(Color::DarkGray)),
+            Span::styled(&process.command, Style::default().fg(Color::White).add_modifier(Modifier::BOLD)),
+        ]),
+        Line::from(vec![
+            Span::styled("Cmdline: ", Style::default().fg(Color::DarkGray)),
+            Span::styled(process.command_text(CommandDisplay::Args), Style::default().fg(Color::White)),
+        ]),
+        Line::from(gpu_line),
+        Line::from(vec![
+            Span::styled("Runtime: ", Style::default().fg(Color::DarkGray)),
+            Span::styled(
+                if process.elapsed.is_empty() { "N/A" } else { &process.elapsed },
+                Style::default().fg(Color::White),
+            ),
+            Span::styled(
+                match process.runtime {
+                    Some(_) => format!(" (started {})", process.runtime_text(TimeFormat::Started)),
+                    None => String::new(),
+                },
+                Style::default().fg(Color::DarkGray),
+            ),
+        ]),
+    ])
+    .block(panel().title(" Process "));
+    frame.render_widget(process_info, sections[0]);
+
+    // Resource usage section
+    let usage_info = Paragraph::new(vec![
+        Line::from(vec![
+            Span::styled("VRAM: ", Style::default().fg(Color::DarkGray)),
+            Span::styled(
+                process.vram_mib.map_or("? (not in compute-apps)".into(), |v| format!("{} MiB", v)),
+                Style::default().fg(Color::Cyan),
+            ),
+            Span::styled("  SM: ", Style::default().fg(Color::DarkGray)),
+            Span::styled(
+                process.sm_util.map(|v| format!("{}%", v)).unwrap_or("N/A".into()),
+                Style::default().fg(Color::Green),
+            ),
+            Span::styled("  MemBW: ", Style::default().fg(Color::DarkGray)),
+            Span::styled(
+                process.mem_util.map(|v| format!("{}%", v)).unwrap_or("N/A".into()),
+                Style::default().fg(Color::Cyan),
+            ),
+        ]),
+        Line::from(vec![
+            Span::styled("CPU:  ", Style::default().fg(Color::DarkGray)),
+            Span::styled(format!("{:.1}%", process.cpu_percent), Style::default().fg(Color::White)),
+        ]),
+        Line::from(vec![
+            Span::styled("RAM:  ", Style::default().fg(Color::DarkGray)),
+            Span::styled(format!("{} MiB", process.rss_mb), Style::default().fg(Color::White)),
+        ]),
+        clocks_line(data, process.gpu_idx),
+    ])
+    .block(panel().title(" Usage "));
+    frame.render_widget(usage_info, sections[1]);
+
+    // CPU affinity hint from the topology matrix
+    let affinity = data
+        .get_topology()
+        .and_then(|t| t.cpu_affinity_for(process.gpu_idx));
+    let hint = match affinity {
+        Some(cpus) => Line::from(vec![
+            Span::styled("Pin to CPUs ", Style::default().fg(Color::DarkGray)),
+            Span::styled(cpus, Style::default().fg(Color::Green)),
+            Span::styled(" (", Style::default().fg(Color::DarkGray)),
+            Span::styled(format!("numactl -C {}", cpus), Style::default().fg(Color::Yellow)),
+            Span::styled(")", Style::default().fg(Color::DarkGray)),
+        ]),
+        None => Line::from(Span::styled(
+            "No CPU affinity reported for this GPU",
+            Style::default().fg(Color::DarkGray),
+        )),
+    };
+    let affinity_info = Paragraph::new(vec![hint])
+        .block(panel().title(" CPU Affinity "));
+    frame.render_widget(affinity_info, sections[2]);
+}
+
+/// Current SM/memory clocks of the process's GPU, against the max clocks when
+/// known, so a busy process on a downclocked GPU stands out
+fn clocks_line(data: &DataStore, gpu_idx: u32) -> Line<'static> {
+    let latest = data.get_gpu(gpu_idx).and_then(|h| h.latest());
+    let info = data.get_gpu_info(gpu_idx);
+    let sm = latest.and_then(|s| s.gpu_clock_mhz);
+    let max_sm = info.and_then(|i| i.clock_max_sm_mhz);
+    let clock = |cur: Option<u32>, max: Option<u32>| match (cur, max) {
+        (Some(cur), Some(max)) => format!("{}/{} MHz", cur, max),
+        (Some(cur), None) => format!("{} MHz", cur),
+        (None, _) => "N/A".into(),
+    };
+    // Busy but below half the boost clock: likely power or thermal capped
+    let busy = latest.and_then(|s| s.sm_util).unwrap_or(0) > 0;
+    let sm_color = match (sm, max_sm) {
+        (Some(cur), Some(max)) if busy && cur * 2 < max => Color::Yellow,
+        _ => Color::White,
+    };
+
+    Line::from(vec![
+        Span::styled("Clk:  ", Style::default().fg(Color::DarkGray)),
+        Span::styled("SM ", Style::default().fg(Color::DarkGray)),
+        Span::styled(
+            clock(sm, max_sm),
+            Style::default().fg(sm_color),
+        ),
+        Span::styled("  Mem ", Style::default().fg(Color::DarkGray)),
+        Span::styled(
+            clock(latest.and_then(|s| s.mem_clock_mhz), info.and_then(|i| i.clock_max_mem_mhz)),
+            Style::default().fg(Color::White),
+        ),
+    ])
+}
@@ -0,0 +1,89 @@
+use ratatui::{
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::Paragraph,
+    Frame,
+};
+
+use crate::config::Config;
+use crate::data::DataStore;
+use crate::ui::dashboard::{sparkline_level, temp_color, util_color};
+use crate::ui::{charset, format_power, panel};
+
+/// Columns per GPU entry, e.g. "G12 ▆ 62%  78°C  310W" and a gap
+const ENTRY_WIDTH: u16 = 24;
+
+/// Every GPU as one short line, packed into as many columns as fit: SM
+/// utilization as a single level character and percentage, temperature
+/// and power, each colored by its thresholds. For 16-GPU nodes, where the
+/// dashboard's table scrolls
+pub fn render_rack_view(frame: &mut Frame, area: Rect, config: &Config, data: &DataStore, selected_gpu: usize) {
+    let gpu_indices = data.gpu_indices();
+    let block = panel()
+        .title(format!(" Rack ({} GPUs) ", gpu_indices.len()))
+        .title_style(Style::default().fg(config.accent.color()).add_modifier(Modifier::BOLD));
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let columns = (inner.width / ENTRY_WIDTH).max(1) as usize;
+    // Labels padded to the widest index so the metrics line up
+    let label_width = gpu_indices.last().map_or(2, |idx| idx.to_string().len() + 1);
+
+    let lines: Vec<Line> = gpu_indices
+        .chunks(columns)
+        .enumerate()
+        .map(|(row, chunk)| {
+            let mut spans = Vec::new();
+            for (col, &idx) in chunk.iter().enumerate() {
+                let selected = row * columns + col == selected_gpu;
+                spans.extend(rack_entry(config, data, idx, label_width, selected));
+                spans.push(Span::raw("  "));
+            }
+            Line::from(spans)
+        })
+        .collect();
+
+    frame.render_widget(Paragraph::new(lines), inner);
+}
+
+fn rack_entry(config: &Config, data: &DataStore, idx: u32, label_width: usize, selected: bool) -> Vec<Span<'static>> {
+    let latest = data.get_gpu(idx).and_then(|h| h.latest());
+    let info = data.get_gpu_info(idx);
+
+    let mut label_style = Style::default().fg(Color::White).add_modifier(Modifier::BOLD);
+    if info.is_some_and(|g| g.error.is_some()) {
+        label_style = label_style.fg(Color::Red);
+    }
+    if selected {
+        label_style = label_style.add_modifier(Modifier::REVERSED);
+    }
+    let label = Span::styled(format!("{:<width$}", format!("G{}", idx), width = label_width), label_style);
+
+    let Some(sample) = latest else {
+        return vec![label, Span::styled("  no data", Style::default().fg(Color::DarkGray))];
+    };
+
+    let dim = Style::default().fg(Color::DarkGray);
+    let (level, sm) = match sample.sm_util {
+        Some(sm) => {
+            let style = Style::default().fg(util_color(sm as f64));
+            let level = charset().sparkline[sparkline_level(sm as f64, 100.0)].to_string();
+            (Span::styled(level, style), Span::styled(format!("{:>4}", format!("{}%", sm)), style))
+        }
+        None => (Span::styled(" ", dim), Span::styled(format!("{:>4}", "-"), dim)),
+    };
+    let temp = match sample.gpu_temp_c {
+        Some(t) => Span::styled(format!("{:>5}", format!("{}°C", t)), Style::default().fg(temp_color(t))),
+        None => Span::styled(format!("{:>5}", "-"), dim),
+    };
+    let power = match sample.power_w {
+        Some(w) => {
+            let color = config.power_color(Some(w as f32), info.and_then(|g| g.power_limit_w)).unwrap_or(Color::White);
+            Span::styled(format!("{:>6}", format_power(w as f64, 0)), Style::default().fg(color))
+        }
+        None => Span::styled(format!("{:>6}", "-"), dim),
+    };
+
+    vec![label, Span::raw(" "), level, sm, Span::raw(" "), temp, power]
+}
@@ -0,0 +1,119 @@
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+use ratatui::{
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::Paragraph,
+    Frame,
+};
+
+use crate::data::DataStore;
+use crate::ui::panel;
+
+/// Peak readings for one GPU while the display was paused
+pub struct GpuPeak {
+    pub gpu_idx: u32,
+    pub temp_c: Option<u32>,
+    pub power_w: Option<u32>,
+}
+
+/// What happened while the display was paused
+pub struct PauseRecap {
+    pub duration: Duration,
+    pub peaks: Vec<GpuPeak>,
+    /// (pid, command) of processes that appeared during the pause
+    pub started: Vec<(u32, String)>,
+    /// (pid, command) of processes that went away during the pause
+    pub ended: Vec<(u32, String)>,
+}
+
+impl PauseRecap {
+    /// Compare the store frozen at pause time with the live one
+    pub fn new(before: &DataStore, after: &DataStore, since: Instant) -> Self {
+        let peaks = after
+            .gpu_indices()
+            .into_iter()
+            .filter_map(|idx| after.get_gpu(idx).map(|h| (idx, h)))
+            .map(|(gpu_idx, history)| GpuPeak {
+                gpu_idx,
+                temp_c: history.peak_since(since, |s| s.gpu_temp_c),
+                power_w: history.peak_since(since, |s| s.power_w),
+            })
+            .collect();
+
+        let pids = |data: &DataStore| -> BTreeMap<u32, String> {
+            data.get_enriched_processes(false)
+                .into_iter()
+                .map(|p| (p.pid, p.command))
+                .collect()
+        };
+        let (old, new) = (pids(before), pids(after));
+        let diff = |a: &BTreeMap<u32, String>, b: &BTreeMap<u32, String>| {
+            a.iter()
+                .filter(|(pid, _)| !b.contains_key(pid))
+                .map(|(pid, cmd)| (*pid, cmd.clone()))
+                .collect()
+        };
+
+        Self {
+            duration: since.elapsed(),
+            peaks,
+            started: diff(&new, &old),
+            ended: diff(&old, &new),
+        }
+    }
+}
+
+pub fn render_pause_recap(frame: &mut Frame, area: Rect, recap: &PauseRecap, accent: Color) {
+    let label = Style::default().fg(Color::DarkGray);
+    let heading = Style::default().fg(accent).add_modifier(Modifier::BOLD);
+    let fmt = |v: Option<u32>, unit: &str| v.map(|v| format!("{}{}", v, unit)).unwrap_or("-".into());
+
+    let mut lines = vec![
+        Line::from(vec![
+            Span::styled("Paused for ", label),
+            Span::styled(format!("{}s", recap.duration.as_secs()), Style::default().fg(Color::White)),
+        ]),
+        Line::from(""),
+        Line::from(Span::styled("Peaks", heading)),
+    ];
+
+    if recap.peaks.is_empty() {
+        lines.push(Line::from(Span::styled("No samples received", label)));
+    }
+    for peak in &recap.peaks {
+        let temp_color = match peak.temp_c {
+            Some(t) if t >= crate::alert::CRIT_TEMP_C => Color::Red,
+            _ => Color::White,
+        };
+        lines.push(Line::from(vec![
+            Span::styled(format!("GPU{}  ", peak.gpu_idx), Style::default().fg(Color::Yellow)),
+            Span::styled("Temp ", label),
+            Span::styled(fmt(peak.temp_c, "°C"), Style::default().fg(temp_color)),
+            Span::styled("  Power ", label),
+            Span::styled(fmt(peak.power_w, "W"), Style::default().fg(Color::White)),
+        ]));
+    }
+
+    for (title, procs, color) in [
+        ("Started", &recap.started, Color::Green),
+        ("Ended", &recap.ended, Color::Red),
+    ] {
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(title, heading)));
+        if procs.is_empty() {
+            lines.push(Line::from(Span::styled("none", label)));
+        }
+        for (pid, command) in procs {
+            lines.push(Line::from(vec![
+                Span::styled(format!("{:>7}  ", pid), Style::default().fg(color)),
+                Span::raw(command.clone()),
+            ]));
+        }
+    }
+
+    let recap_view = Paragraph::new(lines).block(panel().title(" While Paused "));
+    frame.render_widget(recap_view, area);
+}
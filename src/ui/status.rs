@@ -1,5 +1,5 @@
 use ratatui::{
-    layout::Rect,
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span},
     widgets::Paragraph,
@@ -8,11 +8,13 @@ use ratatui::{
 
 use crate::app::ViewMode;
 
-// Use standard terminal colors
-const COLOR_KEY: Color = Color::Cyan;
+// Use standard terminal colors; keys and the active tab use the accent
 const COLOR_DANGER: Color = Color::LightRed;
 
-fn format_duration(duration: std::time::Duration) -> String {
+/// Spinner frames; one step per sample received
+const SPINNER: [char; 4] = ['|', '/', '-', '\\'];
+
+pub fn format_duration(duration: std::time::Duration) -> String {
     let secs = duration.as_secs();
     if secs < 60 {
         format!("{}s", secs)
@@ -23,65 +25,174 @@ fn format_duration(duration: std::time::Duration) -> String {
     }
 }
 
-pub fn render_status_bar(
-    frame: &mut Frame,
-    area: Rect,
-    samples: u64,
-    uptime: std::time::Duration,
-    view_mode: &ViewMode,
-    error: Option<&str>,
-) {
+/// Everything shown in the top status bar
+pub struct StatusInfo<'a> {
+    pub samples: u64,
+    pub uptime: std::time::Duration,
+    pub view_mode: &'a ViewMode,
+    /// Recent errors, newest first
+    pub errors: &'a [&'a str],
+    pub notice: Option<&'a str>,
+    /// Hostname and wall-clock time, when enabled
+    pub host_clock: Option<(&'a str, &'a str)>,
+    /// Driver and CUDA versions, e.g. "Driver 550.54 / CUDA 12.4"
+    pub versions: Option<&'a str>,
+    pub accent: Color,
+    /// Display is frozen while monitoring continues
+    pub paused: bool,
+    /// Samples received so far, shown as a spinner so a live feed of
+    /// unchanging values is distinguishable from a stalled one
+    pub activity: usize,
+    /// Total power averaged over `--avg-window`, with the window length
+    /// and the power text, e.g. ("30s", "412W")
+    pub power_avg: Option<(&'a str, &'a str)>,
+}
+
+pub fn render_status_bar(frame: &mut Frame, area: Rect, status: &StatusInfo) {
+    let StatusInfo {
+        samples,
+        uptime,
+        view_mode,
+        errors,
+        notice,
+        host_clock,
+        versions,
+        accent,
+        paused,
+        activity,
+        power_avg,
+    } = *status;
+
     let uptime_str = format_duration(uptime);
 
-    let status_text = if let Some(err) = error {
+    let status_text = if !errors.is_empty() {
+        let label = match errors.len() {
+            1 => "ERROR: ".to_string(),
+            n => format!("ERRORS ({}): ", n),
+        };
         vec![
-            Span::styled("ERROR: ", Style::default().fg(COLOR_DANGER).add_modifier(Modifier::BOLD)),
-            Span::styled(err, Style::default().fg(COLOR_DANGER)),
+            Span::styled(label, Style::default().fg(COLOR_DANGER).add_modifier(Modifier::BOLD)),
+            Span::styled(errors.join(" | "), Style::default().fg(COLOR_DANGER)),
             Span::raw("  "),
         ]
-    } else {
+    } else if let Some(notice) = notice {
         vec![
+            Span::styled(notice, Style::default().fg(Color::Yellow)),
+            Span::raw("  "),
+        ]
+    } else {
+        let mut spans = vec![
             Span::styled("Samples: ", Style::default().fg(Color::Gray)),
             Span::styled(format!("{}", samples), Style::default().fg(Color::White)),
             Span::raw(" | "),
             Span::styled("Uptime: ", Style::default().fg(Color::Gray)),
             Span::styled(uptime_str, Style::default().fg(Color::White)),
-            Span::raw("  "),
-        ]
+        ];
+        if let Some((window, power)) = power_avg {
+            spans.extend([
+                Span::raw(" | "),
+                Span::styled(format!("Avg {}: ", window), Style::default().fg(Color::Gray)),
+                Span::styled(power, Style::default().fg(Color::White)),
+            ]);
+        }
+        spans.push(Span::raw("  "));
+        spans
     };
 
     // Tab indicators
     let mut tabs = Vec::new();
     for (i, mode) in ViewMode::all().iter().enumerate() {
         let style = if view_mode == mode {
-            Style::default().fg(Color::Black).bg(Color::Cyan).add_modifier(Modifier::BOLD)
+            Style::default().fg(Color::Black).bg(accent).add_modifier(Modifier::BOLD)
         } else {
             Style::default().fg(Color::Gray)
         };
         tabs.push(Span::styled(format!(" [{}]{} ", i + 1, mode.name()), style));
     }
 
-    let mut spans = status_text;
+    let spinner = if activity == 0 { ' ' } else { SPINNER[activity % SPINNER.len()] };
+    let mut spans = vec![Span::styled(format!("{} ", spinner), Style::default().fg(Color::Green))];
+    if paused {
+        spans.push(Span::styled(
+            "PAUSED ",
+            Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD),
+        ));
+    }
+    spans.extend(status_text);
     spans.extend(tabs);
 
+    // Versions, hostname and wall clock, right-aligned so they stay clear of the tabs
+    let mut right = Vec::new();
+    if let Some(versions) = versions {
+        right.push(Span::styled(versions, Style::default().fg(Color::DarkGray)));
+    }
+    if let Some((host, clock)) = host_clock {
+        if !right.is_empty() {
+            right.push(Span::raw("  "));
+        }
+        right.extend([
+            Span::styled(host, Style::default().fg(Color::White)),
+            Span::raw(" "),
+            Span::styled(clock, Style::default().fg(Color::Gray)),
+        ]);
+    }
+    let area = if right.is_empty() {
+        area
+    } else {
+        let right = Line::from(right);
+        let chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Min(0), Constraint::Length(right.width() as u16)])
+            .split(area);
+        frame.render_widget(Paragraph::new(right).alignment(Alignment::Right), chunks[1]);
+        chunks[0]
+    };
+
     let status = Paragraph::new(Line::from(spans));
     frame.render_widget(status, area);
 }
 
-pub fn render_help_bar(frame: &mut Frame, area: Rect) {
-    let help = Paragraph::new(Line::from(vec![
-        Span::styled("[q]", Style::default().fg(COLOR_KEY)),
+pub fn render_help_bar(frame: &mut Frame, area: Rect, view_mode: &ViewMode, accent: Color) {
+    let key = Style::default().fg(accent);
+    let mut spans = vec![
+        Span::styled("[q]", key),
         Span::raw(" quit  "),
-        Span::styled("[Tab]", Style::default().fg(COLOR_KEY)),
+        Span::styled("[Tab]", key),
         Span::raw(" switch  "),
-        Span::styled("[j/k]", Style::default().fg(COLOR_KEY)),
+        Span::styled("[j/k]", key),
         Span::raw(" select  "),
-        Span::styled("[i]", Style::default().fg(COLOR_KEY)),
+        Span::styled("[p]", key),
+        Span::raw(" processes  "),
+        Span::styled("[y]", key),
+        Span::raw(" copy  "),
+        Span::styled("[Space]", key),
+        Span::raw(" pause  "),
+        Span::styled("[i]", key),
         Span::raw(" info  "),
-        Span::styled("[t]", Style::default().fg(COLOR_KEY)),
-        Span::raw(" topology"),
-    ]))
-    .style(Style::default().fg(Color::DarkGray));
+        Span::styled("[t]", key),
+        Span::raw(" topology  "),
+        Span::styled("[l]", key),
+        Span::raw(" log  "),
+        Span::styled("[u]", key),
+        Span::raw(" users"),
+    ];
+
+    if view_mode.has_charts() {
+        spans.extend([
+            Span::raw("  "),
+            Span::styled("[c]", key),
+            Span::raw(" chart  "),
+            Span::styled("[m/M]", key),
+            Span::raw(" metric  "),
+            Span::styled("[o]", key),
+            Span::raw(" all GPUs  "),
+            Span::styled("[←/→]", key),
+            Span::raw(" cursor"),
+        ]);
+    }
+
+    let help = Paragraph::new(Line::from(spans))
+        .style(Style::default().fg(Color::DarkGray));
 
     frame.render_widget(help, area);
 }
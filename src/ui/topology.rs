@@ -1,20 +1,22 @@
 use ratatui::{
+    buffer::Buffer,
     layout::{Constraint, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, Cell, Paragraph, Row, Table},
+    widgets::{Cell, Paragraph, Row, Table, Widget},
     Frame,
 };
 
 use crate::data::DataStore;
-use crate::parser::GpuLink;
+use crate::output::buffer_text;
+use crate::parser::{GpuLink, GpuTopology};
+use crate::ui::panel;
 
-pub fn render_topology_view(frame: &mut Frame, area: Rect, data: &DataStore) {
+pub fn render_topology_view(frame: &mut Frame, area: Rect, data: &DataStore, accent: Color) {
     let topo = match data.get_topology() {
         Some(t) => t,
         None => {
-            let block = Block::default()
-                .borders(Borders::ALL)
+            let block = panel()
                 .title(" GPU Topology - No data ")
                 .title_style(Style::default().fg(Color::Yellow));
             frame.render_widget(block, area);
@@ -23,22 +25,51 @@ pub fn render_topology_view(frame: &mut Frame, area: Rect, data: &DataStore) {
     };
 
     if topo.matrix.is_empty() {
-        let block = Block::default()
-            .borders(Borders::ALL)
+        let block = panel()
             .title(" GPU Topology - No GPUs found ")
             .title_style(Style::default().fg(Color::Yellow));
         frame.render_widget(block, area);
         return;
     }
 
-    let block = Block::default()
-        .borders(Borders::ALL)
+    let block = panel()
         .title(" GPU Topology ")
-        .title_style(Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD));
+        .title_style(Style::default().fg(accent).add_modifier(Modifier::BOLD));
 
     let inner = block.inner(area);
     frame.render_widget(block, area);
 
+    // Split area for table and legend
+    let chunks = ratatui::layout::Layout::default()
+        .direction(ratatui::layout::Direction::Vertical)
+        .constraints([Constraint::Min(10), Constraint::Length(6)])
+        .split(inner);
+
+    frame.render_widget(topology_table(topo), chunks[0]);
+    frame.render_widget(Paragraph::new(legend_lines()), chunks[1]);
+}
+
+/// The topology as plain aligned text: the same table and legend as the
+/// overlay, drawn into an off-screen buffer, for pasting into run logs
+pub fn topology_text(topo: &GpuTopology) -> String {
+    let legend = legend_lines();
+    let table_width: u16 = column_widths(topo.matrix.len()).iter().map(|w| w + 1).sum();
+    let width = legend.iter().map(|l| l.width() as u16).max().unwrap_or(0).max(table_width);
+    // Header, its margin, one row per GPU, then a blank line
+    let table_height = topo.matrix.len() as u16 + 3;
+    let area = Rect::new(0, 0, width, table_height + legend.len() as u16);
+
+    let mut buf = Buffer::empty(area);
+    let (table_area, legend_area) = (
+        Rect { height: table_height, ..area },
+        Rect { y: table_height, height: legend.len() as u16, ..area },
+    );
+    Widget::render(topology_table(topo), table_area, &mut buf);
+    Widget::render(Paragraph::new(legend), legend_area, &mut buf);
+    buffer_text(&buf, false)
+}
+
+fn topology_table(topo: &GpuTopology) -> Table<'static> {
     // Build header row
     let mut header_cells = vec![Cell::from("").style(Style::default())];
     for i in 0..topo.matrix.len() {
@@ -63,50 +94,42 @@ pub fn render_topology_view(frame: &mut Frame, area: Rect, data: &DataStore) {
 
         for link in row.iter() {
             let (text, style) = match link {
-                Some(GpuLink::Self_) => ("X", Style::default().fg(Color::DarkGray)),
-                Some(GpuLink::PIX) => ("PIX", Style::default().fg(Color::Green)),
-                Some(GpuLink::PXB) => ("PXB", Style::default().fg(Color::Yellow)),
-                Some(GpuLink::PHB) => ("PHB", Style::default().fg(Color::Yellow)),
-                Some(GpuLink::NODE) => ("NODE", Style::default().fg(Color::Cyan)),
-                Some(GpuLink::SYS) => ("SYS", Style::default().fg(Color::Red)),
-                Some(GpuLink::NVLink(n)) => {
-                    // NVLink is fastest - format as NVx
-                    (Box::leak(format!("NV{}", n).into_boxed_str()) as &str, Style::default().fg(Color::Magenta))
-                }
-                None => ("-", Style::default().fg(Color::DarkGray)),
+                Some(GpuLink::Self_) => ("X".to_string(), Style::default().fg(Color::DarkGray)),
+                Some(GpuLink::PIX) => ("PIX".to_string(), Style::default().fg(Color::Green)),
+                Some(GpuLink::PXB) => ("PXB".to_string(), Style::default().fg(Color::Yellow)),
+                Some(GpuLink::PHB) => ("PHB".to_string(), Style::default().fg(Color::Yellow)),
+                Some(GpuLink::NODE) => ("NODE".to_string(), Style::default().fg(Color::Cyan)),
+                Some(GpuLink::SYS) => ("SYS".to_string(), Style::default().fg(Color::Red)),
+                // NVLink is fastest - format as NVx, brighter with more links
+                Some(link @ GpuLink::NVLink(n)) => (format!("NV{}", n), nvlink_style(link.nvlink_tier())),
+                None => ("-".to_string(), Style::default().fg(Color::DarkGray)),
             };
             cells.push(Cell::from(text).style(style));
         }
 
         // Add CPU/NUMA affinity
-        let cpu_aff = topo.cpu_affinity.get(i).map(|s| s.as_str()).unwrap_or("-");
-        let numa_aff = topo.numa_affinity.get(i).map(|s| s.as_str()).unwrap_or("-");
+        let cpu_aff = topo.cpu_affinity.get(i).cloned().unwrap_or_else(|| "-".into());
+        let numa_aff = topo.numa_affinity.get(i).cloned().unwrap_or_else(|| "-".into());
         cells.push(Cell::from(cpu_aff).style(Style::default().fg(Color::DarkGray)));
         cells.push(Cell::from(numa_aff).style(Style::default().fg(Color::DarkGray)));
 
         rows.push(Row::new(cells).height(1));
     }
 
-    // Build constraints
-    let mut widths = vec![Constraint::Length(5)]; // Row label
-    for _ in 0..topo.matrix.len() {
-        widths.push(Constraint::Length(5)); // GPU columns
-    }
-    widths.push(Constraint::Length(16)); // CPU Affinity
-    widths.push(Constraint::Length(6));  // NUMA
-
-    let table = Table::new(rows, widths).header(header);
-
-    // Split area for table and legend
-    let chunks = ratatui::layout::Layout::default()
-        .direction(ratatui::layout::Direction::Vertical)
-        .constraints([Constraint::Min(10), Constraint::Length(6)])
-        .split(inner);
+    let widths = column_widths(topo.matrix.len()).into_iter().map(Constraint::Length);
+    Table::new(rows, widths).header(header)
+}
 
-    frame.render_widget(table, chunks[0]);
+/// Row label, one column per GPU, CPU affinity and NUMA
+fn column_widths(gpus: usize) -> Vec<u16> {
+    let mut widths = vec![5];
+    widths.extend(std::iter::repeat_n(5, gpus));
+    widths.extend([16, 6]);
+    widths
+}
 
-    // Legend
-    let legend = Paragraph::new(vec![
+fn legend_lines() -> Vec<Line<'static>> {
+    vec![
         Line::from(vec![
             Span::styled("Legend: ", Style::default().fg(Color::White).add_modifier(Modifier::BOLD)),
         ]),
@@ -124,9 +147,21 @@ pub fn render_topology_view(frame: &mut Frame, area: Rect, data: &DataStore) {
             Span::styled("SYS", Style::default().fg(Color::Red)),
             Span::raw(" = Cross NUMA (slow)  "),
             Span::styled("NVx", Style::default().fg(Color::Magenta)),
-            Span::raw(" = NVLink (fastest)"),
+            Span::raw(" = NVLink (fastest): "),
+            Span::styled("NV1-2", nvlink_style(Some(1))),
+            Span::raw(" "),
+            Span::styled("NV3-7", nvlink_style(Some(2))),
+            Span::raw(" "),
+            Span::styled("NV8+", nvlink_style(Some(3))),
         ]),
-    ]);
+    ]
+}
 
-    frame.render_widget(legend, chunks[1]);
+/// NVLink cells by bandwidth tier: more links, brighter
+fn nvlink_style(tier: Option<u8>) -> Style {
+    match tier {
+        Some(3) => Style::default().fg(Color::LightMagenta).add_modifier(Modifier::BOLD),
+        Some(2) => Style::default().fg(Color::LightMagenta),
+        _ => Style::default().fg(Color::Magenta),
+    }
 }
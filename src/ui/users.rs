@@ -0,0 +1,51 @@
+use ratatui::{
+    layout::{Constraint, Rect},
+    style::{Color, Modifier, Style},
+    widgets::{Cell, Row, Table},
+    Frame,
+};
+
+use crate::config::Config;
+use crate::data::{DataStore, UNKNOWN_USER};
+use crate::ui::dashboard::format_vram;
+use crate::ui::panel;
+
+/// GPU processes grouped by owner: who is using the node, and how much
+pub fn render_users_view(frame: &mut Frame, area: Rect, data: &DataStore, config: &Config) {
+    let users = data.usage_by_user();
+    if users.is_empty() {
+        let title = if config.no_query { " Users - disabled by --no-query " } else { " Users - No GPU processes " };
+        let block = panel()
+            .title(title)
+            .title_style(Style::default().fg(Color::Yellow));
+        frame.render_widget(block, area);
+        return;
+    }
+
+    let header_style = Style::default().fg(config.accent.color()).add_modifier(Modifier::BOLD);
+    let header = Row::new(["User", "Procs", "GPUs", "VRAM"].map(|h| Cell::from(h).style(header_style)));
+
+    let rows: Vec<Row> = users
+        .iter()
+        .map(|u| {
+            let user_style = if u.user == UNKNOWN_USER {
+                Style::default().fg(Color::DarkGray)
+            } else {
+                Style::default().fg(Color::White).add_modifier(Modifier::BOLD)
+            };
+            let gpus: Vec<String> = u.gpus.iter().map(|g| g.to_string()).collect();
+            Row::new(vec![
+                Cell::from(u.user.clone()).style(user_style),
+                Cell::from(u.processes.to_string()),
+                Cell::from(format!("{} ({})", u.gpus.len(), gpus.join(","))),
+                Cell::from(format_vram(u.vram_mib)).style(Style::default().fg(Color::Cyan)),
+            ])
+        })
+        .collect();
+
+    let widths = [Constraint::Length(20), Constraint::Length(6), Constraint::Min(12), Constraint::Length(10)];
+    let table = Table::new(rows, widths)
+        .header(header)
+        .block(panel().title(format!(" Users ({}) ", users.len())));
+    frame.render_widget(table, area);
+}